@@ -1,119 +1,144 @@
 // D:/Work/Projects/Modding/Skyrim/dds-edit/src/cli/cli_args.rs
 
-use crate::structs::struct_config::Config;
+use crate::{
+    cli::cli_command::Command,
+    structs::{
+        struct_channelop::ChannelOp,
+        struct_config::Config,
+        struct_ddsconfig::{
+            validate_alpha_test_ref, DdsFormat, DdsQuality, MipFilter, MipmapSetting,
+        },
+        struct_gameprofile::GameProfile,
+        struct_normalgen::NormalGenConfig,
+        struct_outputlayout::{validate_name_template, OutputLayout},
+        struct_packageconfig::PackageConfig,
+        struct_resizeconfig::{ResizeFilter, SharpenConfig},
+        struct_sizespec::{Pow2Policy, SizeSpec, UpscalePolicy},
+    },
+};
 use std::env;
 
+#[allow(clippy::module_inception)]
 pub mod cli_args {
     use super::*;
 
-    /// Parses command-line arguments and returns a `Config` struct.
+    /// Parsed command line: the subcommand and the configuration for it.
+    pub struct CliArgs {
+        /// The subcommand to run.
+        pub command: Command,
+
+        /// Configuration built from the command-line options.
+        pub config: Config,
+
+        /// Help text to print instead of running the command, if help was requested.
+        pub help: Option<&'static str>,
+    }
+
+    /// Parses the command-line arguments of the process into a subcommand and its `Config`.
     ///
     /// # Returns
     ///
-    /// * `Ok(Config)` if parsing succeeds.
+    /// * `Ok(CliArgs)` if parsing succeeds.
     /// * `Err(String)` with an error message if parsing fails.
-    pub fn parse_arguments() -> Result<Config, String> {
-        let args: Vec<String> = env::args().collect();
+    pub fn parse_arguments() -> Result<CliArgs, String> {
+        parse_args(&env::args().collect::<Vec<_>>())
+    }
+
+    /// Parses command-line arguments into a subcommand and its `Config`.
+    ///
+    /// The first argument selects the subcommand (`convert`, `info`, `decode`,
+    /// `verify` or `help`). If it is not a subcommand, `convert` is assumed so
+    /// that existing invocations keep working.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - The arguments, starting with the program name.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(CliArgs)` if parsing succeeds.
+    /// * `Err(String)` with an error message if parsing fails.
+    fn parse_args(args: &[String]) -> Result<CliArgs, String> {
         let mut config = Config::default();
 
-        let mut i = 1; // Start from 1 to skip the program name
+        // 'help [COMMAND]' prints the help of a command, or the list of commands.
+        if args.get(1).map(String::as_str) == Some("help") {
+            let help = match args.get(2) {
+                Some(name) => Command::from_name(name)
+                    .ok_or_else(|| format!("Unknown command '{}'", name))?
+                    .help(),
+                None => Command::general_help(),
+            };
+            return Ok(CliArgs {
+                command: Command::Convert,
+                config,
+                help: Some(help),
+            });
+        }
+
+        // Select the subcommand, defaulting to 'convert'.
+        let (command, mut i) = match args.get(1).and_then(|arg| Command::from_name(arg)) {
+            Some(command) => (command, 2),
+            None => (Command::Convert, 1), // Start from 1 to skip the program name
+        };
+        if command == Command::Decode {
+            config.decode_only = true;
+        }
+
         while i < args.len() {
             let arg = &args[i];
 
-            if arg.starts_with("--") {
-                // Handle long options
-                if let Some((key, value)) = parse_long_option(arg) {
-                    match key.as_str() {
-                        "pathout" => {
-                            config.path_out = value.ok_or("Missing value for --pathout")?;
-                        }
-                        "maxdepth" => {
-                            let val = value.ok_or("Missing value for --maxdepth")?;
-                            config.max_depth = val.parse::<usize>().map_err(|_| "Invalid value for --maxdepth")?;
-                        }
-                        "target-widths" => {
-                            let val = value.ok_or("Missing value for --target-widths")?;
-                            config.target_sizes = parse_target_widths(&val)?;
-                        }
-                        "threads" => {
-                            let val = value.ok_or("Missing value for --threads")?;
-                            config.threads = val.parse::<usize>().map_err(|_| "Invalid value for --threads")?;
-                        }
-                        "useconfig" => {
-                            config.config_file = Some(value.ok_or("Missing value for --useconfig")?);
-                        }
-                        _ => return Err(format!("Unknown option '{}'", arg)),
+            if arg == "-h" || arg == "--help" {
+                return Ok(CliArgs {
+                    command,
+                    config,
+                    help: Some(command.help()),
+                });
+            }
+
+            if let Some((key, value)) = parse_long_option(arg) {
+                if !command.accepts(&key) {
+                    return Err(format!("Unknown option '--{}' for '{}'", key, command));
+                }
+
+                // Handle boolean flags like '--decode'
+                if apply_flag(&mut config, &key) {
+                    if value.is_some() {
+                        return Err(format!("Option '--{}' takes no value", key));
                     }
-                } else {
-                    // If there's no '=', the value is in the next argument
-                    let key = arg.trim_start_matches("--");
                     i += 1;
-                    if i >= args.len() {
-                        return Err(format!("Missing value for '--{}'", key));
-                    }
-                    let value = &args[i];
-                    match key {
-                        "pathout" => {
-                            config.path_out = value.clone();
-                        }
-                        "maxdepth" => {
-                            config.max_depth = value.parse::<usize>().map_err(|_| "Invalid value for --maxdepth")?;
-                        }
-                        "target-widths" => {
-                            config.target_sizes = parse_target_widths(value)?;
-                        }
-                        "threads" => {
-                            config.threads = value.parse::<usize>().map_err(|_| "Invalid value for --threads")?;
-                        }
-                        "useconfig" => {
-                            config.config_file = Some(value.clone());
+                    continue;
+                }
+
+                // Handle long options as '--key=value' or '--key value'
+                let value = match value {
+                    Some(value) => value,
+                    None => {
+                        i += 1;
+                        if i >= args.len() {
+                            return Err(format!("Missing value for '--{}'", key));
                         }
-                        _ => return Err(format!("Unknown option '--{}'", key)),
+                        args[i].clone()
                     }
+                };
+                if !apply_option(&mut config, &key, &value, &format!("--{}", key))? {
+                    return Err(format!("Unknown option '--{}'", key));
                 }
             } else if arg.starts_with("-") {
                 // Handle short options like -o, -d, -t, -n, -c
                 let chars: Vec<char> = arg.chars().collect();
                 let mut j = 1;
                 while j < chars.len() {
-                    match chars[j] {
-                        'o' => {
-                            i += 1;
-                            if i >= args.len() {
-                                return Err("Missing value for -o".to_string());
-                            }
-                            config.path_out = args[i].clone();
-                        }
-                        'd' => {
-                            i += 1;
-                            if i >= args.len() {
-                                return Err("Missing value for -d".to_string());
-                            }
-                            config.max_depth = args[i].parse::<usize>().map_err(|_| "Invalid value for -d")?;
-                        }
-                        't' => {
-                            i += 1;
-                            if i >= args.len() {
-                                return Err("Missing value for -t".to_string());
-                            }
-                            config.target_sizes = parse_target_widths(&args[i])?;
-                        }
-                        'n' => {
-                            i += 1;
-                            if i >= args.len() {
-                                return Err("Missing value for -n".to_string());
-                            }
-                            config.threads = args[i].parse::<usize>().map_err(|_| "Invalid value for -n")?;
-                        }
-                        'c' => {
-                            i += 1;
-                            if i >= args.len() {
-                                return Err("Missing value for -c".to_string());
-                            }
-                            config.config_file = Some(args[i].clone());
-                        }
-                        _ => return Err(format!("Unknown option '-{}'", chars[j])),
+                    let key = short_option_key(chars[j])
+                        .filter(|key| command.accepts(key))
+                        .ok_or_else(|| {
+                            format!("Unknown option '-{}' for '{}'", chars[j], command)
+                        })?;
+                    i += 1;
+                    if i >= args.len() {
+                        return Err(format!("Missing value for -{}", chars[j]));
                     }
+                    apply_option(&mut config, key, &args[i], &format!("-{}", chars[j]))?;
                     j += 1;
                 }
             } else {
@@ -132,7 +157,169 @@ pub mod cli_args {
             return Err("Input path is required.".to_string());
         }
 
-        Ok(config)
+        Ok(CliArgs {
+            command,
+            config,
+            help: None,
+        })
+    }
+
+    /// Applies a single option value to the configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The `Config` to update.
+    /// * `key` - The long option name without dashes (e.g., `pathout`).
+    /// * `value` - The option value.
+    /// * `flag` - The option as written on the command line, used in error messages.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` if the option was applied.
+    /// * `Ok(false)` if the option is unknown.
+    /// * `Err(String)` with an error message if the value is invalid.
+    fn apply_option(
+        config: &mut Config,
+        key: &str,
+        value: &str,
+        flag: &str,
+    ) -> Result<bool, String> {
+        match key {
+            "pathout" => {
+                config.path_out = value.to_string();
+            }
+            "maxdepth" => {
+                config.max_depth = value
+                    .parse::<usize>()
+                    .map_err(|_| format!("Invalid value for {}", flag))?;
+            }
+            "target-widths" | "target-sizes" => {
+                config.target_sizes = parse_target_sizes(value)?;
+            }
+            "layout" => {
+                config.layout = value.parse::<OutputLayout>()?;
+            }
+            "name" | "name-template" => {
+                validate_name_template(value)?;
+                config.name_template = Some(value.to_string());
+            }
+            "upscale" => {
+                config.upscale = value.parse::<UpscalePolicy>()?;
+            }
+            "upscale-filter" => {
+                config.upscale_filter = value.parse::<ResizeFilter>()?;
+            }
+            "pow2" => {
+                config.pow2 = value.parse::<Pow2Policy>()?;
+            }
+            "threads" => {
+                config.threads = value
+                    .parse::<usize>()
+                    .map_err(|_| format!("Invalid value for {}", flag))?;
+            }
+            "watch-debounce" => {
+                config.watch_debounce_ms = value
+                    .parse::<u64>()
+                    .map_err(|_| format!("Invalid value for {}", flag))?;
+            }
+            "quality" => {
+                config.quality = value.parse::<DdsQuality>()?;
+            }
+            "mipmaps" => {
+                config.mipmaps = value.parse::<MipmapSetting>()?;
+            }
+            "resize-filter" => {
+                config.resize_filter = value.parse::<ResizeFilter>()?;
+            }
+            "sharpen" => {
+                let amount = value
+                    .parse::<f32>()
+                    .map_err(|_| format!("Invalid value for {}", flag))?;
+                let sharpen = SharpenConfig {
+                    amount,
+                    ..SharpenConfig::default()
+                };
+                sharpen.validate()?;
+                config.sharpen = Some(sharpen);
+            }
+            "mip-filter" => {
+                config.mip_filter = value.parse::<MipFilter>()?;
+            }
+            "alpha-test-ref" => {
+                let value = value
+                    .parse::<f32>()
+                    .map_err(|_| format!("Invalid value for {}", flag))?;
+                config.alpha_test_ref = Some(validate_alpha_test_ref(value)?);
+            }
+            "format" => {
+                config.format = Some(value.parse::<DdsFormat>()?);
+            }
+            "channel-ops" => {
+                config.channel_ops = value
+                    .split(',')
+                    .map(|op| op.parse::<ChannelOp>())
+                    .collect::<Result<_, _>>()?;
+            }
+            "game" => {
+                config.game = value.parse::<GameProfile>()?;
+            }
+            "useconfig" => {
+                config.config_file = Some(value.to_string());
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Applies a boolean flag that takes no value to the configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The `Config` to update.
+    /// * `key` - The long flag name without dashes (e.g., `decode`).
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the flag was applied, `false` if `key` is not a flag.
+    fn apply_flag(config: &mut Config, key: &str) -> bool {
+        match key {
+            "decode" => config.decode_only = true,
+            "keep-intermediate" => config.keep_intermediate = true,
+            "force" => config.force = true,
+            "watch" => config.watch = true,
+            "no-renormalize" => config.renormalize_normals = false,
+            "no-multiple-of-4" => config.multiple_of_4 = false,
+            "normal-from-height" => config.normal_from_height = Some(NormalGenConfig::default()),
+            "package" => config.package = Some(PackageConfig::default()),
+            "json" => config.json_output = true,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Maps a short option character to its long option name.
+    ///
+    /// # Arguments
+    ///
+    /// * `short` - The short option character (e.g., `o`).
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&str)` with the long option name if the short option is known.
+    /// * `None` if the short option is unknown.
+    fn short_option_key(short: char) -> Option<&'static str> {
+        match short {
+            'o' => Some("pathout"),
+            'd' => Some("maxdepth"),
+            't' => Some("target-widths"),
+            'n' => Some("threads"),
+            'q' => Some("quality"),
+            'm' => Some("mipmaps"),
+            'f' => Some("format"),
+            'g' => Some("game"),
+            'c' => Some("useconfig"),
+            _ => None,
+        }
     }
 
     /// Parses a long option in the format '--key=value'.
@@ -147,30 +334,141 @@ pub mod cli_args {
     /// * `Some((key, None))` if only a key is found.
     /// * `None` if the argument is not a long option.
     fn parse_long_option(arg: &str) -> Option<(String, Option<String>)> {
-        if arg.starts_with("--") {
-            let mut parts = arg[2..].splitn(2, '=');
-            let key = parts.next()?.to_string();
-            let value = parts.next().map(|s| s.to_string());
-            Some((key, value))
-        } else {
-            None
-        }
+        let mut parts = arg.strip_prefix("--")?.splitn(2, '=');
+        let key = parts.next()?.to_string();
+        let value = parts.next().map(|s| s.to_string());
+        Some((key, value))
     }
 
-    /// Parses a comma-separated list of target widths into a vector of u32.
+    /// Parses a comma-separated list of target sizes (e.g., `1024,h:512,50%`).
     ///
     /// # Arguments
     ///
-    /// * `widths_str` - A string slice containing comma-separated widths.
+    /// * `sizes_str` - A string slice containing comma-separated size specs.
     ///
     /// # Returns
     ///
-    /// * `Ok(Vec<u32>)` if parsing succeeds.
+    /// * `Ok(Vec<SizeSpec>)` if parsing succeeds.
     /// * `Err(String)` with an error message if parsing fails.
-    fn parse_target_widths(widths_str: &str) -> Result<Vec<u32>, String> {
-        widths_str
+    fn parse_target_sizes(sizes_str: &str) -> Result<Vec<SizeSpec>, String> {
+        sizes_str
             .split(',')
-            .map(|s| s.trim().parse::<u32>().map_err(|_| format!("Invalid target width '{}'", s.trim())))
+            .map(|s| s.parse::<SizeSpec>())
             .collect()
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn parse(args: &[&str]) -> Result<CliArgs, String> {
+            let args: Vec<String> = std::iter::once("dds-edit")
+                .chain(args.iter().copied())
+                .map(String::from)
+                .collect();
+            parse_args(&args)
+        }
+
+        fn parse_error(args: &[&str]) -> String {
+            match parse(args) {
+                Ok(_) => panic!("{:?} parsed without an error", args),
+                Err(e) => e,
+            }
+        }
+
+        #[test]
+        fn dispatches_to_subcommands() {
+            for (name, command) in [
+                ("convert", Command::Convert),
+                ("info", Command::Info),
+                ("decode", Command::Decode),
+                ("verify", Command::Verify),
+            ] {
+                let cli = parse(&[name, "textures"]).unwrap();
+                assert_eq!(cli.command, command);
+                assert_eq!(cli.config.path_in, "textures");
+                assert!(cli.help.is_none());
+            }
+            assert!(parse(&["decode", "textures"]).unwrap().config.decode_only);
+        }
+
+        #[test]
+        fn invocations_without_a_subcommand_convert() {
+            let cli = parse(&[
+                "textures",
+                "-o",
+                "out",
+                "--target-widths=1024,512",
+                "--force",
+            ])
+            .unwrap();
+            assert_eq!(cli.command, Command::Convert);
+            assert_eq!(cli.config.path_in, "textures");
+            assert_eq!(cli.config.path_out, "out");
+            assert_eq!(
+                cli.config.target_sizes,
+                [SizeSpec::Width(1024), SizeSpec::Width(512)]
+            );
+            assert!(cli.config.force);
+        }
+
+        #[test]
+        fn options_accept_both_value_forms() {
+            let cli = parse(&["convert", "textures", "--threads", "8", "--quality=fast"]).unwrap();
+            assert_eq!(cli.config.threads, 8);
+            assert_eq!(cli.config.quality, DdsQuality::Fast);
+        }
+
+        #[test]
+        fn help_is_returned_instead_of_running() {
+            let cli = parse(&["help", "info"]).unwrap();
+            assert_eq!(cli.help, Some(Command::Info.help()));
+            let cli = parse(&["help"]).unwrap();
+            assert_eq!(cli.help, Some(Command::general_help()));
+            let cli = parse(&["verify", "--help"]).unwrap();
+            assert_eq!(cli.help, Some(Command::Verify.help()));
+            assert_eq!(parse_error(&["help", "resize"]), "Unknown command 'resize'");
+        }
+
+        #[test]
+        fn rejects_options_of_other_subcommands() {
+            assert_eq!(
+                parse_error(&["info", "textures", "--threads", "8"]),
+                "Unknown option '--threads' for 'info'"
+            );
+            assert_eq!(
+                parse_error(&["decode", "textures", "-q", "fast"]),
+                "Unknown option '-q' for 'decode'"
+            );
+            assert_eq!(
+                parse_error(&["textures", "--bogus"]),
+                "Unknown option '--bogus' for 'convert'"
+            );
+        }
+
+        #[test]
+        fn rejects_values_for_flags() {
+            assert_eq!(
+                parse_error(&["textures", "--force=yes"]),
+                "Option '--force' takes no value"
+            );
+            assert_eq!(
+                parse_error(&["info", "textures", "--json=true"]),
+                "Option '--json' takes no value"
+            );
+        }
+
+        #[test]
+        fn reports_missing_values_and_inputs() {
+            assert_eq!(
+                parse_error(&["textures", "--threads"]),
+                "Missing value for '--threads'"
+            );
+            assert_eq!(parse_error(&["convert"]), "Input path is required.");
+            assert_eq!(
+                parse_error(&["textures", "more"]),
+                "Unexpected positional argument 'more'"
+            );
+        }
+    }
 }
@@ -0,0 +1,246 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/cli/cli_command.rs
+
+/// Subcommands of the command-line interface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// Resize images and convert them to DDS (the default).
+    Convert,
+
+    /// Print header details of DDS and image files.
+    Info,
+
+    /// Decode DDS files to PNG.
+    Decode,
+
+    /// Check converted outputs against their inputs.
+    Verify,
+}
+
+/// Help text listing all subcommands.
+const GENERAL_HELP: &str = "\
+Usage: dds-edit <COMMAND> [OPTIONS] <INPUT_PATH>
+
+Commands:
+  convert  Resize images and convert them to DDS (default)
+  info     Print header details of DDS and image files
+  decode   Decode DDS files to PNG
+  verify   Check converted outputs against their inputs
+  help     Print help for a command
+
+Run 'dds-edit help <COMMAND>' for the options of a command.
+";
+
+/// Help text of the `convert` subcommand.
+const CONVERT_HELP: &str = "\
+Usage: dds-edit convert [OPTIONS] <INPUT_PATH>
+
+Resizes images to the target sizes and converts them to DDS.
+
+Options:
+  -o, --pathout <PATH>         Output directory or .zip archive (default: ./images/output)
+  -d, --maxdepth <DEPTH>       Maximum directory traversal depth
+      --layout <LAYOUT>        Output layout: mirrored (default), flat, per_size
+      --name <TEMPLATE>        Output file name template, e.g. {stem}_{width}
+                               (default: {stem}_{size}, flat: {relpath}_{stem}_{size},
+                               per_size: {stem})
+  -t, --target-widths <LIST>   Comma-separated target sizes: 1024, w:1024, h:512,
+                               max:2048 or 50% (default: 2048)
+      --upscale <POLICY>       Target sizes larger than the source: skip (default),
+                               clamp, upscale
+      --upscale-filter <FILTER>
+                               Filter for --upscale upscale (default: catmull-rom)
+      --pow2 <POLICY>          Power-of-two policy: none (default), up, down, nearest, pad
+      --no-multiple-of-4       Keep dimensions that are not multiples of 4
+  -n, --threads <COUNT>        Number of concurrent tasks (default: 4)
+      --resize-filter <FILTER> Resize filter: nearest, triangle (default), catmull-rom,
+                               gaussian, lanczos3
+      --sharpen <AMOUNT>       Unsharp mask strength after resizing (e.g. 0.5)
+  -q, --quality <QUALITY>      Compression quality: fast, normal, slow
+  -m, --mipmaps <MIPMAPS>      Mipmaps: auto, none or a level count
+      --mip-filter <FILTER>    Mip filter: box (default), kaiser, lanczos
+      --alpha-test-ref <REF>   Preserve alpha-test coverage at this reference (0-1) on mips
+  -f, --format <FORMAT>        Explicit DDS format (e.g. BC7RgbaUnormSrgb)
+  -g, --game <GAME>            Target game: skyrim-le, skyrim-se, fallout4, dx10 (default)
+      --channel-ops <LIST>     Channel operations, e.g. invert:g,swizzle:bgra,fill:a=1
+  -c, --useconfig <FILE>       Load settings from a TOML configuration file
+      --keep-intermediate      Also save the resized images
+      --no-renormalize         Filter normal maps like color images
+      --normal-from-height     Also generate a normal map (_n) from every height map
+      --package                Generate a FOMOD installer with one option per target size
+                               (uses the per_size layout and the [package] table)
+      --force                  Reprocess all inputs, ignoring the cache
+      --watch                  Keep running and reprocess changed inputs
+      --watch-debounce <MS>    Quiet period before reprocessing (default: 500)
+  -h, --help                   Print this help
+";
+
+/// Help text of the `info` subcommand.
+const INFO_HELP: &str = "\
+Usage: dds-edit info [OPTIONS] <PATH>
+
+Prints header details of a DDS or image file, or of all supported files in a directory.
+For DDS files this includes the FourCC and DXGI formats, header type, mip count,
+array size, cubemap flags, pitch and data sizes.
+
+Options:
+  -d, --maxdepth <DEPTH>       Maximum directory traversal depth
+      --json                   Print a JSON array instead of the human-readable listing
+  -h, --help                   Print this help
+";
+
+/// Help text of the `decode` subcommand.
+const DECODE_HELP: &str = "\
+Usage: dds-edit decode [OPTIONS] <INPUT_PATH>
+
+Decodes DDS files to PNG files of the same name, without resizing.
+
+Options:
+  -o, --pathout <PATH>         Output directory or .zip archive (default: ./images/output)
+  -d, --maxdepth <DEPTH>       Maximum directory traversal depth
+  -n, --threads <COUNT>        Number of concurrent tasks (default: 4)
+  -c, --useconfig <FILE>       Load settings from a TOML configuration file
+      --watch                  Keep running and decode changed inputs
+      --watch-debounce <MS>    Quiet period before decoding (default: 500)
+  -h, --help                   Print this help
+";
+
+/// Help text of the `verify` subcommand.
+const VERIFY_HELP: &str = "\
+Usage: dds-edit verify [OPTIONS] <INPUT_PATH>
+
+Checks that every input has its DDS outputs in the output directory, with the
+expected dimensions and format. Exits with status 1 if any check fails.
+
+Options:
+  -o, --pathout <PATH>         Output directory to check (default: ./images/output)
+  -d, --maxdepth <DEPTH>       Maximum directory traversal depth
+      --layout <LAYOUT>        Output layout the outputs were converted with
+      --name <TEMPLATE>        Output file name template the outputs were converted with
+  -t, --target-widths <LIST>   Comma-separated target sizes (default: 2048)
+      --upscale <POLICY>       Upscale policy the outputs were converted with
+      --pow2 <POLICY>          Power-of-two policy the outputs were converted with
+      --no-multiple-of-4       The outputs were converted with --no-multiple-of-4
+  -f, --format <FORMAT>        Expected DDS format instead of the role-based one
+  -g, --game <GAME>            Target game the outputs were converted for
+      --channel-ops <LIST>     Channel operations the outputs were converted with
+      --normal-from-height     Also check the normal maps generated from height maps
+  -c, --useconfig <FILE>       Load settings from a TOML configuration file
+  -h, --help                   Print this help
+";
+
+impl Command {
+    /// Looks up a subcommand by name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The subcommand as written on the command line.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(Command)` if the name is a known subcommand, otherwise `None`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "convert" => Some(Command::Convert),
+            "info" => Some(Command::Info),
+            "decode" => Some(Command::Decode),
+            "verify" => Some(Command::Verify),
+            _ => None,
+        }
+    }
+
+    /// Returns the help text of the subcommand.
+    pub fn help(&self) -> &'static str {
+        match self {
+            Command::Convert => CONVERT_HELP,
+            Command::Info => INFO_HELP,
+            Command::Decode => DECODE_HELP,
+            Command::Verify => VERIFY_HELP,
+        }
+    }
+
+    /// Returns the help text listing all subcommands.
+    pub fn general_help() -> &'static str {
+        GENERAL_HELP
+    }
+
+    /// Returns `true` if the subcommand accepts the long option or flag `key`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The long option name without dashes (e.g., `pathout`).
+    pub fn accepts(&self, key: &str) -> bool {
+        let options: &[&str] = match self {
+            Command::Convert => &[
+                "pathout",
+                "maxdepth",
+                "layout",
+                "name",
+                "name-template",
+                "target-widths",
+                "target-sizes",
+                "upscale",
+                "upscale-filter",
+                "pow2",
+                "no-multiple-of-4",
+                "threads",
+                "resize-filter",
+                "sharpen",
+                "quality",
+                "mipmaps",
+                "mip-filter",
+                "alpha-test-ref",
+                "format",
+                "game",
+                "channel-ops",
+                "useconfig",
+                "keep-intermediate",
+                "no-renormalize",
+                "normal-from-height",
+                "package",
+                "force",
+                "watch",
+                "watch-debounce",
+                "decode",
+            ],
+            Command::Info => &["maxdepth", "json"],
+            Command::Decode => &[
+                "pathout",
+                "maxdepth",
+                "threads",
+                "useconfig",
+                "watch",
+                "watch-debounce",
+            ],
+            Command::Verify => &[
+                "pathout",
+                "maxdepth",
+                "layout",
+                "name",
+                "name-template",
+                "target-widths",
+                "target-sizes",
+                "upscale",
+                "pow2",
+                "no-multiple-of-4",
+                "format",
+                "game",
+                "channel-ops",
+                "normal-from-height",
+                "useconfig",
+            ],
+        };
+        options.contains(&key)
+    }
+}
+
+impl std::fmt::Display for Command {
+    /// Formats the `Command` as its command-line name.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Command::Convert => write!(f, "convert"),
+            Command::Info => write!(f, "info"),
+            Command::Decode => write!(f, "decode"),
+            Command::Verify => write!(f, "verify"),
+        }
+    }
+}
@@ -1,3 +1,4 @@
 // src/cli/mod.rs
 
 pub mod cli_args;
+pub mod cli_command;
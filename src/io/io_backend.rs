@@ -0,0 +1,249 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/io/io_backend.rs
+
+use std::{
+    collections::BTreeSet,
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use super::io_out::create_parent_directory;
+
+/// Where the outputs of a run are written.
+///
+/// Output paths are always resolved below `path_out`; the backend decides
+/// whether they become files in a directory or entries of a zip archive.
+#[derive(Debug, Clone, Default)]
+pub enum OutputBackend {
+    /// Write every output as a file at its path.
+    #[default]
+    Directory,
+
+    /// Write every output as an entry of the archive at `path_out`, named by
+    /// its path relative to `path_out`.
+    Zip(Arc<ZipOutput>),
+}
+
+/// A zip archive that outputs are streamed into while processing.
+#[derive(Debug)]
+pub struct ZipOutput {
+    /// Path of the archive; output paths are made relative to it.
+    root: PathBuf,
+
+    /// The archive writer, `None` once the archive is finished.
+    writer: Mutex<Option<ZipWriter<BufWriter<File>>>>,
+
+    /// Names of the entries written so far.
+    entries: Mutex<BTreeSet<String>>,
+}
+
+/// Returns `true` if a path names a zip archive rather than a directory.
+///
+/// # Arguments
+///
+/// * `path` - The path to check (e.g., `path_out`).
+pub fn is_archive_path(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("zip"))
+}
+
+impl OutputBackend {
+    /// Creates the backend writing into a new zip archive, replacing an existing one.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the archive.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(OutputBackend)` writing into the archive.
+    /// * `Err(String)` with an error message if the archive cannot be created.
+    pub fn zip(path: &Path) -> Result<Self, String> {
+        create_parent_directory(path)?;
+        let file = File::create(path)
+            .map_err(|e| format!("Failed to create archive '{}': {}", path.display(), e))?;
+        Ok(OutputBackend::Zip(Arc::new(ZipOutput {
+            root: path.to_path_buf(),
+            writer: Mutex::new(Some(ZipWriter::new(BufWriter::new(file)))),
+            entries: Mutex::new(BTreeSet::new()),
+        })))
+    }
+
+    /// Returns `true` if outputs are written into an archive.
+    pub fn is_archive(&self) -> bool {
+        matches!(self, OutputBackend::Zip(_))
+    }
+
+    /// Writes one output.
+    ///
+    /// Missing parent directories are created. Archive entries are deflated
+    /// when `compress` is set and stored as they are otherwise, which suits
+    /// data that is already compressed, such as DDS and PNG files.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the output below `path_out`.
+    /// * `bytes` - Contents of the output.
+    /// * `compress` - Whether archive entries are compressed.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` if the output was written.
+    /// * `Err(String)` with an error message if writing fails.
+    pub fn write(&self, path: &Path, bytes: &[u8], compress: bool) -> Result<(), String> {
+        match self {
+            OutputBackend::Directory => {
+                create_parent_directory(path)?;
+                std::fs::write(path, bytes)
+                    .map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+            }
+            OutputBackend::Zip(zip) => {
+                let name = zip.entry_name(path)?;
+                let options = SimpleFileOptions::default()
+                    .compression_method(if compress {
+                        CompressionMethod::Deflated
+                    } else {
+                        CompressionMethod::Stored
+                    })
+                    .large_file(bytes.len() as u64 >= u32::MAX as u64);
+
+                let mut writer = zip.writer.lock().map_err(|e| e.to_string())?;
+                let Some(writer) = writer.as_mut() else {
+                    return Err(format!(
+                        "Archive '{}' is already finished",
+                        zip.root.display()
+                    ));
+                };
+                writer
+                    .start_file(name.as_str(), options)
+                    .and_then(|()| writer.write_all(bytes).map_err(Into::into))
+                    .map_err(|e| {
+                        format!(
+                            "Failed to write '{}' to '{}': {}",
+                            name,
+                            zip.root.display(),
+                            e
+                        )
+                    })?;
+                zip.entries.lock().map_err(|e| e.to_string())?.insert(name);
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns `true` if any output was written below a directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the directory below `path_out`.
+    pub fn contains_directory(&self, path: &Path) -> bool {
+        match self {
+            OutputBackend::Directory => path.is_dir(),
+            OutputBackend::Zip(zip) => {
+                let Ok(name) = zip.entry_name(path) else {
+                    return false;
+                };
+                let prefix = format!("{}/", name);
+                zip.entries
+                    .lock()
+                    .is_ok_and(|entries| entries.iter().any(|entry| entry.starts_with(&prefix)))
+            }
+        }
+    }
+
+    /// Completes the outputs of a run.
+    ///
+    /// Writes the central directory of an archive, without which it cannot be
+    /// opened; directories need nothing further.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` if the outputs are complete.
+    /// * `Err(String)` with an error message if the archive cannot be finished.
+    pub fn finish(&self) -> Result<(), String> {
+        let OutputBackend::Zip(zip) = self else {
+            return Ok(());
+        };
+        let Some(writer) = zip.writer.lock().map_err(|e| e.to_string())?.take() else {
+            return Ok(());
+        };
+        writer
+            .finish()
+            .map_err(|e| e.to_string())
+            .and_then(|mut file| file.flush().map_err(|e| e.to_string()))
+            .map_err(|e| format!("Failed to finish archive '{}': {}", zip.root.display(), e))
+    }
+}
+
+impl ZipOutput {
+    /// Converts an output path to the name of its archive entry, using `/` separators.
+    fn entry_name(&self, path: &Path) -> Result<String, String> {
+        let relative = path.strip_prefix(&self.root).map_err(|_| {
+            format!(
+                "Output '{}' is outside of archive '{}'",
+                path.display(),
+                self.root.display()
+            )
+        })?;
+        let name = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        if name.is_empty() {
+            return Err(format!(
+                "Output '{}' does not name an archive entry",
+                path.display()
+            ));
+        }
+        Ok(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_archive_paths() {
+        assert!(is_archive_path(Path::new("out/Textures.ZIP")));
+        assert!(!is_archive_path(Path::new("out/textures")));
+        assert!(!is_archive_path(Path::new("out/zip")));
+    }
+
+    #[test]
+    fn writes_entries_named_relative_to_the_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("out.zip");
+        let backend = OutputBackend::zip(&root).unwrap();
+        assert!(backend.is_archive());
+
+        let output = root.join("textures").join("armor").join("iron_d.dds");
+        backend.write(&output, b"dds", false).unwrap();
+        backend
+            .write(&root.join("fomod").join("info.xml"), b"<fomod/>", true)
+            .unwrap();
+        assert!(backend.contains_directory(&root.join("textures")));
+        assert!(!backend.contains_directory(&root.join("meshes")));
+        assert!(backend
+            .write(&dir.path().join("other.dds"), b"", false)
+            .is_err());
+        assert!(backend.write(&root, b"", false).is_err());
+
+        backend.finish().unwrap();
+        assert!(backend.write(&output, b"dds", false).is_err());
+
+        let mut archive = zip::ZipArchive::new(File::open(&root).unwrap()).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(names, ["fomod/info.xml", "textures/armor/iron_d.dds"]);
+        let entry = archive.by_name("textures/armor/iron_d.dds").unwrap();
+        assert_eq!(entry.compression(), CompressionMethod::Stored);
+        drop(entry);
+        let entry = archive.by_name("fomod/info.xml").unwrap();
+        assert_eq!(entry.compression(), CompressionMethod::Deflated);
+    }
+}
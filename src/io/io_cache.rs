@@ -0,0 +1,153 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/io/io_cache.rs
+
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+use crate::structs::struct_cachemanifest::{CacheManifest, CACHE_MANIFEST_VERSION};
+
+use super::io_source::read_input;
+
+/// File name of the cache manifest inside the output directory.
+pub const CACHE_MANIFEST_FILE: &str = ".dds-edit-cache.toml";
+
+/// Loads the cache manifest from the output directory.
+///
+/// A missing, unreadable or outdated manifest, or one written for a different
+/// input directory, yields an empty manifest so that everything is rebuilt.
+///
+/// # Arguments
+///
+/// * `path_in` - The input directory of the current run.
+/// * `path_out` - The output directory holding the manifest.
+///
+/// # Returns
+///
+/// * `CacheManifest` - The previous manifest, or an empty one.
+pub fn load_cache_manifest(path_in: &str, path_out: &Path) -> CacheManifest {
+    let manifest = std::fs::read_to_string(path_out.join(CACHE_MANIFEST_FILE))
+        .ok()
+        .and_then(|content| toml::from_str::<CacheManifest>(&content).ok());
+
+    match manifest {
+        Some(manifest)
+            if manifest.version == CACHE_MANIFEST_VERSION && manifest.path_in == path_in =>
+        {
+            manifest
+        }
+        _ => CacheManifest::new(path_in),
+    }
+}
+
+/// Writes the cache manifest to the output directory.
+///
+/// # Arguments
+///
+/// * `manifest` - The manifest to write.
+/// * `path_out` - The output directory.
+///
+/// # Returns
+///
+/// * `Ok(())` if the manifest was written.
+/// * `Err(String)` with an error message if serializing or writing fails.
+pub fn save_cache_manifest(manifest: &CacheManifest, path_out: &Path) -> Result<(), String> {
+    let path = path_out.join(CACHE_MANIFEST_FILE);
+    let content = toml::to_string_pretty(manifest)
+        .map_err(|e| format!("Failed to serialize cache manifest: {}", e))?;
+    std::fs::write(&path, content)
+        .map_err(|e| format!("Failed to write cache manifest '{}': {}", path.display(), e))
+}
+
+/// Computes the SHA-256 hash of a file's contents.
+///
+/// # Arguments
+///
+/// * `path` - Path to the file.
+///
+/// # Returns
+///
+/// * `Ok(String)` with the lowercase hex digest.
+/// * `Err(String)` with an error message if the file cannot be read.
+pub fn hash_file(path: &Path) -> Result<String, String> {
+    let bytes = read_input(path)?;
+    Ok(to_hex(&Sha256::digest(&bytes)))
+}
+
+/// Computes the SHA-256 hash of a string.
+///
+/// # Arguments
+///
+/// * `value` - The string to hash.
+///
+/// # Returns
+///
+/// * `String` - The lowercase hex digest.
+pub fn hash_str(value: &str) -> String {
+    to_hex(&Sha256::digest(value.as_bytes()))
+}
+
+/// Converts a path relative to `base` into a manifest key with `/` separators.
+///
+/// # Arguments
+///
+/// * `base` - The directory the key is relative to.
+/// * `path` - The path to convert.
+///
+/// # Returns
+///
+/// * `String` - The relative path, or the full path if it is not inside `base`.
+pub fn manifest_key(base: &Path, path: &Path) -> String {
+    path.strip_prefix(base)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Resolves a manifest key back to a path inside `base`.
+///
+/// # Arguments
+///
+/// * `base` - The directory the key is relative to.
+/// * `key` - The manifest key with `/` separators.
+pub fn manifest_path(base: &Path, key: &str) -> PathBuf {
+    key.split('/')
+        .fold(base.to_path_buf(), |path, part| path.join(part))
+}
+
+/// Formats bytes as a lowercase hex string.
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::struct_cachemanifest::CacheEntry;
+
+    #[test]
+    fn manifest_keys_round_trip() {
+        let base = Path::new("textures");
+        let path = base.join("armor").join("iron_d.png");
+        let key = manifest_key(base, &path);
+        assert_eq!(key, "armor/iron_d.png");
+        assert_eq!(manifest_path(base, &key), path);
+    }
+
+    #[test]
+    fn manifest_round_trips_and_is_tied_to_the_input_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manifest = CacheManifest::new("textures");
+        manifest.files.insert(
+            "iron_d.png".to_string(),
+            CacheEntry {
+                hash: hash_str("pixels"),
+                settings: hash_str("settings"),
+                outputs: vec!["iron_d.dds".to_string()],
+            },
+        );
+        save_cache_manifest(&manifest, dir.path()).unwrap();
+
+        let loaded = load_cache_manifest("textures", dir.path());
+        assert_eq!(loaded.files, manifest.files);
+        assert!(load_cache_manifest("other", dir.path()).files.is_empty());
+    }
+}
@@ -0,0 +1,535 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/io/io_dds.rs
+
+use image_dds::{
+    ddsfile::{D3DFormat, Dds, FourCC, NewD3dParams, PixelFormatFlags},
+    ImageFormat, Surface,
+};
+use std::path::Path;
+
+use crate::structs::struct_colorspace::ColorSpace;
+
+use super::io_source::read_input;
+
+/// Byte offset of the red (luminance) bit mask in a DDS file, after the magic
+/// number, the header fields before the pixel format, and the pixel format's
+/// size, flags, FourCC and bit count.
+const LUMINANCE_MASK_OFFSET: usize = 92;
+
+/// Reads and parses a DDS file with either a legacy or a DX10 header.
+///
+/// # Arguments
+///
+/// * `path` - Path to the DDS file, possibly below a `.zip` file.
+///
+/// # Returns
+///
+/// * `Ok(Dds)` if the file could be read and parsed.
+/// * `Err(String)` with an error message if reading or parsing fails.
+pub fn read_dds(path: &Path) -> Result<Dds, String> {
+    let bytes = read_input(path)
+        .map_err(|e| format!("Failed to open DDS file '{}': {}", path.display(), e))?;
+    parse_dds(&bytes, path)
+}
+
+/// Parses a DDS file from memory.
+///
+/// The header is kept as stored, except that `ddsfile` only keeps the color
+/// masks of RGB formats, so the luminance mask of formats such as `L8` is
+/// restored from the file.
+///
+/// # Arguments
+///
+/// * `bytes` - Contents of the DDS file.
+/// * `path` - Path of the file, used in error messages.
+///
+/// # Returns
+///
+/// * `Ok(Dds)` if the data could be parsed.
+/// * `Err(String)` with an error message if parsing fails.
+pub fn parse_dds(bytes: &[u8], path: &Path) -> Result<Dds, String> {
+    let mut dds = Dds::read(std::io::Cursor::new(bytes))
+        .map_err(|e| format!("Failed to parse DDS file '{}': {}", path.display(), e))?;
+    let spf = &mut dds.header.spf;
+    if spf.flags.contains(PixelFormatFlags::LUMINANCE) && spf.r_bit_mask.is_none() {
+        spf.r_bit_mask = bytes
+            .get(LUMINANCE_MASK_OFFSET..LUMINANCE_MASK_OFFSET + 4)
+            .map(|mask| u32::from_le_bytes([mask[0], mask[1], mask[2], mask[3]]));
+    }
+    Ok(dds)
+}
+
+/// Decodes the base mip level of a 2D DDS texture to an RGBA8 image.
+///
+/// All block compressed formats (BC1-BC7) and the uncompressed formats known to
+/// `image_dds` are decoded directly. Other legacy uncompressed formats
+/// (e.g., `R8G8B8`, `L8`, `A8L8`) are decoded from their pixel format bit masks.
+///
+/// # Arguments
+///
+/// * `dds` - Reference to the parsed `Dds`.
+///
+/// # Returns
+///
+/// * `Ok(image::RgbaImage)` with the decoded base level.
+/// * `Err(String)` with an error message if the texture cannot be decoded.
+pub fn decode_dds(dds: &Dds) -> Result<image::RgbaImage, String> {
+    // Cubemaps, arrays and volumes cannot be resized as a single 2D image.
+    if dds.get_num_array_layers() > 1 || dds.get_depth() > 1 || is_cubemap(dds) {
+        return Err("Cubemaps, texture arrays and volume textures are not supported".to_string());
+    }
+
+    // `ddsfile` reports L8 as R8, which `image_dds` would decode to red only.
+    let luminance =
+        dds.header10.is_none() && dds.header.spf.flags.contains(PixelFormatFlags::LUMINANCE);
+    if let (false, Ok(image_format)) = (luminance, dds_image_format(dds)) {
+        let surface = Surface {
+            width: dds.get_width(),
+            height: dds.get_height(),
+            depth: 1,
+            layers: 1,
+            mipmaps: dds.get_num_mipmap_levels(),
+            image_format,
+            data: dds.data.as_slice(),
+        };
+        return surface
+            .decode_layers_mipmaps_rgba8(0..1, 0..1)
+            .map_err(|e| format!("Failed to decode DDS: {}", e))?
+            .into_image()
+            .map_err(|e| format!("Failed to decode DDS: {}", e));
+    }
+
+    decode_dds_bitmasks(dds)
+}
+
+/// Returns the format the pixel data of a DDS texture is stored in.
+///
+/// Wraps `image_dds::dds_image_format`, which does not know the legacy `ATI1`
+/// FourCC that BC4 is stored with for Direct3D 9 games.
+///
+/// # Arguments
+///
+/// * `dds` - Reference to the parsed `Dds`.
+///
+/// # Returns
+///
+/// * `Ok(ImageFormat)` with the format of the pixel data.
+/// * `Err(String)` with an error message if `image_dds` does not support the format.
+pub fn dds_image_format(dds: &Dds) -> Result<ImageFormat, String> {
+    if dds.header10.is_none() && dds.header.spf.fourcc == Some(FourCC(FourCC::ATI1)) {
+        return Ok(ImageFormat::BC4RUnorm);
+    }
+    image_dds::dds_image_format(dds).map_err(|e| format!("{:?}", e))
+}
+
+/// Returns the number of meaningful channels stored in a DDS texture.
+///
+/// # Arguments
+///
+/// * `dds` - Reference to the parsed `Dds`.
+///
+/// # Returns
+///
+/// * `u8` - The channel count (1 for BC4/R8, 2 for BC5, 3 for opaque, 4 with alpha).
+pub fn dds_channel_count(dds: &Dds) -> u8 {
+    match dds_image_format(dds) {
+        Ok(ImageFormat::R8Unorm | ImageFormat::BC4RUnorm | ImageFormat::BC4RSnorm) => 1,
+        Ok(ImageFormat::BC5RgUnorm | ImageFormat::BC5RgSnorm) => 2,
+        Ok(
+            ImageFormat::BC1RgbaUnorm
+            | ImageFormat::BC1RgbaUnormSrgb
+            | ImageFormat::BC6hRgbUfloat
+            | ImageFormat::BC6hRgbSfloat,
+        ) => 3,
+        Ok(_) => 4,
+        Err(_) => {
+            let spf = &dds.header.spf;
+            let has_alpha = spf.flags.contains(PixelFormatFlags::ALPHA_PIXELS)
+                && spf.a_bit_mask.unwrap_or(0) != 0;
+            if spf.flags.contains(PixelFormatFlags::LUMINANCE) {
+                if has_alpha {
+                    2
+                } else {
+                    1
+                }
+            } else if has_alpha {
+                4
+            } else {
+                3
+            }
+        }
+    }
+}
+
+/// Returns the color space declared by a DDS texture's format.
+///
+/// Only DX10 `*_SRGB` formats declare a color space; for everything else the
+/// color space is unknown and `None` is returned.
+///
+/// # Arguments
+///
+/// * `dds` - Reference to the parsed `Dds`.
+pub fn dds_color_space(dds: &Dds) -> Option<ColorSpace> {
+    match dds_image_format(dds) {
+        Ok(
+            ImageFormat::Rgba8UnormSrgb
+            | ImageFormat::Bgra8UnormSrgb
+            | ImageFormat::BC1RgbaUnormSrgb
+            | ImageFormat::BC2RgbaUnormSrgb
+            | ImageFormat::BC3RgbaUnormSrgb
+            | ImageFormat::BC7RgbaUnormSrgb,
+        ) => Some(ColorSpace::Srgb),
+        _ => None,
+    }
+}
+
+/// Computes the size of the pixel data a DDS header describes.
+///
+/// Covers all mip levels, array layers, cubemap faces and volume slices.
+///
+/// # Arguments
+///
+/// * `dds` - Reference to the parsed `Dds`.
+///
+/// # Returns
+///
+/// * `Some(u64)` with the expected data size in bytes.
+/// * `None` if the format's block size is unknown.
+pub fn dds_expected_data_size(dds: &Dds) -> Option<u64> {
+    // Block edge length in pixels and block size in bytes.
+    let (block, block_bytes) = match dds_image_format(dds) {
+        Ok(
+            ImageFormat::BC1RgbaUnorm
+            | ImageFormat::BC1RgbaUnormSrgb
+            | ImageFormat::BC4RUnorm
+            | ImageFormat::BC4RSnorm,
+        ) => (4, 8),
+        Ok(
+            ImageFormat::BC2RgbaUnorm
+            | ImageFormat::BC2RgbaUnormSrgb
+            | ImageFormat::BC3RgbaUnorm
+            | ImageFormat::BC3RgbaUnormSrgb
+            | ImageFormat::BC5RgUnorm
+            | ImageFormat::BC5RgSnorm
+            | ImageFormat::BC6hRgbUfloat
+            | ImageFormat::BC6hRgbSfloat
+            | ImageFormat::BC7RgbaUnorm
+            | ImageFormat::BC7RgbaUnormSrgb,
+        ) => (4, 16),
+        Ok(ImageFormat::R8Unorm) => (1, 1),
+        Ok(ImageFormat::Bgra4Unorm) => (1, 2),
+        Ok(
+            ImageFormat::Rgba8Unorm
+            | ImageFormat::Rgba8UnormSrgb
+            | ImageFormat::Bgra8Unorm
+            | ImageFormat::Bgra8UnormSrgb,
+        ) => (1, 4),
+        Ok(ImageFormat::Rgba16Float) => (1, 8),
+        Ok(ImageFormat::Rgba32Float) => (1, 16),
+        Err(_) => match dds.header.spf.rgb_bit_count {
+            Some(bits @ (8 | 16 | 24 | 32)) => (1, bits as u64 / 8),
+            _ => return None,
+        },
+        Ok(_) => return None,
+    };
+
+    let level_size = |level: u32| {
+        let width = (dds.get_width() >> level).max(1) as u64;
+        let height = (dds.get_height() >> level).max(1) as u64;
+        let depth = (dds.get_depth() >> level).max(1) as u64;
+        width.div_ceil(block) * height.div_ceil(block) * depth * block_bytes
+    };
+    let layer_size: u64 = (0..dds.get_num_mipmap_levels()).map(level_size).sum();
+
+    // DX10 cubemaps count whole cubes in the array size, legacy ones count faces.
+    let faces = if is_cubemap(dds) && dds.header10.is_some() {
+        6
+    } else {
+        1
+    };
+    Some(layer_size * dds.get_num_array_layers() as u64 * faces)
+}
+
+/// Returns `true` if the DDS texture is a cubemap.
+pub fn is_cubemap(dds: &Dds) -> bool {
+    dds.header
+        .caps2
+        .contains(image_dds::ddsfile::Caps2::CUBEMAP)
+        || matches!(
+            &dds.header10,
+            Some(header10) if header10.misc_flag.contains(image_dds::ddsfile::MiscFlag::TEXTURECUBE)
+        )
+}
+
+/// Rewrites a 2D DDS texture with a legacy (pre-DX10) header.
+///
+/// Games and tools built on Direct3D 9 cannot read the DX10 extended header
+/// that `image_dds` writes. BC1-BC3 are stored as `DXT1`/`DXT3`/`DXT5`, BC4 as
+/// `ATI1` (`BC4S` if signed), BC5 as `ATI2` and BGRA8 as `A8R8G8B8`; the pixel
+/// data is copied unchanged.
+///
+/// # Arguments
+///
+/// * `dds` - The DDS texture to rewrite.
+/// * `format` - The format the pixel data is encoded in.
+///
+/// # Returns
+///
+/// * `Ok(Dds)` with the same pixel data behind a legacy header.
+/// * `Err(String)` if the format has no legacy equivalent.
+pub fn to_legacy_header(dds: Dds, format: ImageFormat) -> Result<Dds, String> {
+    // ATI1 and ATI2 have no D3DFormat; they share the block size of DXT1 and DXT5,
+    // so only the FourCC differs.
+    let (d3d_format, fourcc) = match format {
+        ImageFormat::BC1RgbaUnorm | ImageFormat::BC1RgbaUnormSrgb => (D3DFormat::DXT1, None),
+        ImageFormat::BC2RgbaUnorm | ImageFormat::BC2RgbaUnormSrgb => (D3DFormat::DXT3, None),
+        ImageFormat::BC3RgbaUnorm | ImageFormat::BC3RgbaUnormSrgb => (D3DFormat::DXT5, None),
+        ImageFormat::BC4RUnorm => (D3DFormat::DXT1, Some(FourCC(FourCC::ATI1))),
+        ImageFormat::BC4RSnorm => (D3DFormat::DXT1, Some(FourCC(FourCC::BC4_SNORM))),
+        ImageFormat::BC5RgUnorm => (D3DFormat::DXT5, Some(FourCC(FourCC::ATI2))),
+        ImageFormat::Bgra8Unorm | ImageFormat::Bgra8UnormSrgb => (D3DFormat::A8R8G8B8, None),
+        _ => return Err(format!("{} has no legacy DDS header", format)),
+    };
+
+    let mut legacy = Dds::new_d3d(NewD3dParams {
+        height: dds.get_height(),
+        width: dds.get_width(),
+        depth: None,
+        format: d3d_format,
+        mipmap_levels: Some(dds.get_num_mipmap_levels()),
+        caps2: None,
+    })
+    .map_err(|e| format!("Failed to create legacy DDS header: {}", e))?;
+
+    if fourcc.is_some() {
+        legacy.header.spf.fourcc = fourcc;
+    }
+    legacy.data = dds.data;
+
+    Ok(legacy)
+}
+
+/// Decodes a legacy uncompressed DDS texture using its pixel format bit masks.
+///
+/// # Arguments
+///
+/// * `dds` - Reference to the parsed `Dds`.
+///
+/// # Returns
+///
+/// * `Ok(image::RgbaImage)` with the decoded base level.
+/// * `Err(String)` if the pixel format is compressed or otherwise unsupported.
+fn decode_dds_bitmasks(dds: &Dds) -> Result<image::RgbaImage, String> {
+    let spf = &dds.header.spf;
+    let bit_count = spf.rgb_bit_count.unwrap_or(0);
+    let uncompressed = spf
+        .flags
+        .intersects(PixelFormatFlags::RGB | PixelFormatFlags::LUMINANCE | PixelFormatFlags::ALPHA);
+    if !uncompressed || !matches!(bit_count, 8 | 16 | 24 | 32) {
+        return Err(format!(
+            "Unsupported DDS format (FourCC: {:?}, DXGI: {:?})",
+            spf.fourcc,
+            dds.get_dxgi_format()
+        ));
+    }
+
+    let width = dds.get_width();
+    let height = dds.get_height();
+    let bytes_per_pixel = (bit_count / 8) as usize;
+    let pixel_count = width as usize * height as usize;
+    if dds.data.len() < pixel_count * bytes_per_pixel {
+        return Err("DDS data is smaller than its header declares".to_string());
+    }
+
+    let luminance = spf.flags.contains(PixelFormatFlags::LUMINANCE);
+    let alpha_only = spf.flags.contains(PixelFormatFlags::ALPHA) && !luminance;
+    let r_mask = spf.r_bit_mask.unwrap_or(0);
+    let g_mask = spf.g_bit_mask.unwrap_or(0);
+    let b_mask = spf.b_bit_mask.unwrap_or(0);
+    let a_mask = if spf
+        .flags
+        .intersects(PixelFormatFlags::ALPHA_PIXELS | PixelFormatFlags::ALPHA)
+    {
+        spf.a_bit_mask.unwrap_or(0)
+    } else {
+        0
+    };
+
+    let mut image = image::RgbaImage::new(width, height);
+    for (index, pixel) in image.pixels_mut().enumerate() {
+        let offset = index * bytes_per_pixel;
+        let mut raw = [0u8; 4];
+        raw[..bytes_per_pixel].copy_from_slice(&dds.data[offset..offset + bytes_per_pixel]);
+        let value = u32::from_le_bytes(raw);
+
+        let a = if a_mask != 0 {
+            extract_channel(value, a_mask)
+        } else {
+            255
+        };
+        *pixel = if alpha_only {
+            image::Rgba([255, 255, 255, a])
+        } else if luminance {
+            let l = extract_channel(value, r_mask);
+            image::Rgba([l, l, l, a])
+        } else {
+            image::Rgba([
+                extract_channel(value, r_mask),
+                extract_channel(value, g_mask),
+                extract_channel(value, b_mask),
+                a,
+            ])
+        };
+    }
+
+    Ok(image)
+}
+
+/// Extracts a channel selected by `mask` from a packed pixel and scales it to 8 bits.
+fn extract_channel(value: u32, mask: u32) -> u8 {
+    if mask == 0 {
+        return 0;
+    }
+    let shift = mask.trailing_zeros();
+    let max = mask >> shift;
+    let channel = (value & mask) >> shift;
+    ((channel as u64 * 255 + max as u64 / 2) / max as u64) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image_dds::ddsfile::{AlphaMode, D3D10ResourceDimension, NewDxgiParams};
+
+    fn dx10_dds(format: ImageFormat, block_bytes: usize) -> Dds {
+        let mut dds = Dds::new_dxgi(NewDxgiParams {
+            height: 4,
+            width: 4,
+            depth: None,
+            format: format.into(),
+            mipmap_levels: Some(1),
+            array_layers: None,
+            caps2: None,
+            is_cubemap: false,
+            resource_dimension: D3D10ResourceDimension::Texture2D,
+            alpha_mode: AlphaMode::Unknown,
+        })
+        .unwrap();
+        dds.data = vec![0x55; block_bytes];
+        dds
+    }
+
+    fn round_trip(dds: &Dds) -> Dds {
+        let mut bytes = Vec::new();
+        dds.write(&mut bytes).unwrap();
+        parse_dds(&bytes, Path::new("test.dds")).unwrap()
+    }
+
+    #[test]
+    fn legacy_headers_use_the_expected_fourcc() {
+        for (format, block_bytes, fourcc) in [
+            (ImageFormat::BC1RgbaUnormSrgb, 8, FourCC::DXT1),
+            (ImageFormat::BC3RgbaUnorm, 16, FourCC::DXT5),
+            (ImageFormat::BC4RUnorm, 8, FourCC::ATI1),
+            (ImageFormat::BC4RSnorm, 8, FourCC::BC4_SNORM),
+            (ImageFormat::BC5RgUnorm, 16, FourCC::ATI2),
+        ] {
+            let legacy = to_legacy_header(dx10_dds(format, block_bytes), format).unwrap();
+            assert!(legacy.header10.is_none(), "{}", format);
+            assert_eq!(legacy.header.spf.fourcc, Some(FourCC(fourcc)), "{}", format);
+            assert_eq!(legacy.data, vec![0x55; block_bytes]);
+        }
+    }
+
+    #[test]
+    fn legacy_bc4_reads_back_as_bc4() {
+        let legacy =
+            to_legacy_header(dx10_dds(ImageFormat::BC4RUnorm, 8), ImageFormat::BC4RUnorm).unwrap();
+        let dds = round_trip(&legacy);
+        assert_eq!(dds_image_format(&dds).unwrap(), ImageFormat::BC4RUnorm);
+        assert_eq!(dds_channel_count(&dds), 1);
+    }
+
+    #[test]
+    fn formats_without_legacy_header_are_rejected() {
+        let format = ImageFormat::BC7RgbaUnorm;
+        assert!(to_legacy_header(dx10_dds(format, 16), format).is_err());
+    }
+
+    /// Creates a 2x1 legacy texture with the given pixel bytes.
+    fn legacy_dds(format: D3DFormat, data: &[u8]) -> Dds {
+        let mut dds = Dds::new_d3d(NewD3dParams {
+            height: 1,
+            width: 2,
+            depth: None,
+            format,
+            mipmap_levels: None,
+            caps2: None,
+        })
+        .unwrap();
+        dds.data = data.to_vec();
+        dds
+    }
+
+    /// Creates a 2x1 luminance texture the way `L8` and `A8L8` files store it,
+    /// read back from its bytes.
+    fn luminance_dds(bit_count: u32, a_mask: u32, data: &[u8]) -> Dds {
+        let mut dds = legacy_dds(D3DFormat::L8, data);
+        let spf = &mut dds.header.spf;
+        spf.flags = PixelFormatFlags::LUMINANCE;
+        spf.rgb_bit_count = Some(bit_count);
+        spf.r_bit_mask = Some(0xff);
+        spf.g_bit_mask = None;
+        spf.b_bit_mask = None;
+        if a_mask != 0 {
+            spf.flags |= PixelFormatFlags::ALPHA_PIXELS;
+            spf.a_bit_mask = Some(a_mask);
+        }
+        round_trip(&dds)
+    }
+
+    fn pixels(dds: &Dds) -> Vec<[u8; 4]> {
+        decode_dds(dds)
+            .unwrap()
+            .pixels()
+            .map(|pixel| pixel.0)
+            .collect()
+    }
+
+    #[test]
+    fn decodes_uncompressed_formats_from_bit_masks() {
+        let rgb = legacy_dds(D3DFormat::R8G8B8, &[0x30, 0x20, 0x10, 0xff, 0x00, 0x80]);
+        assert_eq!(
+            pixels(&rgb),
+            [[0x10, 0x20, 0x30, 255], [0x80, 0x00, 0xff, 255]]
+        );
+
+        let luminance = luminance_dds(8, 0, &[0x40, 0xc0]);
+        assert_eq!(
+            pixels(&luminance),
+            [[0x40, 0x40, 0x40, 255], [0xc0, 0xc0, 0xc0, 255]]
+        );
+
+        let luminance_alpha = luminance_dds(16, 0xff00, &[0x40, 0x80, 0xff, 0x00]);
+        assert_eq!(
+            pixels(&luminance_alpha),
+            [[0x40, 0x40, 0x40, 0x80], [255, 255, 255, 0]]
+        );
+
+        let rgb565 = legacy_dds(D3DFormat::R5G6B5, &[0x00, 0xf8, 0xe0, 0x07]);
+        assert_eq!(pixels(&rgb565), [[255, 0, 0, 255], [0, 255, 0, 255]]);
+    }
+
+    #[test]
+    fn rejects_truncated_uncompressed_data() {
+        let dds = legacy_dds(D3DFormat::R8G8B8, &[0x30, 0x20, 0x10]);
+        assert!(decode_dds(&dds).is_err());
+    }
+
+    #[test]
+    fn scales_channels_to_8_bits() {
+        assert_eq!(extract_channel(0x1f, 0x1f), 255);
+        assert_eq!(extract_channel(0x10, 0x1f), 132);
+        assert_eq!(extract_channel(0x0f00, 0x0f00), 255);
+        assert_eq!(extract_channel(0xffff, 0), 0);
+    }
+}
@@ -0,0 +1,114 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/io/io_in.rs
+
+use crate::{
+    io::{
+        io_dds::{dds_channel_count, dds_color_space, decode_dds, parse_dds},
+        io_pngchunks::read_png_color_space,
+        io_psd::load_psd,
+        io_source::read_input,
+    },
+    structs::{struct_colorspace::ColorSpace, struct_filetype::FileType},
+};
+use image::DynamicImage;
+use std::{io::Cursor, path::Path};
+
+/// A decoded input image together with the metadata detected while loading it.
+pub struct LoadedImage {
+    /// The decoded image.
+    pub image: DynamicImage,
+
+    /// File type detected while loading.
+    pub file_type: FileType,
+
+    /// Number of meaningful channels in the source file.
+    pub channels: u8,
+
+    /// Color space declared by the source file, if any.
+    pub color_space: Option<ColorSpace>,
+}
+
+/// Loads an input image of any supported file type.
+///
+/// The file type is detected from the file's magic bytes when possible, falling
+/// back to the type derived from the extension. The file is read once, from
+/// disk or from the zip archive it is in, and decoded from memory.
+///
+/// # Arguments
+///
+/// * `path` - Path to the input image, possibly below a `.zip` file.
+/// * `file_type` - The `FileType` derived from the file extension.
+///
+/// # Returns
+///
+/// * `Ok(LoadedImage)` with the decoded image and its metadata.
+/// * `Err(String)` with an error message if loading fails.
+pub fn load_image(path: &Path, file_type: FileType) -> Result<LoadedImage, String> {
+    let bytes = read_input(path)?;
+    let file_type = FileType::from_magic(&bytes[..bytes.len().min(12)]).unwrap_or(file_type);
+
+    match file_type {
+        FileType::DDS => {
+            let dds = parse_dds(&bytes, path)?;
+            let image = decode_dds(&dds)?;
+            Ok(LoadedImage {
+                image: DynamicImage::ImageRgba8(image),
+                file_type,
+                channels: dds_channel_count(&dds),
+                color_space: dds_color_space(&dds),
+            })
+        }
+        FileType::PSD => {
+            let image = load_psd(&bytes)?;
+            Ok(LoadedImage {
+                channels: image.color().channel_count(),
+                image,
+                file_type,
+                color_space: None,
+            })
+        }
+        _ => {
+            let mut image = image::ImageReader::new(Cursor::new(&bytes))
+                .with_guessed_format()
+                .map_err(|e| e.to_string())?
+                .decode()
+                .map_err(|e| e.to_string())?;
+
+            // 32-bit TGAs without alpha bits carry an unused fourth byte.
+            if matches!(file_type, FileType::TGA) && tga_has_unused_alpha(&bytes) {
+                image = DynamicImage::ImageRgb8(image.to_rgb8());
+            }
+
+            let color_space = match file_type {
+                FileType::PNG => read_png_color_space(&bytes),
+                _ => None,
+            };
+            Ok(LoadedImage {
+                channels: image.color().channel_count(),
+                image,
+                file_type,
+                color_space,
+            })
+        }
+    }
+}
+
+/// Detects the file type of a file from its magic bytes.
+///
+/// # Arguments
+///
+/// * `path` - Path to the file, possibly below a `.zip` file.
+///
+/// # Returns
+///
+/// * `Some(FileType)` if the magic bytes match a supported type.
+/// * `None` if the file cannot be read or has no known magic bytes.
+pub fn detect_file_type(path: &Path) -> Option<FileType> {
+    let bytes = read_input(path).ok()?;
+    FileType::from_magic(&bytes[..bytes.len().min(12)])
+}
+
+/// Returns `true` if the bytes of a TGA file store 32 bits per pixel but declare no alpha bits.
+fn tga_has_unused_alpha(bytes: &[u8]) -> bool {
+    // Byte 16 is the pixel depth, the low nibble of byte 17 the alpha bit count.
+    bytes.len() >= 18 && bytes[16] == 32 && bytes[17] & 0x0F == 0
+}
@@ -0,0 +1,219 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/io/io_naming.rs
+
+use std::path::{Path, PathBuf};
+
+use crate::structs::{
+    struct_config::Config, struct_imagedata::ImageData, struct_outputlayout::OutputLayout,
+    struct_sizespec::SizeSpec, struct_texturerole::TextureRole,
+};
+
+/// Values substituted into the naming template for one output.
+pub struct OutputName<'a> {
+    /// File name of the image without extension.
+    pub stem: &'a str,
+
+    /// The target size the output is written for.
+    pub spec: SizeSpec,
+
+    /// Width of the output.
+    pub width: u32,
+
+    /// Height of the output.
+    pub height: u32,
+
+    /// Texture role of the image.
+    pub role: TextureRole,
+
+    /// DDS format of the output.
+    pub format: String,
+}
+
+/// Resolves the path of an output from the layout and naming template.
+///
+/// The directory of the input relative to `path_in` is taken from the
+/// mirrored `file_path_out` of the image. A template using `{relpath}` places
+/// it itself, so the mirrored and per-size layouts do not add it again; in
+/// the flat layout, `{relpath}` joins its directories with `_` so that the
+/// output stays in one folder. `/` in the expanded template creates
+/// subdirectories; empty path components are dropped.
+///
+/// # Arguments
+///
+/// * `config` - The configuration holding `path_out`, the layout and the template.
+/// * `image_data` - The image, whose `file_path_out` mirrors its input directory.
+/// * `name` - Values for the template placeholders.
+/// * `extension` - Extension of the output without the leading dot (e.g., `dds`).
+///
+/// # Returns
+///
+/// * `PathBuf` - The full path of the output.
+pub fn output_path(
+    config: &Config,
+    image_data: &ImageData,
+    name: &OutputName,
+    extension: &str,
+) -> PathBuf {
+    let base = Path::new(&config.path_out);
+    let relpath = image_data
+        .file_path_out
+        .strip_prefix(base)
+        .unwrap_or(Path::new(""))
+        .to_string_lossy()
+        .replace('\\', "/");
+
+    let template = config
+        .name_template
+        .as_deref()
+        .unwrap_or_else(|| config.layout.default_template());
+    let mirrored = if template.contains("{relpath}") {
+        ""
+    } else {
+        relpath.as_str()
+    };
+
+    let directory = match config.layout {
+        OutputLayout::Mirrored => base.join(mirrored),
+        OutputLayout::Flat => base.to_path_buf(),
+        OutputLayout::PerSize => base.join(name.spec.folder_name()).join(mirrored),
+    };
+    let relpath = match config.layout {
+        OutputLayout::Flat => relpath.replace('/', "_"),
+        OutputLayout::Mirrored | OutputLayout::PerSize => relpath,
+    };
+    let expanded = expand_template(template, name, &relpath);
+
+    let mut path = expanded
+        .split('/')
+        .filter(|part| !part.is_empty() && *part != "." && *part != "..")
+        .fold(directory, |path, part| path.join(part));
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".");
+    file_name.push(extension);
+    path.set_file_name(file_name);
+    path
+}
+
+/// Replaces the placeholders of a naming template; unknown ones are kept as written.
+///
+/// For inputs directly in `path_in`, `{relpath}` is empty and a separator
+/// (`/`, `_`, `-` or `.`) right after it is dropped as well.
+fn expand_template(template: &str, name: &OutputName, relpath: &str) -> String {
+    let mut expanded = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        expanded.push_str(&rest[..start]);
+        let placeholder = &rest[start + 1..start + end];
+        match placeholder {
+            "stem" => expanded.push_str(name.stem),
+            "size" => expanded.push_str(&name.spec.suffix()),
+            "width" => expanded.push_str(&name.width.to_string()),
+            "height" => expanded.push_str(&name.height.to_string()),
+            "role" => expanded.push_str(&name.role.to_string().to_lowercase()),
+            "format" => expanded.push_str(&name.format),
+            "relpath" => expanded.push_str(relpath),
+            _ => expanded.push_str(&rest[start..start + end + 1]),
+        }
+        rest = &rest[start + end + 1..];
+        if placeholder == "relpath" && relpath.is_empty() {
+            rest = rest.strip_prefix(['/', '_', '-', '.']).unwrap_or(rest);
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(spec: SizeSpec) -> OutputName<'static> {
+        OutputName {
+            stem: "cuirass",
+            spec,
+            width: 2048,
+            height: 1024,
+            role: TextureRole::Diffuse,
+            format: "BC1RgbaUnormSrgb".to_string(),
+        }
+    }
+
+    /// Resolves the output of `textures/armor/cuirass.png` in `layout`.
+    fn path(layout: OutputLayout, template: Option<&str>, directory: &str) -> PathBuf {
+        let config = Config {
+            path_out: "out".to_string(),
+            layout,
+            name_template: template.map(str::to_string),
+            ..Config::default()
+        };
+        let input = Path::new("in").join(directory).join("cuirass.png");
+        let output = Path::new("out").join(directory).join("cuirass.png");
+        let image_data = ImageData::from((input, output));
+        output_path(&config, &image_data, &name(SizeSpec::Width(2048)), "dds")
+    }
+
+    #[test]
+    fn default_templates_per_layout() {
+        let armor = Path::new("textures").join("armor");
+        assert_eq!(
+            path(OutputLayout::Mirrored, None, "textures/armor"),
+            Path::new("out").join(&armor).join("cuirass_2048.dds")
+        );
+        assert_eq!(
+            path(OutputLayout::PerSize, None, "textures/armor"),
+            Path::new("out").join("2K").join(&armor).join("cuirass.dds")
+        );
+        assert_eq!(
+            path(OutputLayout::Flat, None, "textures/armor"),
+            Path::new("out").join("textures_armor_cuirass_2048.dds")
+        );
+        assert_eq!(
+            path(OutputLayout::Flat, None, ""),
+            Path::new("out").join("cuirass_2048.dds")
+        );
+    }
+
+    #[test]
+    fn relpath_in_template_places_the_directory() {
+        assert_eq!(
+            path(
+                OutputLayout::Mirrored,
+                Some("{size}/{relpath}/{stem}"),
+                "textures/armor"
+            ),
+            Path::new("out")
+                .join("2048")
+                .join("textures")
+                .join("armor")
+                .join("cuirass.dds")
+        );
+        assert_eq!(
+            path(OutputLayout::Mirrored, Some("{size}/{relpath}/{stem}"), ""),
+            Path::new("out").join("2048").join("cuirass.dds")
+        );
+        assert_eq!(
+            path(OutputLayout::Mirrored, Some("../{stem}"), ""),
+            Path::new("out").join("cuirass.dds")
+        );
+    }
+
+    #[test]
+    fn expands_placeholders() {
+        let name = name(SizeSpec::Max(2048));
+        assert_eq!(
+            expand_template("{stem}_{size}_{width}x{height}_{role}_{format}", &name, ""),
+            "cuirass_max2048_2048x1024_diffuse_BC1RgbaUnormSrgb"
+        );
+        assert_eq!(expand_template("{relpath}-{stem}", &name, ""), "cuirass");
+        assert_eq!(
+            expand_template("{relpath}-{stem}", &name, "a_b"),
+            "a_b-cuirass"
+        );
+        assert_eq!(
+            expand_template("{unknown}_{stem", &name, ""),
+            "{unknown}_{stem"
+        );
+    }
+}
@@ -2,35 +2,67 @@
 
 use crate::structs::struct_filetype::FileType;
 use image::DynamicImage;
-use std::path::{Path, PathBuf};
+use std::{
+    io::Cursor,
+    path::{Path, PathBuf},
+};
 
-/// Sets up the output directory by creating it if it doesn't exist.
+use super::io_backend::{is_archive_path, OutputBackend};
+
+/// Sets up the output of a run.
+///
+/// A path ending in `.zip` creates a new archive that outputs are written
+/// into; any other path is an output directory, created if it doesn't exist.
 ///
 /// # Arguments
 ///
-/// * `output_path` - The path to the output directory.
+/// * `output_path` - The path to the output directory or archive.
 ///
 /// # Returns
 ///
-/// * `Ok(())` if the directory is set up successfully.
+/// * `Ok(OutputBackend)` writing to the directory or archive.
 /// * `Err(String)` with an error message if it fails.
-pub fn setup_output_directory(output_path: &str) -> Result<(), String> {
+pub fn setup_output_directory(output_path: &str) -> Result<OutputBackend, String> {
     let path = Path::new(output_path);
+    if is_archive_path(path) {
+        return OutputBackend::zip(path);
+    }
     if !path.exists() {
         std::fs::create_dir_all(path)
             .map_err(|e| format!("Failed to create output directory '{}': {}", output_path, e))?;
     }
-    Ok(())
+    Ok(OutputBackend::Directory)
 }
 
-/// Saves a resized image with an appropriate suffix and format.
+/// Returns the extension and encoder format resized images of a file type are saved with.
+///
+/// Types that cannot be written back are saved as PNG.
+///
+/// # Arguments
+///
+/// * `file_type` - The `FileType` of the source image.
+pub fn intermediate_format(file_type: FileType) -> (&'static str, image::ImageFormat) {
+    match file_type.intermediate() {
+        FileType::JPG => ("jpg", image::ImageFormat::Jpeg),
+        FileType::TGA => ("tga", image::ImageFormat::Tga),
+        FileType::BMP => ("bmp", image::ImageFormat::Bmp),
+        FileType::TIFF => ("tiff", image::ImageFormat::Tiff),
+        FileType::WEBP => ("webp", image::ImageFormat::WebP),
+        _ => ("png", image::ImageFormat::Png),
+    }
+}
+
+/// Saves a resized image in the intermediate format of its file type.
+///
+/// Missing parent directories are created. In archives, formats that are
+/// already compressed are stored as they are.
 ///
 /// # Arguments
 ///
 /// * `img` - Reference to the `DynamicImage` to save.
-/// * `output_path` - The base output path.
-/// * `suffix` - Suffix to append to the file name.
+/// * `output_path` - The output path, with the extension from `intermediate_format`.
 /// * `file_type` - The `FileType` enum indicating the image format.
+/// * `output` - The backend the image is written to.
 ///
 /// # Returns
 ///
@@ -39,43 +71,59 @@ pub fn setup_output_directory(output_path: &str) -> Result<(), String> {
 pub fn save_image(
     img: &DynamicImage,
     output_path: &Path,
-    suffix: &str,
     file_type: FileType, // Changed to accept FileType by value
+    output: &OutputBackend,
 ) -> Result<PathBuf, String> {
-    let mut output_path = output_path.to_path_buf();
-    let file_stem = output_path
-        .file_stem()
-        .unwrap_or_default()
-        .to_string_lossy()
-        .to_string();
-    let extension = match file_type {
-        FileType::JPG => "jpg",
-        FileType::PNG => "png",
-        FileType::UNKNOWN => "img", // Default extension
-    };
-    let file_name = format!("{}_{}.{}", file_stem, suffix, extension);
-    output_path.set_file_name(file_name);
+    let (_, format) = intermediate_format(file_type);
+    let file_type = file_type.intermediate();
 
-    // Save the image based on its type
-    match file_type {
-        FileType::JPG => {
-            img.save_with_format(&output_path, image::ImageFormat::Jpeg)
-                .map_err(|e| {
-                    format!("Failed to save JPG image '{}': {}", output_path.display(), e)
-                })?;
-        }
-        FileType::PNG => {
-            img.save_with_format(&output_path, image::ImageFormat::Png)
-                .map_err(|e| {
-                    format!("Failed to save PNG image '{}': {}", output_path.display(), e)
-                })?;
+    // Float images (e.g., renormalized normal maps) are saved with 8-bit channels.
+    let converted;
+    let img = match img {
+        DynamicImage::ImageRgb32F(_) => {
+            converted = DynamicImage::ImageRgb8(img.to_rgb8());
+            &converted
         }
-        FileType::UNKNOWN => {
-            img.save(&output_path).map_err(|e| {
-                format!("Failed to save image '{}': {}", output_path.display(), e)
-            })?;
+        DynamicImage::ImageRgba32F(_) => {
+            converted = DynamicImage::ImageRgba8(img.to_rgba8());
+            &converted
         }
-    }
+        _ => img,
+    };
+
+    // Encode the image in the chosen format
+    let mut bytes = Cursor::new(Vec::new());
+    img.write_to(&mut bytes, format).map_err(|e| {
+        format!(
+            "Failed to save {} image '{}': {}",
+            file_type,
+            output_path.display(),
+            e
+        )
+    })?;
+    let compress = !matches!(
+        format,
+        image::ImageFormat::Png | image::ImageFormat::Jpeg | image::ImageFormat::WebP
+    );
+    output.write(output_path, bytes.get_ref(), compress)?;
 
-    Ok(output_path)
+    Ok(output_path.to_path_buf())
+}
+
+/// Creates the parent directory of an output file if it does not exist.
+///
+/// # Arguments
+///
+/// * `output_path` - Path of the file about to be written.
+///
+/// # Returns
+///
+/// * `Ok(())` if the directory exists afterwards.
+/// * `Err(String)` with an error message if it cannot be created.
+pub fn create_parent_directory(output_path: &Path) -> Result<(), String> {
+    match output_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory '{}': {}", parent.display(), e)),
+        _ => Ok(()),
+    }
 }
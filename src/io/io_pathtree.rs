@@ -6,18 +6,29 @@ use std::{
     path::{Path, PathBuf},
 };
 
+use super::{
+    io_backend::is_archive_path,
+    io_source::{is_archive_entry, list_archive, relative_input_path},
+};
+
 /// Recursively retrieves all paths within `base_path` up to `max_depth`.
 ///
+/// Zip archives are listed like directories: their file entries are added as
+/// paths below the archive path, without extracting them.
+///
 /// # Arguments
 ///
-/// * `base_path` - The root directory to start traversal.
+/// * `base_path` - The root directory or zip archive to start traversal.
 /// * `max_depth` - The maximum depth for recursive traversal.
 ///
 /// # Returns
 ///
 /// * `Ok(Vec<PathBuf>)` containing all collected paths.
 /// * `Err(Box<dyn std::error::Error>)` if an error occurs during traversal.
-pub fn get_path_tree(base_path: &str, max_depth: usize) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+pub fn get_path_tree(
+    base_path: &str,
+    max_depth: usize,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
     let mut paths = Vec::new();
     let base = Path::new(base_path);
 
@@ -44,7 +55,12 @@ pub fn get_path_tree(base_path: &str, max_depth: usize) -> Result<Vec<PathBuf>,
 ///
 /// * `Ok(())` if traversal succeeds.
 /// * `Err(Box<dyn std::error::Error>)` if an error occurs.
-fn collect_paths(path: &Path, paths: &mut Vec<PathBuf>, current_depth: usize, max_depth: usize) -> Result<(), Box<dyn std::error::Error>> {
+fn collect_paths(
+    path: &Path,
+    paths: &mut Vec<PathBuf>,
+    current_depth: usize,
+    max_depth: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
     // Stop recursion if the current depth exceeds max_depth.
     if current_depth > max_depth {
         return Ok(());
@@ -59,6 +75,16 @@ fn collect_paths(path: &Path, paths: &mut Vec<PathBuf>, current_depth: usize, ma
             let entry = entry?;
             collect_paths(&entry.path(), paths, current_depth + 1, max_depth)?;
         }
+    } else if is_archive_path(path) && path.is_file() {
+        // Archive entries count their directories towards the depth.
+        for entry in list_archive(path)? {
+            let depth = entry
+                .strip_prefix(path)
+                .map_or(0, |entry| entry.components().count());
+            if current_depth + depth <= max_depth {
+                paths.push(entry);
+            }
+        }
     }
 
     Ok(())
@@ -66,6 +92,9 @@ fn collect_paths(path: &Path, paths: &mut Vec<PathBuf>, current_depth: usize, ma
 
 /// Filters the collected paths to include only specified file types.
 ///
+/// Files inside zip archives are kept like files on disk; the archives
+/// themselves are not inputs.
+///
 /// # Arguments
 ///
 /// * `paths` - Slice of paths to filter.
@@ -82,7 +111,7 @@ pub fn filter_pathtree_types(paths: &[PathBuf], extensions: &[&str]) -> Vec<Path
     paths
         .iter()
         .filter(|path| {
-            if path.is_file() {
+            if path.is_file() || is_archive_entry(path) {
                 if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
                     ext_set.contains(&ext.to_lowercase())
                 } else {
@@ -98,6 +127,9 @@ pub fn filter_pathtree_types(paths: &[PathBuf], extensions: &[&str]) -> Vec<Path
 
 /// Mirrors the input path structure into the output path.
 ///
+/// Files inside a zip archive are mirrored relative to the archive root, in
+/// the directory of the archive.
+///
 /// # Arguments
 ///
 /// * `base_in` - The root input directory.
@@ -108,13 +140,18 @@ pub fn filter_pathtree_types(paths: &[PathBuf], extensions: &[&str]) -> Vec<Path
 ///
 /// * `Ok(PathBuf)` representing the mirrored output path.
 /// * `Err(Box<dyn std::error::Error>)` if path manipulation fails.
-pub fn mirror_path(base_in: &Path, base_out: &Path, path_in: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
-    // Calculate the relative path from base_in.
-    let relative = path_in.strip_prefix(base_in)?;
+pub fn mirror_path(
+    base_in: &Path,
+    base_out: &Path,
+    path_in: &Path,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    // Calculate the relative path from base_in, or from the root of an archive.
+    let relative = relative_input_path(base_in, path_in)?;
     // Join the relative path with base_out to create the mirrored path.
     Ok(base_out.join(relative))
 }
 
+use crate::logging::smnprint;
 /// Prints the directory tree of the filtered paths up to `max_depth`.
 ///
 /// # Arguments
@@ -126,7 +163,6 @@ pub fn mirror_path(base_in: &Path, base_out: &Path, path_in: &Path) -> Result<Pa
 ///
 /// * `()`
 use colored::Color;
-use crate::logging::smnprint;
 
 /// Prints the directory tree of the filtered paths up to `max_depth` with colorized output.
 ///
@@ -0,0 +1,72 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/io/io_pngchunks.rs
+
+use crate::structs::struct_colorspace::ColorSpace;
+
+/// The eight byte signature every PNG file starts with.
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// Detects the color space of a PNG file from its `sRGB`, `iCCP` and `gAMA` chunks.
+///
+/// Following the PNG specification, `sRGB` and `iCCP` take precedence over `gAMA`.
+/// An ICC profile is assumed to be sRGB unless its name mentions "linear".
+///
+/// # Arguments
+///
+/// * `bytes` - Contents of the PNG file.
+///
+/// # Returns
+///
+/// * `Some(ColorSpace)` if the file carries color space information.
+/// * `None` if the file has no such chunks or is not a PNG.
+pub fn read_png_color_space(bytes: &[u8]) -> Option<ColorSpace> {
+    if bytes.len() < PNG_SIGNATURE.len() || bytes[..8] != PNG_SIGNATURE {
+        return None;
+    }
+
+    let mut gamma = None;
+    let mut offset = PNG_SIGNATURE.len();
+
+    // Walk the chunks up to the first image data chunk.
+    while offset + 8 <= bytes.len() {
+        let length = u32::from_be_bytes(bytes[offset..offset + 4].try_into().ok()?) as usize;
+        let chunk_type = &bytes[offset + 4..offset + 8];
+        let data_start = offset + 8;
+        let data_end = data_start.checked_add(length)?;
+        if data_end > bytes.len() {
+            return None;
+        }
+        let data = &bytes[data_start..data_end];
+
+        match chunk_type {
+            b"sRGB" => return Some(ColorSpace::Srgb),
+            b"iCCP" => {
+                // The chunk starts with a null-terminated profile name.
+                let name_end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+                let name = String::from_utf8_lossy(&data[..name_end]).to_lowercase();
+                return Some(if name.contains("linear") {
+                    ColorSpace::Linear
+                } else {
+                    ColorSpace::Srgb
+                });
+            }
+            b"gAMA" if data.len() == 4 => {
+                // Stored as the encoding gamma times 100000 (45455 for sRGB-like data).
+                let value = u32::from_be_bytes(data.try_into().ok()?) as f32 / 100_000.0;
+                gamma = Some(value);
+            }
+            b"IDAT" | b"IEND" => break,
+            _ => {}
+        }
+
+        // Skip the chunk data and its CRC.
+        offset = data_end + 4;
+    }
+
+    gamma.map(|value| {
+        if (value - 1.0).abs() < 0.05 {
+            ColorSpace::Linear
+        } else {
+            ColorSpace::Srgb
+        }
+    })
+}
@@ -0,0 +1,396 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/io/io_psd.rs
+
+use image::{DynamicImage, ImageBuffer, Luma, LumaA, Rgb, Rgba};
+
+/// PSD color mode for grayscale images.
+const COLOR_MODE_GRAYSCALE: u16 = 1;
+
+/// PSD color mode for RGB images.
+const COLOR_MODE_RGB: u16 = 3;
+
+/// Largest width or height of a PSD file.
+const MAX_PSD_DIMENSION: u32 = 30_000;
+
+/// Largest width or height of a PSB (large document format) file.
+const MAX_PSB_DIMENSION: u32 = 300_000;
+
+/// Largest number of bytes a PackBits run of two bytes unpacks to.
+const MAX_PACKBITS_RATIO: usize = 64;
+
+/// Minimal big-endian reader over the bytes of a PSD file.
+struct PsdReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> PsdReader<'a> {
+    /// Takes the next `len` bytes.
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| "Unexpected end of PSD file".to_string())?;
+        let slice = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+
+    /// Reads a big-endian `u16`.
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    /// Reads a big-endian `u32`.
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    /// Reads a big-endian `u64`.
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_be_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// Skips a section prefixed with a `u32` length.
+    fn skip_section(&mut self) -> Result<(), String> {
+        let len = self.u32()? as usize;
+        self.take(len).map(|_| ())
+    }
+}
+
+/// Loads the flattened composite image stored in a PSD or PSB file.
+///
+/// Photoshop stores a merged copy of all visible layers when "Maximize
+/// Compatibility" is enabled; layers themselves are not read. Grayscale and RGB
+/// documents with 8 or 16 bits per channel, raw or RLE compressed, are supported.
+///
+/// # Arguments
+///
+/// * `bytes` - Contents of the PSD file.
+///
+/// # Returns
+///
+/// * `Ok(DynamicImage)` with the composite image.
+/// * `Err(String)` with an error message if the file is unsupported or invalid.
+pub fn load_psd(bytes: &[u8]) -> Result<DynamicImage, String> {
+    decode_psd(bytes)
+}
+
+/// Decodes the flattened composite image from the bytes of a PSD or PSB file.
+fn decode_psd(bytes: &[u8]) -> Result<DynamicImage, String> {
+    let mut reader = PsdReader { bytes, offset: 0 };
+
+    // File header.
+    if reader.take(4)? != b"8BPS" {
+        return Err("Not a PSD file".to_string());
+    }
+    let version = reader.u16()?;
+    if version != 1 && version != 2 {
+        return Err(format!("Unsupported PSD version {}", version));
+    }
+    let is_psb = version == 2;
+    reader.take(6)?; // Reserved
+    let channels = reader.u16()? as usize;
+    let height = reader.u32()?;
+    let width = reader.u32()?;
+    let depth = reader.u16()?;
+    let color_mode = reader.u16()?;
+
+    let color_channels = match color_mode {
+        COLOR_MODE_GRAYSCALE => 1,
+        COLOR_MODE_RGB => 3,
+        _ => return Err(format!("Unsupported PSD color mode {}", color_mode)),
+    };
+    if channels < color_channels {
+        return Err(format!(
+            "PSD has {} channels, expected at least {}",
+            channels, color_channels
+        ));
+    }
+    if depth != 8 && depth != 16 {
+        return Err(format!("Unsupported PSD bit depth {}", depth));
+    }
+    let max_dimension = if is_psb {
+        MAX_PSB_DIMENSION
+    } else {
+        MAX_PSD_DIMENSION
+    };
+    if !(1..=max_dimension).contains(&width) || !(1..=max_dimension).contains(&height) {
+        return Err(format!(
+            "Unsupported PSD dimensions {}x{}, expected 1 to {} pixels",
+            width, height, max_dimension
+        ));
+    }
+
+    // Color mode data, image resources, and layer and mask information.
+    reader.skip_section()?;
+    reader.skip_section()?;
+    let layer_section_len = if is_psb {
+        reader.u64()?
+    } else {
+        reader.u32()? as u64
+    };
+    let layer_section =
+        reader.take(usize::try_from(layer_section_len).map_err(|e| e.to_string())?)?;
+    let transparent = has_merged_transparency(layer_section, is_psb);
+
+    // Image data section holding the merged composite.
+    let compression = reader.u16()?;
+    let too_large = || format!("PSD image of {}x{} pixels is too large", width, height);
+    let bytes_per_sample = (depth / 8) as usize;
+    let row_len = (width as usize)
+        .checked_mul(bytes_per_sample)
+        .ok_or_else(too_large)?;
+    let rows = (height as usize)
+        .checked_mul(channels)
+        .ok_or_else(too_large)?;
+    let planar_len = row_len.checked_mul(rows).ok_or_else(too_large)?;
+
+    let planar = match compression {
+        0 => reader.take(planar_len)?.to_vec(),
+        1 => {
+            // Byte counts for every row of every channel precede the RLE data.
+            let count_len = if is_psb { 4 } else { 2 };
+            let counts = reader.take(rows.checked_mul(count_len).ok_or_else(too_large)?)?;
+            let counts: Vec<usize> = counts
+                .chunks_exact(count_len)
+                .map(|count| count.iter().fold(0, |acc, &byte| acc << 8 | byte as usize))
+                .collect();
+            let packed_len = counts
+                .iter()
+                .try_fold(0usize, |acc, &count| acc.checked_add(count))
+                .ok_or_else(too_large)?;
+            let packed = reader.take(packed_len)?;
+            if planar_len > packed_len.saturating_mul(MAX_PACKBITS_RATIO) {
+                return Err("Unexpected end of PSD file".to_string());
+            }
+
+            let mut planar = Vec::with_capacity(planar_len);
+            let mut packed = PsdReader {
+                bytes: packed,
+                offset: 0,
+            };
+            for count in counts {
+                unpack_bits(packed.take(count)?, row_len, &mut planar)?;
+            }
+            planar
+        }
+        _ => return Err(format!("Unsupported PSD compression {}", compression)),
+    };
+
+    // Only keep the color channels, and the first extra channel as alpha if the
+    // composite is transparent; other extra channels are saved selections.
+    let has_alpha = transparent && channels > color_channels;
+    let out_channels = color_channels + usize::from(has_alpha);
+    let plane_len = row_len * height as usize;
+    let pixel_count = width as usize * height as usize;
+
+    let sample = |channel: usize, pixel: usize| -> u16 {
+        let start = channel * plane_len + pixel * bytes_per_sample;
+        if bytes_per_sample == 2 {
+            u16::from_be_bytes([planar[start], planar[start + 1]])
+        } else {
+            planar[start] as u16
+        }
+    };
+
+    let mut interleaved = Vec::with_capacity(pixel_count * out_channels);
+    for pixel in 0..pixel_count {
+        for channel in 0..out_channels {
+            interleaved.push(sample(channel, pixel));
+        }
+    }
+
+    let invalid = || "Invalid PSD image dimensions".to_string();
+    if depth == 8 {
+        let data: Vec<u8> = interleaved.into_iter().map(|v| v as u8).collect();
+        Ok(match (color_channels, has_alpha) {
+            (1, false) => DynamicImage::ImageLuma8(
+                ImageBuffer::<Luma<u8>, _>::from_raw(width, height, data).ok_or_else(invalid)?,
+            ),
+            (1, true) => DynamicImage::ImageLumaA8(
+                ImageBuffer::<LumaA<u8>, _>::from_raw(width, height, data).ok_or_else(invalid)?,
+            ),
+            (_, false) => DynamicImage::ImageRgb8(
+                ImageBuffer::<Rgb<u8>, _>::from_raw(width, height, data).ok_or_else(invalid)?,
+            ),
+            (_, true) => DynamicImage::ImageRgba8(
+                ImageBuffer::<Rgba<u8>, _>::from_raw(width, height, data).ok_or_else(invalid)?,
+            ),
+        })
+    } else {
+        let data = interleaved;
+        Ok(match (color_channels, has_alpha) {
+            (1, false) => DynamicImage::ImageLuma16(
+                ImageBuffer::<Luma<u16>, _>::from_raw(width, height, data).ok_or_else(invalid)?,
+            ),
+            (1, true) => DynamicImage::ImageLumaA16(
+                ImageBuffer::<LumaA<u16>, _>::from_raw(width, height, data).ok_or_else(invalid)?,
+            ),
+            (_, false) => DynamicImage::ImageRgb16(
+                ImageBuffer::<Rgb<u16>, _>::from_raw(width, height, data).ok_or_else(invalid)?,
+            ),
+            (_, true) => DynamicImage::ImageRgba16(
+                ImageBuffer::<Rgba<u16>, _>::from_raw(width, height, data).ok_or_else(invalid)?,
+            ),
+        })
+    }
+}
+
+/// Returns `true` if the merged composite of a PSD file has transparency.
+///
+/// Photoshop signals this with a negative layer count, which means the first
+/// extra channel of the composite holds its alpha.
+///
+/// # Arguments
+///
+/// * `layer_section` - The layer and mask information section.
+/// * `is_psb` - Whether the file is a PSB, whose section lengths are 64 bits.
+fn has_merged_transparency(layer_section: &[u8], is_psb: bool) -> bool {
+    let mut reader = PsdReader {
+        bytes: layer_section,
+        offset: 0,
+    };
+    let layer_info_len = if is_psb {
+        reader.u64().ok()
+    } else {
+        reader.u32().ok().map(u64::from)
+    };
+    layer_info_len.is_some_and(|len| len >= 2) && reader.u16().is_ok_and(|count| (count as i16) < 0)
+}
+
+/// Decompresses one PackBits encoded row and appends it to `out`.
+///
+/// # Arguments
+///
+/// * `data` - The compressed row.
+/// * `row_len` - The expected decompressed row length in bytes.
+/// * `out` - Buffer the decompressed row is appended to.
+fn unpack_bits(data: &[u8], row_len: usize, out: &mut Vec<u8>) -> Result<(), String> {
+    let start = out.len();
+    let mut i = 0;
+    while i < data.len() && out.len() - start < row_len {
+        let header = data[i] as i8;
+        i += 1;
+        if header >= 0 {
+            // Copy the next header + 1 bytes literally.
+            let count = header as usize + 1;
+            let literal = data
+                .get(i..i + count)
+                .ok_or_else(|| "Corrupt PSD RLE data".to_string())?;
+            out.extend_from_slice(literal);
+            i += count;
+        } else if header != -128 {
+            // Repeat the next byte 1 - header times.
+            let count = (1 - header as isize) as usize;
+            let value = *data
+                .get(i)
+                .ok_or_else(|| "Corrupt PSD RLE data".to_string())?;
+            out.extend(std::iter::repeat_n(value, count));
+            i += 1;
+        }
+    }
+
+    if out.len() - start != row_len {
+        return Err("Corrupt PSD RLE data".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a PSD file with the given header fields, layer count and image data.
+    fn psd(
+        channels: u16,
+        width: u32,
+        height: u32,
+        layer_count: Option<i16>,
+        data: &[u8],
+    ) -> Vec<u8> {
+        let mut bytes = b"8BPS".to_vec();
+        bytes.extend_from_slice(&1u16.to_be_bytes());
+        bytes.extend_from_slice(&[0; 6]);
+        bytes.extend_from_slice(&channels.to_be_bytes());
+        bytes.extend_from_slice(&height.to_be_bytes());
+        bytes.extend_from_slice(&width.to_be_bytes());
+        bytes.extend_from_slice(&8u16.to_be_bytes());
+        bytes.extend_from_slice(&COLOR_MODE_RGB.to_be_bytes());
+        bytes.extend_from_slice(&0u32.to_be_bytes());
+        bytes.extend_from_slice(&0u32.to_be_bytes());
+        match layer_count {
+            Some(count) => {
+                bytes.extend_from_slice(&8u32.to_be_bytes());
+                bytes.extend_from_slice(&4u32.to_be_bytes());
+                bytes.extend_from_slice(&count.to_be_bytes());
+                bytes.extend_from_slice(&[0; 2]);
+            }
+            None => bytes.extend_from_slice(&0u32.to_be_bytes()),
+        }
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    #[test]
+    fn decodes_raw_rgb() {
+        let mut data = 0u16.to_be_bytes().to_vec();
+        data.extend_from_slice(&[10, 20, 30, 40, 50, 60]);
+        let image = decode_psd(&psd(3, 2, 1, None, &data)).unwrap().to_rgb8();
+        assert_eq!(image.dimensions(), (2, 1));
+        assert_eq!(image.as_raw(), &[10, 30, 50, 20, 40, 60]);
+    }
+
+    #[test]
+    fn uses_first_extra_channel_as_alpha_only_with_transparency() {
+        let mut data = 0u16.to_be_bytes().to_vec();
+        data.extend_from_slice(&[1, 2, 3, 128, 200]);
+        let transparent = decode_psd(&psd(5, 1, 1, Some(-1), &data)).unwrap();
+        assert_eq!(transparent.to_rgba8().as_raw(), &[1, 2, 3, 128]);
+
+        let opaque = decode_psd(&psd(5, 1, 1, Some(1), &data)).unwrap();
+        assert!(!opaque.color().has_alpha());
+        let flat = decode_psd(&psd(4, 1, 1, None, &data[..6])).unwrap();
+        assert!(!flat.color().has_alpha());
+    }
+
+    #[test]
+    fn decodes_rle_rows() {
+        let mut data = 1u16.to_be_bytes().to_vec();
+        for _ in 0..3 {
+            data.extend_from_slice(&2u16.to_be_bytes());
+        }
+        data.extend_from_slice(&[0xFD, 7, 0xFD, 8, 0xFD, 9]);
+        let image = decode_psd(&psd(3, 4, 1, None, &data)).unwrap().to_rgb8();
+        assert!(image.pixels().all(|pixel| pixel.0 == [7, 8, 9]));
+    }
+
+    #[test]
+    fn rejects_oversized_and_truncated_files() {
+        let data = 0u16.to_be_bytes();
+        assert!(decode_psd(&psd(3, 30_001, 1, None, &data)).is_err());
+        assert!(decode_psd(&psd(3, 1, 0, None, &data)).is_err());
+        assert!(decode_psd(&psd(3, 30_000, 30_000, None, &data)).is_err());
+
+        // RLE counts claiming more output than the packed data can hold.
+        let mut data = 1u16.to_be_bytes().to_vec();
+        for _ in 0..3 {
+            data.extend_from_slice(&0u16.to_be_bytes());
+        }
+        assert!(decode_psd(&psd(3, 30_000, 1, None, &data)).is_err());
+    }
+
+    #[test]
+    fn unpack_bits_handles_literals_runs_and_no_ops() {
+        let mut out = vec![0xAA];
+        unpack_bits(&[0x02, 1, 2, 3, 0x80, 0xFE, 9], 6, &mut out).unwrap();
+        assert_eq!(out, [0xAA, 1, 2, 3, 9, 9, 9]);
+    }
+
+    #[test]
+    fn unpack_bits_rejects_corrupt_rows() {
+        assert!(unpack_bits(&[0x05, 1, 2], 6, &mut Vec::new()).is_err());
+        assert!(unpack_bits(&[0xFE], 3, &mut Vec::new()).is_err());
+        assert!(unpack_bits(&[0xFE, 1], 4, &mut Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,64 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/io/io_report.rs
+
+use std::path::{Path, PathBuf};
+
+use crate::structs::struct_runreport::{RunReport, RUN_REPORT_VERSION};
+
+use super::{io_backend::is_archive_path, io_out::create_parent_directory};
+
+/// File name of the run report inside the output directory.
+pub const RUN_REPORT_FILE: &str = ".dds-edit-report.json";
+
+/// Returns the path of the run report for an output directory or archive.
+///
+/// The report of an archive is written next to it (`mod.zip` gets
+/// `mod.dds-edit-report.json`), so that it is not distributed with the outputs.
+///
+/// # Arguments
+///
+/// * `path_out` - The output directory or archive.
+pub fn run_report_path(path_out: &Path) -> PathBuf {
+    if is_archive_path(path_out) {
+        path_out.with_extension(&RUN_REPORT_FILE[1..])
+    } else {
+        path_out.join(RUN_REPORT_FILE)
+    }
+}
+
+/// Loads the run report of the previous run.
+///
+/// # Arguments
+///
+/// * `path_in` - The input directory of the run.
+/// * `path_out` - The output directory or archive.
+///
+/// # Returns
+///
+/// * `Some(RunReport)` with the previous report, or `None` if it is missing,
+///   unreadable, of another layout version or for another input directory.
+pub fn load_run_report(path_in: &str, path_out: &Path) -> Option<RunReport> {
+    let content = std::fs::read_to_string(run_report_path(path_out)).ok()?;
+    serde_json::from_str::<RunReport>(&content)
+        .ok()
+        .filter(|report| report.version == RUN_REPORT_VERSION && report.path_in == path_in)
+}
+
+/// Writes the run report for the output directory or archive, replacing the report of the previous run.
+///
+/// # Arguments
+///
+/// * `report` - The report to write.
+/// * `path_out` - The output directory or archive.
+///
+/// # Returns
+///
+/// * `Ok(())` if the report was written.
+/// * `Err(String)` with an error message if serializing or writing fails.
+pub fn save_run_report(report: &RunReport, path_out: &Path) -> Result<(), String> {
+    let path = run_report_path(path_out);
+    let content = serde_json::to_string_pretty(report)
+        .map_err(|e| format!("Failed to serialize run report: {}", e))?;
+    create_parent_directory(&path)?;
+    std::fs::write(&path, content)
+        .map_err(|e| format!("Failed to write run report '{}': {}", path.display(), e))
+}
@@ -0,0 +1,370 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/io/io_source.rs
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::Read,
+    path::{Component, Path, PathBuf},
+};
+
+use zip::ZipArchive;
+
+use super::io_backend::is_archive_path;
+
+/// Largest buffer reserved up front for an archive entry; the size recorded
+/// in the archive is not trusted beyond that.
+const MAX_ENTRY_RESERVE: u64 = 64 * 1024 * 1024;
+
+/// Where an input file is read from.
+///
+/// Files inside a zip archive are addressed by the archive path joined with
+/// the entry name (e.g., `packs/armor.zip/textures/cuirass.png`), so they can
+/// be listed, filtered and mirrored like loose files.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputSource {
+    /// A file on disk.
+    File(PathBuf),
+
+    /// An entry of a zip archive.
+    Archive {
+        /// Path of the archive on disk.
+        archive: PathBuf,
+
+        /// Name of the entry inside the archive, using `/` separators.
+        entry: String,
+    },
+}
+
+impl InputSource {
+    /// Resolves the source of an input path.
+    ///
+    /// A path below an existing `.zip` file names one of its entries; any
+    /// other path is a file on disk. Archives nested in archives are not
+    /// looked into.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The input path.
+    pub fn of(path: &Path) -> Self {
+        match path.parent().and_then(enclosing_archive) {
+            Some(archive) => InputSource::Archive {
+                archive: archive.to_path_buf(),
+                entry: entry_name(path.strip_prefix(archive).unwrap_or(path)),
+            },
+            None => InputSource::File(path.to_path_buf()),
+        }
+    }
+
+    /// Reads the whole input into memory.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<u8>)` with the contents of the input.
+    /// * `Err(String)` with an error message if the file or entry cannot be read.
+    pub fn read(&self) -> Result<Vec<u8>, String> {
+        match self {
+            InputSource::File(path) => std::fs::read(path)
+                .map_err(|e| format!("Failed to read '{}': {}", path.display(), e)),
+            InputSource::Archive { archive, entry } => {
+                let mut zip = open_archive(archive)?;
+                let mut file = zip.by_name(entry).map_err(|e| {
+                    format!(
+                        "Failed to open '{}' in '{}': {}",
+                        entry,
+                        archive.display(),
+                        e
+                    )
+                })?;
+                let mut bytes = Vec::with_capacity(file.size().min(MAX_ENTRY_RESERVE) as usize);
+                file.read_to_end(&mut bytes).map_err(|e| {
+                    format!(
+                        "Failed to read '{}' in '{}': {}",
+                        entry,
+                        archive.display(),
+                        e
+                    )
+                })?;
+                Ok(bytes)
+            }
+        }
+    }
+
+    /// Returns `true` if the input exists and is a file.
+    pub fn exists(&self) -> bool {
+        match self {
+            InputSource::File(path) => path.is_file(),
+            InputSource::Archive { archive, entry } => {
+                open_archive(archive).is_ok_and(|zip| zip.index_for_name(entry).is_some())
+            }
+        }
+    }
+
+    /// Returns the size of the input in bytes, or 0 if it cannot be read.
+    pub fn size(&self) -> u64 {
+        match self {
+            InputSource::File(path) => std::fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+            InputSource::Archive { archive, entry } => open_archive(archive)
+                .ok()
+                .and_then(|mut zip| zip.by_name(entry).map(|file| file.size()).ok())
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// Reads an input file or archive entry into memory.
+///
+/// # Arguments
+///
+/// * `path` - The input path, possibly below a `.zip` file.
+///
+/// # Returns
+///
+/// * `Ok(Vec<u8>)` with the contents of the input.
+/// * `Err(String)` with an error message if it cannot be read.
+pub fn read_input(path: &Path) -> Result<Vec<u8>, String> {
+    InputSource::of(path).read()
+}
+
+/// Returns `true` if a path names an entry inside a zip archive.
+///
+/// # Arguments
+///
+/// * `path` - The input path.
+pub fn is_archive_entry(path: &Path) -> bool {
+    matches!(InputSource::of(path), InputSource::Archive { .. })
+}
+
+/// Lists the files of a zip archive as input paths below the archive path.
+///
+/// Directory entries and entries with absolute or `..` names are left out.
+///
+/// # Arguments
+///
+/// * `archive` - Path of the archive.
+///
+/// # Returns
+///
+/// * `Ok(Vec<PathBuf>)` with the archive path joined with every file entry.
+/// * `Err(String)` with an error message if the archive cannot be read.
+pub fn list_archive(archive: &Path) -> Result<Vec<PathBuf>, String> {
+    let zip = open_archive(archive)?;
+    let mut paths: Vec<PathBuf> = zip
+        .file_names()
+        .filter(|name| !name.ends_with('/'))
+        .filter(|name| {
+            Path::new(name)
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+        })
+        .map(|name| archive.join(name))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Lists the files directly inside a directory of the input tree.
+///
+/// Works for directories on disk as well as for an archive and the
+/// directories inside it.
+///
+/// # Arguments
+///
+/// * `directory` - The directory to list.
+///
+/// # Returns
+///
+/// * `Vec<PathBuf>` with the files, empty if the directory cannot be read.
+pub fn list_input_directory(directory: &Path) -> Vec<PathBuf> {
+    match enclosing_archive(directory) {
+        Some(archive) => list_archive(archive)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|path| path.parent() == Some(directory))
+            .collect(),
+        None => std::fs::read_dir(directory)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| path.is_file())
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
+}
+
+/// Returns the path of an input relative to the root it is mirrored from.
+///
+/// Archive entries are relative to the root of their archive, placed in the
+/// directory of the archive, so that `packs/armor.zip/textures/cuirass.png`
+/// is written like a loose `packs/textures/cuirass.png`; other inputs are
+/// relative to `base_in`.
+///
+/// # Arguments
+///
+/// * `base_in` - The input path of the run.
+/// * `path` - The input to resolve.
+///
+/// # Returns
+///
+/// * `Ok(PathBuf)` with the relative path.
+/// * `Err(String)` with an error message if `path` is outside of `base_in`.
+pub fn relative_input_path(base_in: &Path, path: &Path) -> Result<PathBuf, String> {
+    match InputSource::of(path) {
+        InputSource::Archive { archive, entry } => {
+            let directory = archive
+                .parent()
+                .and_then(|parent| parent.strip_prefix(base_in).ok())
+                .unwrap_or(Path::new(""));
+            Ok(directory.join(entry))
+        }
+        InputSource::File(_) => path
+            .strip_prefix(base_in)
+            .map(Path::to_path_buf)
+            .map_err(|_| format!("'{}' is outside of '{}'", path.display(), base_in.display())),
+    }
+}
+
+/// Checks that no two inputs are mirrored to the same relative path.
+///
+/// An archive entry and a loose file, or entries of two archives in the same
+/// directory, with the same relative path would overwrite each other's outputs.
+///
+/// # Arguments
+///
+/// * `base_in` - The input path of the run.
+/// * `paths` - The inputs of the run.
+///
+/// # Returns
+///
+/// * `Ok(())` if every input has its own relative path.
+/// * `Err(String)` listing the inputs that collide.
+pub fn check_input_collisions(base_in: &Path, paths: &[PathBuf]) -> Result<(), String> {
+    let mut inputs: BTreeMap<PathBuf, Vec<&Path>> = BTreeMap::new();
+    for path in paths {
+        let relative = relative_input_path(base_in, path).unwrap_or_else(|_| path.clone());
+        inputs.entry(relative).or_default().push(path);
+    }
+
+    let collisions: Vec<String> = inputs
+        .into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .map(|(relative, paths)| {
+            let paths: Vec<String> = paths
+                .iter()
+                .map(|path| format!("'{}'", path.display()))
+                .collect();
+            format!("  '{}': {}", relative.display(), paths.join(", "))
+        })
+        .collect();
+    if collisions.is_empty() {
+        return Ok(());
+    }
+    Err(format!(
+        "Inputs would be written to the same outputs:\n{}",
+        collisions.join("\n")
+    ))
+}
+
+/// Returns the archive a path lies in, or the path itself if it is an archive.
+fn enclosing_archive(path: &Path) -> Option<&Path> {
+    path.ancestors()
+        .find(|ancestor| is_archive_path(ancestor) && ancestor.is_file())
+}
+
+/// Opens a zip archive for reading.
+fn open_archive(archive: &Path) -> Result<ZipArchive<File>, String> {
+    let file = File::open(archive)
+        .map_err(|e| format!("Failed to open archive '{}': {}", archive.display(), e))?;
+    ZipArchive::new(file)
+        .map_err(|e| format!("Failed to read archive '{}': {}", archive.display(), e))
+}
+
+/// Converts a path relative to an archive into an entry name with `/` separators.
+fn entry_name(relative: &Path) -> String {
+    relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    /// Writes a zip archive with the given entries.
+    fn write_zip(path: &Path, entries: &[(&str, &[u8])]) {
+        let mut zip = ZipWriter::new(File::create(path).unwrap());
+        for (name, bytes) in entries {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(bytes).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn resolves_and_reads_archive_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("armor.zip");
+        write_zip(
+            &archive,
+            &[
+                ("textures/", b""),
+                ("textures/cuirass.png", b"png"),
+                ("../evil.png", b""),
+            ],
+        );
+
+        let entry = archive.join("textures").join("cuirass.png");
+        assert_eq!(
+            InputSource::of(&entry),
+            InputSource::Archive {
+                archive: archive.clone(),
+                entry: "textures/cuirass.png".to_string()
+            }
+        );
+        assert_eq!(read_input(&entry).unwrap(), b"png");
+        assert!(InputSource::of(&entry).exists());
+        assert!(!InputSource::of(&archive.join("missing.png")).exists());
+        assert_eq!(list_archive(&archive).unwrap(), vec![entry.clone()]);
+        assert_eq!(list_input_directory(&archive.join("textures")), vec![entry]);
+    }
+
+    #[test]
+    fn mirrors_entries_in_the_archive_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("packs")).unwrap();
+        let archive = dir.path().join("packs").join("armor.zip");
+        write_zip(&archive, &[("textures/cuirass.png", b"")]);
+        let entry = archive.join("textures").join("cuirass.png");
+
+        assert_eq!(
+            relative_input_path(dir.path(), &entry).unwrap(),
+            Path::new("packs").join("textures").join("cuirass.png")
+        );
+        assert_eq!(
+            relative_input_path(&archive, &entry).unwrap(),
+            Path::new("textures").join("cuirass.png")
+        );
+        assert!(relative_input_path(&dir.path().join("other"), &dir.path().join("a.png")).is_err());
+    }
+
+    #[test]
+    fn reports_inputs_mirrored_to_the_same_path() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["a.zip", "b.zip"] {
+            write_zip(&dir.path().join(name), &[("cuirass.png", b"")]);
+        }
+        let loose = dir.path().join("boots.png");
+        std::fs::write(&loose, b"").unwrap();
+        let a = dir.path().join("a.zip").join("cuirass.png");
+        let b = dir.path().join("b.zip").join("cuirass.png");
+
+        assert!(check_input_collisions(dir.path(), &[a.clone(), loose.clone()]).is_ok());
+        let error = check_input_collisions(dir.path(), &[a, b, loose]).unwrap_err();
+        assert!(error.contains("a.zip") && error.contains("b.zip"));
+        assert!(!error.contains("boots.png"));
+    }
+}
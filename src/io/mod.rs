@@ -1,2 +1,11 @@
+pub mod io_backend;
+pub mod io_cache;
+pub mod io_dds;
+pub mod io_in;
+pub mod io_naming;
+pub mod io_out;
 pub mod io_pathtree;
-pub mod io_out;
\ No newline at end of file
+pub mod io_pngchunks;
+pub mod io_psd;
+pub mod io_report;
+pub mod io_source;
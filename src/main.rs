@@ -1,32 +1,55 @@
 // D:/Work/Projects/Modding/Skyrim/dds-edit/src/main.rs
 
 use crate::{
+    io::io_backend::is_archive_path,
     io::io_out::setup_output_directory,
     io::io_pathtree::{filter_pathtree_types, get_path_tree},
+    io::io_source::check_input_collisions,
     logging::{smnprint, smnprintln},
-    processing::processing_core::process_files,
+    processing::{
+        processing_core::process_files, processing_info::print_info,
+        processing_verify::verify_files, processing_watch::watch_files,
+    },
 };
-use cli::cli_args::cli_args::parse_arguments;
+use cli::{cli_args::cli_args::parse_arguments, cli_command::Command};
 use colored::Color;
 use io::io_pathtree::print_path_tree;
-use structs::{struct_config::Config, struct_configloader::load_config_from_file};
+use structs::{
+    struct_config::Config,
+    struct_configloader::load_config_from_file,
+    struct_ddsconfig::{DdsQuality, MipFilter, MipmapSetting},
+    struct_filetype::FileType,
+    struct_gameprofile::GameProfile,
+    struct_outputlayout::OutputLayout,
+    struct_resizeconfig::ResizeFilter,
+    struct_sizespec::{Pow2Policy, SizeSpec, UpscalePolicy},
+};
+
+use std::path::{Path, PathBuf};
 
-mod structs;
 mod cli;
 mod io;
-mod processing;
 mod logging;
+mod processing;
+mod structs;
 
 #[tokio::main]
 async fn main() {
     // Parse and validate command-line arguments.
-    let cli_config = match parse_arguments() {
-        Ok(cfg) => cfg,
+    let cli_args = match parse_arguments() {
+        Ok(args) => args,
         Err(e) => {
             eprintln!("{}", e);
+            eprintln!("Run 'dds-edit help' for usage.");
             std::process::exit(1);
         }
     };
+    if let Some(help) = cli_args.help {
+        print!("{}", help);
+        return;
+    }
+    let command = cli_args.command;
+    let cli_config = cli_args.config;
 
     // Initialize the configuration with defaults.
     let mut config = Config::default();
@@ -54,15 +77,162 @@ async fn main() {
         std::process::exit(1);
     }
 
+    // A package has one folder per resolution option.
+    if config.package.is_some() && config.layout != OutputLayout::PerSize && !config.decode_only {
+        smnprintln(
+            format!(
+                "Packaging uses the per_size layout instead of {}.",
+                config.layout
+            ),
+            Color::Yellow,
+        );
+        config.layout = OutputLayout::PerSize;
+    }
+
+    // Outputs of different target sizes must not overwrite each other.
+    if matches!(command, Command::Convert | Command::Verify) && !config.decode_only {
+        if let Err(e) = config.validate_naming() {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    // Archives are written from scratch in a single pass and are not read back.
+    if is_archive_path(Path::new(&config.path_out)) {
+        let unsupported = match command {
+            Command::Verify => Some("verify"),
+            Command::Convert | Command::Decode if config.watch => Some("--watch"),
+            _ => None,
+        };
+        if let Some(unsupported) = unsupported {
+            eprintln!(
+                "Error: {} needs an output directory, not an archive.",
+                unsupported
+            );
+            std::process::exit(1);
+        }
+    }
+    if is_archive_path(Path::new(&config.path_in)) && config.watch {
+        eprintln!("Error: --watch needs an input directory, not an archive.");
+        std::process::exit(1);
+    }
+
+    match command {
+        Command::Info => run_info(&config),
+        Command::Verify => run_verify(&config).await,
+        Command::Convert | Command::Decode => run_convert(&config).await,
+    }
+}
+
+/// Runs the `convert` and `decode` subcommands.
+///
+/// # Arguments
+///
+/// * `config` - Reference to the merged `Config`.
+async fn run_convert(config: &Config) {
     // Log the parsed configuration parameters.
-    log_parameters(&config);
+    log_parameters(config);
+
+    // Setup the output directory or archive.
+    let config = &Config {
+        output: match setup_output_directory(&config.path_out) {
+            Ok(output) => output,
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        },
+        ..config.clone()
+    };
+
+    // Define supported file types; decode mode only takes DDS inputs.
+    let supported_types: &[&str] = if config.decode_only {
+        &["dds"]
+    } else {
+        FileType::supported_extensions()
+    };
+
+    // Collect the input files of supported types.
+    let filtered_paths = collect_inputs(config, supported_types);
+    check_outputs_distinct(config, &filtered_paths);
+
+    if filtered_paths.is_empty() {
+        eprintln!("No supported image files found in '{}'.", config.path_in);
+    } else {
+        print_path_tree(&filtered_paths, config.max_depth);
+
+        // Process the filtered image files.
+        if let Err(e) = process_files(&filtered_paths, config).await {
+            eprintln!("Error during processing: {}", e);
+            std::process::exit(1);
+        }
+    }
 
-    // Setup the output directory.
-    if let Err(e) = setup_output_directory(&config.path_out) {
+    // Keep reprocessing inputs as they change.
+    if config.watch {
+        if let Err(e) = watch_files(config, supported_types).await {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+
+    // An archive can only be opened once its central directory is written.
+    if let Err(e) = config.output.finish() {
         eprintln!("{}", e);
         std::process::exit(1);
     }
+}
+
+/// Runs the `info` subcommand on a file or directory.
+///
+/// # Arguments
+///
+/// * `config` - Reference to the merged `Config`.
+fn run_info(config: &Config) {
+    let filtered_paths = collect_inputs(config, FileType::supported_extensions());
+    if filtered_paths.is_empty() {
+        eprintln!("No supported image files found in '{}'.", config.path_in);
+        std::process::exit(1);
+    }
+
+    if !print_info(&filtered_paths, config.json_output) {
+        std::process::exit(1);
+    }
+}
 
+/// Runs the `verify` subcommand.
+///
+/// # Arguments
+///
+/// * `config` - Reference to the merged `Config`.
+async fn run_verify(config: &Config) {
+    log_parameters(config);
+
+    let filtered_paths = collect_inputs(config, FileType::supported_extensions());
+    check_outputs_distinct(config, &filtered_paths);
+    if filtered_paths.is_empty() {
+        eprintln!("No supported image files found in '{}'.", config.path_in);
+        std::process::exit(0);
+    }
+
+    if !verify_files(&filtered_paths, config).await {
+        std::process::exit(1);
+    }
+}
+
+/// Collects the input files of the given types below `path_in`, up to `max_depth`.
+///
+/// Exits the process if the input path cannot be traversed.
+///
+/// # Arguments
+///
+/// * `config` - Reference to the merged `Config`.
+/// * `supported_types` - File extensions to include.
+///
+/// # Returns
+///
+/// * `Vec<PathBuf>` with the matching files.
+fn collect_inputs(config: &Config, supported_types: &[&str]) -> Vec<PathBuf> {
     // Get all file paths up to the specified max_depth.
     let all_paths = match get_path_tree(&config.path_in, config.max_depth) {
         Ok(paths) => paths,
@@ -72,22 +242,20 @@ async fn main() {
         }
     };
 
-    // Define supported file types.
-    let supported_types = vec!["jpg", "jpeg", "png"];
-
     // Filter paths to include only supported image types.
-    let filtered_paths = filter_pathtree_types(&all_paths, &supported_types);
-
-    if filtered_paths.is_empty() {
-        eprintln!("No supported image files found in '{}'.", config.path_in);
-        std::process::exit(0);
-    }
-
-    print_path_tree(&filtered_paths, config.max_depth);
+    filter_pathtree_types(&all_paths, supported_types)
+}
 
-    // Process the filtered image files.
-    if let Err(e) = process_files(&filtered_paths, &config).await {
-        eprintln!("Error during processing: {}", e);
+/// Exits the process if two inputs are mirrored to the same relative path,
+/// since they would overwrite each other's outputs.
+///
+/// # Arguments
+///
+/// * `config` - Reference to the merged `Config`.
+/// * `filtered_paths` - The collected input files.
+fn check_outputs_distinct(config: &Config, filtered_paths: &[PathBuf]) {
+    if let Err(e) = check_input_collisions(Path::new(&config.path_in), filtered_paths) {
+        eprintln!("{}", e);
         std::process::exit(1);
     }
 }
@@ -120,17 +288,93 @@ fn merge_configs(file_config: Config, cli_config: Config) -> Config {
         } else {
             file_config.max_depth
         },
-        target_sizes: if cli_config.target_sizes != vec![2048] {
+        layout: if cli_config.layout != OutputLayout::Mirrored {
+            cli_config.layout
+        } else {
+            file_config.layout
+        },
+        name_template: cli_config.name_template.or(file_config.name_template),
+        target_sizes: if cli_config.target_sizes != vec![SizeSpec::Width(2048)] {
             cli_config.target_sizes
         } else {
             file_config.target_sizes
         },
+        upscale: if cli_config.upscale != UpscalePolicy::Skip {
+            cli_config.upscale
+        } else {
+            file_config.upscale
+        },
+        upscale_filter: if cli_config.upscale_filter != ResizeFilter::CatmullRom {
+            cli_config.upscale_filter
+        } else {
+            file_config.upscale_filter
+        },
+        pow2: if cli_config.pow2 != Pow2Policy::None {
+            cli_config.pow2
+        } else {
+            file_config.pow2
+        },
+        multiple_of_4: cli_config.multiple_of_4 && file_config.multiple_of_4,
         threads: if cli_config.threads != 4 {
             cli_config.threads
         } else {
             file_config.threads
         },
+        resize_filter: if cli_config.resize_filter != ResizeFilter::Triangle {
+            cli_config.resize_filter
+        } else {
+            file_config.resize_filter
+        },
+        sharpen: cli_config.sharpen.or(file_config.sharpen),
+        quality: if cli_config.quality != DdsQuality::Normal {
+            cli_config.quality
+        } else {
+            file_config.quality
+        },
+        mipmaps: if cli_config.mipmaps != MipmapSetting::Auto {
+            cli_config.mipmaps
+        } else {
+            file_config.mipmaps
+        },
+        mip_filter: if cli_config.mip_filter != MipFilter::Box {
+            cli_config.mip_filter
+        } else {
+            file_config.mip_filter
+        },
+        alpha_test_ref: cli_config.alpha_test_ref.or(file_config.alpha_test_ref),
+        format: cli_config.format.or(file_config.format),
+        game: if cli_config.game != GameProfile::Dx10 {
+            cli_config.game
+        } else {
+            file_config.game
+        },
+        role_suffixes: file_config.role_suffixes, // Only configurable through the file
+        color_space: file_config.color_space,
+        role: file_config.role,
+        normal_from_height: file_config
+            .normal_from_height
+            .or(cli_config.normal_from_height),
+        package: file_config.package.or(cli_config.package),
+        channel_ops: if !cli_config.channel_ops.is_empty() {
+            cli_config.channel_ops
+        } else {
+            file_config.channel_ops
+        },
+        renormalize_normals: cli_config.renormalize_normals && file_config.renormalize_normals,
+        rules: file_config.rules,
+        packs: file_config.packs, // Only configurable through the file
+        decode_only: cli_config.decode_only || file_config.decode_only,
+        keep_intermediate: cli_config.keep_intermediate || file_config.keep_intermediate,
+        force: cli_config.force || file_config.force,
+        json_output: cli_config.json_output,
+        watch: cli_config.watch || file_config.watch,
+        watch_debounce_ms: if cli_config.watch_debounce_ms != 500 {
+            cli_config.watch_debounce_ms
+        } else {
+            file_config.watch_debounce_ms
+        },
         config_file: cli_config.config_file, // Retain the config_file field from CLI if needed
+        output: cli_config.output,
     }
 }
 
@@ -144,13 +388,61 @@ fn log_parameters(config: &Config) {
     smnprint("Input Path: ", Color::White);
     smnprintln(&config.path_in, Color::BrightCyan);
     smnprint("Output Path: ", Color::White);
-    smnprintln(&config.path_out, Color::BrightCyan);
+    if is_archive_path(Path::new(&config.path_out)) {
+        smnprintln(
+            format!("{} (Zip Archive)", config.path_out),
+            Color::BrightCyan,
+        );
+    } else {
+        smnprintln(&config.path_out, Color::BrightCyan);
+    }
     smnprint("Max Depth: ", Color::White);
-    smnprintln(&config.max_depth.to_string(), Color::BrightCyan);
+    smnprintln(config.max_depth.to_string(), Color::BrightCyan);
+    smnprint("Layout: ", Color::White);
+    smnprintln(
+        format!(
+            "{}, {}",
+            config.layout,
+            config
+                .name_template
+                .as_deref()
+                .unwrap_or_else(|| config.layout.default_template())
+        ),
+        Color::BrightCyan,
+    );
     smnprint("Threads: ", Color::White);
-    smnprintln(&config.threads.to_string(), Color::BrightCyan);
+    smnprintln(config.threads.to_string(), Color::BrightCyan);
+    smnprint("Resize Filter: ", Color::White);
+    smnprintln(config.resize_filter.to_string(), Color::BrightCyan);
+    if let Some(sharpen) = &config.sharpen {
+        smnprint("Sharpen: ", Color::White);
+        smnprintln(
+            format!(
+                "Amount = {}, Radius = {}, Threshold = {}",
+                sharpen.amount, sharpen.radius, sharpen.threshold
+            ),
+            Color::BrightCyan,
+        );
+    }
+    smnprint("Quality: ", Color::White);
+    smnprintln(config.quality.to_string(), Color::BrightCyan);
+    smnprint("Mipmaps: ", Color::White);
+    smnprintln(config.mipmaps.to_string(), Color::BrightCyan);
+    smnprint("Mip Filter: ", Color::White);
+    smnprintln(config.mip_filter.to_string(), Color::BrightCyan);
+    if let Some(alpha_test_ref) = config.alpha_test_ref {
+        smnprint("Alpha Test Reference: ", Color::White);
+        smnprintln(alpha_test_ref.to_string(), Color::BrightCyan);
+    }
+    smnprint("Format: ", Color::White);
+    match &config.format {
+        Some(format) => smnprintln(format.to_string(), Color::BrightCyan),
+        None => smnprintln("Auto (by texture role)", Color::BrightCyan),
+    }
+    smnprint("Game: ", Color::White);
+    smnprintln(config.game.to_string(), Color::BrightCyan);
 
-    smnprint("Width Targets: ", Color::White);
+    smnprint("Size Targets: ", Color::White);
     for (i, size) in config.target_sizes.iter().enumerate() {
         if i > 0 {
             smnprint(", ", Color::White);
@@ -158,4 +450,29 @@ fn log_parameters(config: &Config) {
         smnprint(size.to_string(), Color::BrightCyan);
     }
     println!();
+    smnprint("Upscale: ", Color::White);
+    match config.upscale {
+        UpscalePolicy::Upscale => smnprintln(
+            format!("{} ({})", config.upscale, config.upscale_filter),
+            Color::BrightCyan,
+        ),
+        _ => smnprintln(config.upscale.to_string(), Color::BrightCyan),
+    }
+    smnprint("Power of Two: ", Color::White);
+    smnprintln(config.pow2.to_string(), Color::BrightCyan);
+    if let Some(package) = &config.package {
+        smnprint("Package: ", Color::White);
+        smnprintln(
+            format!(
+                "{} {}",
+                if package.name.is_empty() {
+                    "(input directory name)"
+                } else {
+                    &package.name
+                },
+                package.version
+            ),
+            Color::BrightCyan,
+        );
+    }
 }
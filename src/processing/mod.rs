@@ -1,3 +1,17 @@
+pub mod processing_analysis;
+pub mod processing_bc1;
+pub mod processing_channels;
+pub mod processing_color;
 pub mod processing_core;
+pub mod processing_dds;
 pub mod processing_img;
-pub mod processing_dds;
\ No newline at end of file
+pub mod processing_info;
+pub mod processing_mips;
+pub mod processing_normal;
+pub mod processing_pack;
+pub mod processing_package;
+pub mod processing_plan;
+pub mod processing_sharpen;
+pub mod processing_size;
+pub mod processing_verify;
+pub mod processing_watch;
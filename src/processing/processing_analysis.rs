@@ -0,0 +1,80 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/processing/processing_analysis.rs
+
+use image::Rgba32FImage;
+
+use crate::structs::struct_imageanalysis::{AlphaContent, ImageAnalysis};
+
+/// Largest difference from 0 or 1, and between color channels, that still
+/// counts as equal. Half an 8-bit step, so 8-bit inputs compare exactly.
+const TOLERANCE: f32 = 0.5 / 255.0;
+
+/// Analyzes the alpha and color content of an image.
+///
+/// # Arguments
+///
+/// * `image` - The image, with channel operations already applied.
+///
+/// # Returns
+///
+/// * `ImageAnalysis` - How alpha is used and whether the image is grayscale.
+pub fn analyze_image(image: &Rgba32FImage) -> ImageAnalysis {
+    let mut alpha = AlphaContent::Opaque;
+    let mut grayscale = true;
+
+    for pixel in image.pixels() {
+        let a = pixel[3];
+        if a < 1.0 - TOLERANCE {
+            if a > TOLERANCE {
+                alpha = AlphaContent::Gradient;
+            } else if alpha == AlphaContent::Opaque {
+                alpha = AlphaContent::Binary;
+            }
+        }
+        if grayscale
+            && ((pixel[0] - pixel[1]).abs() > TOLERANCE || (pixel[1] - pixel[2]).abs() > TOLERANCE)
+        {
+            grayscale = false;
+        }
+        if alpha == AlphaContent::Gradient && !grayscale {
+            break;
+        }
+    }
+
+    ImageAnalysis { alpha, grayscale }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    fn analyze(pixels: &[[f32; 4]]) -> ImageAnalysis {
+        let image = Rgba32FImage::from_fn(pixels.len() as u32, 1, |x, _| Rgba(pixels[x as usize]));
+        analyze_image(&image)
+    }
+
+    #[test]
+    fn classifies_alpha() {
+        let opaque = [0.2, 0.4, 0.6, 1.0];
+        assert_eq!(analyze(&[opaque, opaque]).alpha, AlphaContent::Opaque);
+        assert_eq!(
+            analyze(&[opaque, [0.0, 0.0, 0.0, 0.0]]).alpha,
+            AlphaContent::Binary
+        );
+        assert_eq!(
+            analyze(&[opaque, [0.0, 0.0, 0.0, 0.5]]).alpha,
+            AlphaContent::Gradient
+        );
+        assert_eq!(
+            analyze(&[opaque, [0.0, 0.0, 0.0, 0.5], [0.0, 0.0, 0.0, 0.0]]).alpha,
+            AlphaContent::Gradient
+        );
+    }
+
+    #[test]
+    fn detects_grayscale_within_tolerance() {
+        let gray = [0.5, 0.5 + TOLERANCE / 2.0, 0.5, 1.0];
+        assert!(analyze(&[gray, [1.0, 1.0, 1.0, 1.0]]).grayscale);
+        assert!(!analyze(&[gray, [1.0, 0.9, 1.0, 1.0]]).grayscale);
+    }
+}
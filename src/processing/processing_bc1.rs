@@ -0,0 +1,189 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/processing/processing_bc1.rs
+
+use image_dds::SurfaceRgba32Float;
+
+/// Alpha below which a pixel is stored as transparent.
+const ALPHA_THRESHOLD: f32 = 0.5;
+
+/// Re-encodes the BC1 blocks of a surface that contain transparent pixels with 1-bit alpha.
+///
+/// The BC1 encoder only writes opaque four-color blocks. BC1 has a second
+/// block mode, selected by storing the smaller endpoint first, with three
+/// colors and a transparent index. Blocks with any pixel below half alpha are
+/// switched to that mode, keeping the encoder's endpoints; opaque blocks are
+/// left untouched.
+///
+/// # Arguments
+///
+/// * `encoded` - The BC1 data of all mip levels, as produced from `chain`.
+/// * `chain` - The uncompressed mip chain the data was encoded from.
+pub fn apply_punch_through_alpha(encoded: &mut [u8], chain: &SurfaceRgba32Float<Vec<f32>>) {
+    let mut offset = 0;
+    for mipmap in 0..chain.mipmaps {
+        let width = (chain.width >> mipmap).max(1) as usize;
+        let height = (chain.height >> mipmap).max(1) as usize;
+        let blocks_x = width.div_ceil(4);
+        let blocks_y = height.div_ceil(4);
+        let Some(pixels) = chain.get(0, 0, mipmap) else {
+            return;
+        };
+
+        for block_y in 0..blocks_y {
+            for block_x in 0..blocks_x {
+                let Some(block) = encoded.get_mut(offset..offset + 8) else {
+                    return;
+                };
+                offset += 8;
+
+                // Gather the pixels of the block; blocks at the border may be partial.
+                let mut block_pixels = [None; 16];
+                for (i, slot) in block_pixels.iter_mut().enumerate() {
+                    let x = block_x * 4 + i % 4;
+                    let y = block_y * 4 + i / 4;
+                    if x < width && y < height {
+                        let start = (y * width + x) * 4;
+                        *slot = Some([
+                            pixels[start],
+                            pixels[start + 1],
+                            pixels[start + 2],
+                            pixels[start + 3],
+                        ]);
+                    }
+                }
+                if block_pixels
+                    .iter()
+                    .flatten()
+                    .all(|pixel| pixel[3] >= ALPHA_THRESHOLD)
+                {
+                    continue;
+                }
+                encode_punch_through_block(block, &block_pixels);
+            }
+        }
+    }
+}
+
+/// Rewrites a BC1 block in three-color mode with transparent pixels.
+///
+/// # Arguments
+///
+/// * `block` - The 8 bytes of the block, holding the encoder's endpoints.
+/// * `pixels` - The 16 pixels of the block in row order; `None` outside the image.
+fn encode_punch_through_block(block: &mut [u8], pixels: &[Option<[f32; 4]>; 16]) {
+    let color0 = u16::from_le_bytes([block[0], block[1]]);
+    let color1 = u16::from_le_bytes([block[2], block[3]]);
+
+    // Three-color mode requires color0 <= color1.
+    let (low, high) = (color0.min(color1), color0.max(color1));
+    let low_rgb = rgb565_to_rgb(low);
+    let high_rgb = rgb565_to_rgb(high);
+    let palette = [
+        low_rgb,
+        high_rgb,
+        [
+            (low_rgb[0] + high_rgb[0]) / 2.0,
+            (low_rgb[1] + high_rgb[1]) / 2.0,
+            (low_rgb[2] + high_rgb[2]) / 2.0,
+        ],
+    ];
+
+    let mut indices = 0u32;
+    for (i, pixel) in pixels.iter().enumerate() {
+        let index = match pixel {
+            Some(pixel) if pixel[3] >= ALPHA_THRESHOLD => {
+                let distance = |color: &[f32; 3]| {
+                    (0..3)
+                        .map(|channel| (pixel[channel] * 255.0 - color[channel]).powi(2))
+                        .sum::<f32>()
+                };
+                (0..3)
+                    .min_by(|&a, &b| distance(&palette[a]).total_cmp(&distance(&palette[b])))
+                    .unwrap_or(0) as u32
+            }
+            _ => 3,
+        };
+        indices |= index << (2 * i);
+    }
+
+    block[0..2].copy_from_slice(&low.to_le_bytes());
+    block[2..4].copy_from_slice(&high.to_le_bytes());
+    block[4..8].copy_from_slice(&indices.to_le_bytes());
+}
+
+/// Expands an RGB565 endpoint to 8-bit channel values.
+fn rgb565_to_rgb(color: u16) -> [f32; 3] {
+    let r = ((color >> 11) & 0x1f) as f32;
+    let g = ((color >> 5) & 0x3f) as f32;
+    let b = (color & 0x1f) as f32;
+    [r * 255.0 / 31.0, g * 255.0 / 63.0, b * 255.0 / 31.0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image_dds::{ImageFormat, Mipmaps, Quality};
+
+    /// Encodes an 8x4 surface whose left block has a transparent corner.
+    fn encode() -> (Vec<u8>, SurfaceRgba32Float<Vec<f32>>) {
+        let mut data = Vec::new();
+        for y in 0..4 {
+            for x in 0..8 {
+                let alpha = if x < 2 && y < 2 { 0.0 } else { 1.0 };
+                data.extend_from_slice(&[x as f32 / 7.0, 0.5, 1.0 - x as f32 / 7.0, alpha]);
+            }
+        }
+        let chain = SurfaceRgba32Float {
+            width: 8,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data,
+        };
+        let encoded = chain
+            .encode(
+                ImageFormat::BC1RgbaUnorm,
+                Quality::Fast,
+                Mipmaps::FromSurface,
+            )
+            .unwrap();
+        (encoded.data, chain)
+    }
+
+    #[test]
+    fn transparent_pixels_use_the_punch_through_index() {
+        let (mut encoded, chain) = encode();
+        let opaque_block = encoded[8..16].to_vec();
+        apply_punch_through_alpha(&mut encoded, &chain);
+
+        // The opaque block is untouched, the other switches to three-color mode.
+        assert_eq!(encoded[8..16], opaque_block);
+        let color0 = u16::from_le_bytes([encoded[0], encoded[1]]);
+        let color1 = u16::from_le_bytes([encoded[2], encoded[3]]);
+        assert!(color0 <= color1);
+
+        let decoded = image_dds::Surface {
+            width: 8,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: ImageFormat::BC1RgbaUnorm,
+            data: encoded,
+        }
+        .decode_rgba8()
+        .unwrap();
+        for (i, pixel) in decoded.data.chunks_exact(4).enumerate() {
+            let (x, y) = (i % 8, i / 8);
+            let expected = if x < 2 && y < 2 { 0 } else { 255 };
+            assert_eq!(pixel[3], expected, "({}, {})", x, y);
+        }
+    }
+
+    #[test]
+    fn expands_rgb565_endpoints() {
+        assert_eq!(rgb565_to_rgb(0xffff), [255.0, 255.0, 255.0]);
+        assert_eq!(rgb565_to_rgb(0xf800), [255.0, 0.0, 0.0]);
+        assert_eq!(rgb565_to_rgb(0x0000), [0.0, 0.0, 0.0]);
+    }
+}
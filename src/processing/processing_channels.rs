@@ -0,0 +1,134 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/processing/processing_channels.rs
+
+use image::{DynamicImage, Rgba32FImage};
+
+use crate::structs::struct_channelop::{Channel, ChannelOp};
+
+/// Applies channel operations to an image in place, in the listed order.
+///
+/// # Arguments
+///
+/// * `image` - The image to modify.
+/// * `ops` - The operations to apply.
+pub fn apply_channel_ops(image: &mut Rgba32FImage, ops: &[ChannelOp]) {
+    if ops.is_empty() {
+        return;
+    }
+
+    for pixel in image.pixels_mut() {
+        for op in ops {
+            match *op {
+                ChannelOp::Invert(channel) => {
+                    pixel[channel.index()] = 1.0 - pixel[channel.index()];
+                }
+                ChannelOp::Swizzle(channels) => {
+                    let source = pixel.0;
+                    for (target, channel) in channels.iter().enumerate() {
+                        pixel[target] = source[channel.index()];
+                    }
+                }
+                ChannelOp::Fill(channel, value) => {
+                    pixel[channel.index()] = value;
+                }
+            }
+        }
+    }
+}
+
+/// Returns the number of meaningful channels after applying channel operations.
+///
+/// Only alpha is tracked: it becomes meaningful when a color channel is
+/// swizzled into it, and meaningless when it is filled with a constant. The
+/// result feeds the format selection, e.g. so that moving a specular map into
+/// alpha picks a format that stores alpha.
+///
+/// # Arguments
+///
+/// * `ops` - The operations that will be applied.
+/// * `channels` - The channel count of the input image.
+///
+/// # Returns
+///
+/// * `u8` - The channel count of the output image.
+pub fn output_channels(ops: &[ChannelOp], channels: u8) -> u8 {
+    let has_alpha = |channels: u8| channels == 2 || channels == 4;
+
+    // Track which source channels carry data: color always, alpha only if present.
+    let mut meaningful = [true, true, true, has_alpha(channels)];
+    for op in ops {
+        match *op {
+            ChannelOp::Invert(_) => {}
+            ChannelOp::Swizzle(sources) => {
+                let previous = meaningful;
+                for (target, source) in sources.iter().enumerate() {
+                    meaningful[target] = previous[source.index()];
+                }
+            }
+            ChannelOp::Fill(channel, _) => meaningful[channel.index()] = false,
+        }
+    }
+
+    let alpha = meaningful[Channel::A.index()];
+    match (channels, alpha) {
+        (1, true) => 2,
+        (2, false) => 1,
+        (3, true) => 4,
+        (4, false) => 3,
+        _ => channels,
+    }
+}
+
+/// Converts an RGBA float image to the color type of another image.
+///
+/// Used after processing in float, so that intermediate files are written
+/// with the channels and bit depth of the input.
+///
+/// # Arguments
+///
+/// * `image` - The processed image.
+/// * `like` - The image whose color type to use.
+///
+/// # Returns
+///
+/// * `DynamicImage` - The converted image; unknown color types stay RGBA float.
+pub fn to_color_type(image: Rgba32FImage, like: &DynamicImage) -> DynamicImage {
+    let image = DynamicImage::ImageRgba32F(image);
+    match like {
+        DynamicImage::ImageLuma8(_) => DynamicImage::ImageLuma8(image.to_luma8()),
+        DynamicImage::ImageLumaA8(_) => DynamicImage::ImageLumaA8(image.to_luma_alpha8()),
+        DynamicImage::ImageRgb8(_) => DynamicImage::ImageRgb8(image.to_rgb8()),
+        DynamicImage::ImageRgba8(_) => DynamicImage::ImageRgba8(image.to_rgba8()),
+        DynamicImage::ImageLuma16(_) => DynamicImage::ImageLuma16(image.to_luma16()),
+        DynamicImage::ImageLumaA16(_) => DynamicImage::ImageLumaA16(image.to_luma_alpha16()),
+        DynamicImage::ImageRgb16(_) => DynamicImage::ImageRgb16(image.to_rgb16()),
+        DynamicImage::ImageRgba16(_) => DynamicImage::ImageRgba16(image.to_rgba16()),
+        DynamicImage::ImageRgb32F(_) => DynamicImage::ImageRgb32F(image.to_rgb32f()),
+        _ => image,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ops(ops: &[&str]) -> Vec<ChannelOp> {
+        ops.iter().map(|op| op.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn applies_operations_in_order() {
+        let mut image = Rgba32FImage::from_pixel(1, 1, image::Rgba([0.25, 0.5, 0.75, 1.0]));
+        apply_channel_ops(&mut image, &ops(&["invert:g", "swizzle:argb", "fill:b=0"]));
+        assert_eq!(image.get_pixel(0, 0).0, [1.0, 0.25, 0.0, 0.75]);
+    }
+
+    #[test]
+    fn tracks_meaningful_alpha() {
+        assert_eq!(output_channels(&[], 3), 3);
+        assert_eq!(output_channels(&ops(&["swizzle:rgbr"]), 3), 4);
+        assert_eq!(output_channels(&ops(&["fill:a=1"]), 4), 3);
+        assert_eq!(output_channels(&ops(&["swizzle:aaaa"]), 3), 3);
+        assert_eq!(output_channels(&ops(&["invert:a"]), 2), 2);
+        assert_eq!(output_channels(&ops(&["swizzle:rrrr"]), 1), 2);
+    }
+}
@@ -0,0 +1,79 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/processing/processing_color.rs
+
+use image_dds::ImageFormat;
+
+use crate::structs::struct_colorspace::ColorSpace;
+
+/// Returns the color space a DDS format expects its data to be encoded in.
+///
+/// # Arguments
+///
+/// * `format` - The target `ImageFormat`.
+///
+/// # Returns
+///
+/// * `ColorSpace::Srgb` for `*Srgb` formats, `ColorSpace::Linear` otherwise.
+pub fn format_color_space(format: ImageFormat) -> ColorSpace {
+    match format {
+        ImageFormat::Rgba8UnormSrgb
+        | ImageFormat::Bgra8UnormSrgb
+        | ImageFormat::BC1RgbaUnormSrgb
+        | ImageFormat::BC2RgbaUnormSrgb
+        | ImageFormat::BC3RgbaUnormSrgb
+        | ImageFormat::BC7RgbaUnormSrgb => ColorSpace::Srgb,
+        _ => ColorSpace::Linear,
+    }
+}
+
+/// Converts the RGB channels of an image between color spaces.
+///
+/// Alpha is left untouched. If both color spaces match, the image is returned as is.
+///
+/// # Arguments
+///
+/// * `image` - The image to convert.
+/// * `from` - The color space the image is currently encoded in.
+/// * `to` - The color space to encode the image in.
+///
+/// # Returns
+///
+/// * `image::Rgba32FImage` - The converted image.
+pub fn convert_color_space(
+    image: image::Rgba32FImage,
+    from: ColorSpace,
+    to: ColorSpace,
+) -> image::Rgba32FImage {
+    let transfer: fn(f32) -> f32 = match (from, to) {
+        (ColorSpace::Linear, ColorSpace::Srgb) => linear_to_srgb,
+        (ColorSpace::Srgb, ColorSpace::Linear) => srgb_to_linear,
+        _ => return image,
+    };
+
+    let mut image = image;
+    for pixel in image.pixels_mut() {
+        pixel[0] = transfer(pixel[0]);
+        pixel[1] = transfer(pixel[1]);
+        pixel[2] = transfer(pixel[2]);
+    }
+    image
+}
+
+/// Encodes a linear value with the sRGB transfer function.
+fn linear_to_srgb(value: f32) -> f32 {
+    let value = value.clamp(0.0, 1.0);
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Decodes an sRGB encoded value to linear.
+fn srgb_to_linear(value: f32) -> f32 {
+    let value = value.clamp(0.0, 1.0);
+    if value <= 0.040_45 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
@@ -1,22 +1,61 @@
 // D:/Work/Projects/Modding/Skyrim/dds-edit/src/processing/processing_core.rs
 
 use std::{
+    collections::HashSet,
     fs,
     path::{Path, PathBuf},
     sync::Arc,
 };
 
+use colored::Color;
+
 use crate::{
-    io::io_pathtree::mirror_path,
-    structs::{struct_config::Config, struct_imagedata::ImageData},
+    io::{
+        io_cache::{
+            hash_file, hash_str, load_cache_manifest, manifest_key, manifest_path,
+            save_cache_manifest,
+        },
+        io_pathtree::mirror_path,
+        io_report::{load_run_report, save_run_report},
+        io_source::{relative_input_path, InputSource},
+    },
+    logging::{smnprintln, LogQueue, LogType},
+    structs::{
+        struct_cachemanifest::{CacheEntry, CacheManifest},
+        struct_config::Config,
+        struct_imagedata::ImageData,
+        struct_packconfig::PackConfig,
+        struct_runreport::{
+            InputReport, InputStatus, ProcessedOutputs, RunReport, RUN_REPORT_VERSION,
+        },
+    },
 };
 
-use super::processing_img::process_img;
+/// Result of a processing task: the manifest key of the input, its cache
+/// entry (the previous one if processing failed) and its report entry.
+type TaskResult = (String, Option<CacheEntry>, InputReport);
+
+use super::{
+    processing_img::process_img,
+    processing_pack::{find_pack_groups, pack_sources_exist, packed_path, process_pack},
+    processing_package::write_fomod,
+    processing_plan::pack_config,
+};
 
 /// Processes a list of image paths based on the provided configuration.
 ///
 /// This function distributes image processing tasks asynchronously.
 ///
+/// Unless `decode_only` is set, a cache manifest in `path_out` is used to skip
+/// inputs whose contents and effective settings are unchanged since the last
+/// run (bypassed with `force`). Outputs that are no longer produced, including
+/// those of inputs that were removed, are deleted afterwards. A run report
+/// listing every input, its outputs and what was done for each target size is
+/// written next to the manifest.
+///
+/// Archives are written from scratch on every run, so they use no cache
+/// manifest, and their report is written next to them.
+///
 /// # Arguments
 ///
 /// * `filtered_paths` - Slice of `PathBuf` pointing to image files to process.
@@ -26,16 +65,73 @@ use super::processing_img::process_img;
 ///
 /// * `Ok(())` if processing succeeds.
 /// * `Err(String)` with an error message if processing fails.
-pub async fn process_files(
+pub async fn process_files(filtered_paths: &[PathBuf], config: &Config) -> Result<(), String> {
+    process_paths(filtered_paths, None, config).await
+}
+
+/// Processes changed and removed inputs, leaving all other cache entries untouched.
+///
+/// Used by watch mode to update single files without treating every input
+/// that is not part of the batch as removed. The cache manifest and the run
+/// report keep the entries of the inputs outside of the batch.
+///
+/// # Arguments
+///
+/// * `changed_paths` - Input files that were created or modified.
+/// * `removed_paths` - Input files that were deleted.
+/// * `config` - Reference to the `Config` struct containing configuration parameters.
+///
+/// # Returns
+///
+/// * `Ok(())` if processing succeeds.
+/// * `Err(String)` with an error message if processing fails.
+pub async fn process_changed_files(
+    changed_paths: &[PathBuf],
+    removed_paths: &[PathBuf],
+    config: &Config,
+) -> Result<(), String> {
+    process_paths(changed_paths, Some(removed_paths), config).await
+}
+
+/// Shared implementation of `process_files` and `process_changed_files`.
+///
+/// # Arguments
+///
+/// * `filtered_paths` - Slice of `PathBuf` pointing to image files to process.
+/// * `removed_paths` - `None` for a full run, where every input missing from
+///   `filtered_paths` counts as removed; otherwise the inputs that were removed.
+/// * `config` - Reference to the `Config` struct containing configuration parameters.
+async fn process_paths(
     filtered_paths: &[PathBuf],
+    removed_paths: Option<&[PathBuf]>,
     config: &Config,
 ) -> Result<(), String> {
     // Convert input and output paths to Path objects.
     let path_in = Path::new(&config.path_in);
     let path_out = Path::new(&config.path_out);
 
+    // Group pack sources; those consumed by a pack are not converted on their own.
+    let (pack_groups, pack_sources) = if config.decode_only {
+        (Vec::new(), HashSet::new())
+    } else {
+        find_pack_groups(filtered_paths, &config.packs)
+    };
+
+    // Manifest keys of every input of this run, including pack sources and packed textures.
+    let run_keys: HashSet<String> = filtered_paths
+        .iter()
+        .chain(pack_groups.iter().map(|group| &group.path))
+        .map(|path| manifest_key(path_in, path))
+        .collect();
+
     // Wrap filtered_paths in an Arc to allow safe sharing across tasks.
-    let paths = Arc::new(filtered_paths.to_owned());
+    let paths = Arc::new(
+        filtered_paths
+            .iter()
+            .filter(|path| !pack_sources.contains(*path))
+            .cloned()
+            .collect::<Vec<_>>(),
+    );
 
     // Determine the total number of images to process.
     let total_images = paths.len();
@@ -44,6 +140,14 @@ pub async fn process_files(
     // Keep start time
     let start_time = std::time::Instant::now();
 
+    // Load the manifest of the previous run; decode mode and archives do not use the cache.
+    let use_cache = !config.decode_only && !config.output.is_archive();
+    let previous_manifest = Arc::new(if use_cache {
+        load_cache_manifest(&config.path_in, path_out)
+    } else {
+        CacheManifest::new(&config.path_in)
+    });
+
     // Create a semaphore to limit concurrency.
     let semaphore = Arc::new(tokio::sync::Semaphore::new(concurrency_limit));
 
@@ -52,7 +156,13 @@ pub async fn process_files(
 
     for idx in 0..total_images {
         let paths_clone = Arc::clone(&paths);
-        let config_clone = config.clone();
+        // Resolve per-path rules for this file, relative to its archive for archive entries.
+        let relative_path =
+            relative_input_path(path_in, &paths[idx]).unwrap_or_else(|_| paths[idx].clone());
+        let config_clone = config.resolve_for(&relative_path);
+        let key = manifest_key(path_in, &paths[idx]);
+        let settings = hash_str(&config_clone.settings_fingerprint());
+        let previous_manifest = Arc::clone(&previous_manifest);
         let path_in = path_in.to_path_buf();
         let path_out = path_out.to_path_buf();
         let semaphore_clone = Arc::clone(&semaphore);
@@ -66,55 +176,560 @@ pub async fn process_files(
             let _permit = permit;
 
             let input_path = &paths_clone[idx];
+            let previous = previous_manifest.files.get(&key).cloned();
+
+            // Hash the input to detect changes since the last run.
+            let hash = if use_cache {
+                let input_clone = input_path.clone();
+                match tokio::task::spawn_blocking(move || hash_file(&input_clone)).await {
+                    Ok(Ok(hash)) => hash,
+                    Ok(Err(e)) => {
+                        eprintln!("{}", e);
+                        return failed_result(key, previous);
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to spawn blocking task: {}", e);
+                        return failed_result(key, previous);
+                    }
+                }
+            } else {
+                String::new()
+            };
+
+            // Skip inputs whose contents, settings and outputs are unchanged.
+            if use_cache
+                && !config_clone.force
+                && is_up_to_date(&key, &previous, &hash, &settings, &path_out)
+            {
+                return unchanged_result(key, previous);
+            }
 
             // Compute the mirrored output path.
             let output_path = match mirror_path(&path_in, &path_out, input_path) {
                 Ok(op) => op,
                 Err(e) => {
-                    eprintln!(
-                        "Error mirroring path '{}': {}",
-                        input_path.display(),
-                        e
-                    );
-                    return;
+                    eprintln!("Error mirroring path '{}': {}", input_path.display(), e);
+                    return failed_result(key, previous);
                 }
             };
 
-            // Ensure that the parent directory exists in the output path.
-            if let Some(parent) = output_path.parent() {
-                if let Err(e) = fs::create_dir_all(parent) {
-                    eprintln!(
-                        "Failed to create directory '{}': {}",
-                        parent.display(),
-                        e
-                    );
-                    return;
-                }
-            }
+            // Output directories are created as files are written, since the layout decides where they go.
 
             // Create an ImageData instance from the input and output paths.
-            let image_data =
-                ImageData::from((input_path.clone(), output_path.clone()));
+            let image_data = ImageData::from((input_path.clone(), output_path.clone()));
             // Process the image (resizing and DDS conversion).
-            process_img(image_data, &config_clone).await;
+            // On failure the previous entry is kept so that the file is retried.
+            match process_img(image_data, &config_clone).await {
+                Some(processed) => processed_result(key, hash, settings, processed, &path_out),
+                None => failed_result(key, previous),
+            }
         });
 
         // Store the task handle.
         handles.push(handle);
     }
 
-    // Wait for all tasks to finish processing.
+    // Process the packed textures, cached under the key of their packed path.
+    for group in pack_groups {
+        let relative_path =
+            relative_input_path(path_in, &group.path).unwrap_or_else(|_| group.path.clone());
+        let config_clone = pack_config(&group, &config.resolve_for(&relative_path));
+        let key = manifest_key(path_in, &group.path);
+        let pack = serde_json::to_string(&group.pack).unwrap_or_default();
+        let settings = hash_str(&format!("{}|{}", config_clone.settings_fingerprint(), pack));
+        let previous = previous_manifest.files.get(&key).cloned();
+        let output_path = match mirror_path(path_in, path_out, &group.path) {
+            Ok(output_path) => output_path,
+            Err(e) => {
+                eprintln!("Error mirroring path '{}': {}", group.path.display(), e);
+                continue;
+            }
+        };
+        let path_out = path_out.to_path_buf();
+        let permit = Arc::clone(&semaphore).acquire_owned().await.unwrap();
+
+        handles.push(tokio::spawn(async move {
+            let _permit = permit;
+
+            // The pack changes when any of its sources does.
+            let sources: Vec<PathBuf> = group.sources.values().cloned().collect();
+            let hash = match tokio::task::spawn_blocking(move || {
+                sources
+                    .iter()
+                    .map(|source| hash_file(source))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .await
+            {
+                Ok(Ok(hashes)) => hash_str(&hashes.join("|")),
+                Ok(Err(e)) => {
+                    eprintln!("{}", e);
+                    return failed_result(key, previous);
+                }
+                Err(e) => {
+                    eprintln!("Failed to spawn blocking task: {}", e);
+                    return failed_result(key, previous);
+                }
+            };
+
+            if !config_clone.force && is_up_to_date(&key, &previous, &hash, &settings, &path_out) {
+                return unchanged_result(key, previous);
+            }
+
+            match process_pack(group, output_path, &config_clone).await {
+                Some(processed) => processed_result(key, hash, settings, processed, &path_out),
+                None => failed_result(key, previous),
+            }
+        }));
+    }
+
+    // Manifest keys of the removed inputs, including the packs missing a source.
+    let removed_keys: HashSet<String> = removed_paths
+        .unwrap_or_default()
+        .iter()
+        .flat_map(|removed_path| {
+            std::iter::once(manifest_key(path_in, removed_path)).chain(
+                config
+                    .packs
+                    .iter()
+                    .filter_map(|pack| packed_path(removed_path, pack))
+                    .map(|packed_path| manifest_key(path_in, &packed_path)),
+            )
+        })
+        .collect();
+
+    // A full run rebuilds the manifest from scratch; a partial run updates the previous one.
+    let mut manifest = match removed_paths {
+        None => CacheManifest::new(&config.path_in),
+        Some(_) => {
+            let mut manifest = (*previous_manifest).clone();
+            manifest.files.retain(|key, _| !removed_keys.contains(key));
+            manifest
+        }
+    };
+
+    // Wait for all tasks to finish processing and collect their cache and report entries.
+    let mut reports = Vec::new();
     for handle in handles {
-        if let Err(e) = handle.await {
-            eprintln!("Task panicked: {:?}", e);
+        match handle.await {
+            Ok((key, entry, report)) => {
+                if let Some(entry) = entry {
+                    manifest.files.insert(key, entry);
+                }
+                reports.push(report);
+            }
+            Err(e) => eprintln!("Task panicked: {:?}", e),
+        }
+    }
+
+    if use_cache {
+        if removed_paths.is_none() {
+            keep_excluded_entries(
+                &previous_manifest,
+                &mut manifest,
+                &run_keys,
+                path_in,
+                &config.packs,
+            );
+        }
+        remove_stale_outputs(&previous_manifest, &manifest, path_out);
+        if let Err(e) = save_cache_manifest(&manifest, path_out) {
+            eprintln!("{}", e);
         }
     }
 
     // Calculate the total processing time.
     let elapsed_time = start_time.elapsed().as_secs_f64();
+
+    // Decode mode only writes PNG files, so there is nothing to report.
+    if !config.decode_only {
+        // A partial run keeps the entries of the inputs outside of it.
+        if removed_paths.is_some() {
+            if let Some(previous_report) = load_run_report(&config.path_in, path_out) {
+                let run_inputs: HashSet<String> =
+                    reports.iter().map(|report| report.input.clone()).collect();
+                reports.extend(previous_report.inputs.into_iter().filter(|report| {
+                    !run_inputs.contains(&report.input) && !removed_keys.contains(&report.input)
+                }));
+            }
+        }
+        reports.sort_by(|a, b| a.input.cmp(&b.input));
+        let report = RunReport {
+            version: RUN_REPORT_VERSION,
+            path_in: config.path_in.clone(),
+            path_out: config.path_out.clone(),
+            elapsed_seconds: elapsed_time,
+            inputs: reports,
+        };
+        if let Err(e) = save_run_report(&report, path_out) {
+            eprintln!("{}", e);
+        }
+
+        // Regenerate the installer, since the set of per-size folders may have changed.
+        if config.package.is_some() {
+            if let Err(e) = write_fomod(config) {
+                eprintln!("{}", e);
+            }
+        }
+    }
     println!("--------------------------");
     println!("Processing completed in {:.2} seconds.", elapsed_time);
     println!("--------------------------");
 
     Ok(())
 }
+
+/// Builds the task result of an input that was processed.
+///
+/// # Arguments
+///
+/// * `key` - The manifest key of the input.
+/// * `hash` - Hash of the input's contents.
+/// * `settings` - Hash of the input's effective settings.
+/// * `processed` - The files written and the target size records.
+/// * `path_out` - The output directory the recorded paths are made relative to.
+fn processed_result(
+    key: String,
+    hash: String,
+    settings: String,
+    processed: ProcessedOutputs,
+    path_out: &Path,
+) -> TaskResult {
+    let outputs: Vec<String> = processed
+        .files
+        .iter()
+        .map(|output| manifest_key(path_out, output))
+        .collect();
+    let targets = processed
+        .targets
+        .into_iter()
+        .map(|mut target| {
+            target.output = target
+                .output
+                .map(|output| PathBuf::from(manifest_key(path_out, &output)));
+            target
+        })
+        .collect();
+    let report = InputReport {
+        input: key.clone(),
+        status: InputStatus::Processed,
+        outputs: outputs.clone(),
+        targets,
+    };
+    (
+        key,
+        Some(CacheEntry {
+            hash,
+            settings,
+            outputs,
+        }),
+        report,
+    )
+}
+
+/// Builds the task result of an input skipped as unchanged, keeping its previous outputs.
+fn unchanged_result(key: String, previous: Option<CacheEntry>) -> TaskResult {
+    let report = InputReport {
+        input: key.clone(),
+        status: InputStatus::Unchanged,
+        outputs: previous
+            .as_ref()
+            .map(|entry| entry.outputs.clone())
+            .unwrap_or_default(),
+        targets: Vec::new(),
+    };
+    (key, previous, report)
+}
+
+/// Builds the task result of an input that failed; the previous entry is kept so it is retried.
+fn failed_result(key: String, previous: Option<CacheEntry>) -> TaskResult {
+    let report = InputReport {
+        input: key.clone(),
+        status: InputStatus::Failed,
+        outputs: Vec::new(),
+        targets: Vec::new(),
+    };
+    (key, previous, report)
+}
+
+/// Returns `true` if an input's contents, settings and outputs are unchanged since the last run.
+///
+/// Logs that the input is skipped.
+///
+/// # Arguments
+///
+/// * `key` - The manifest key of the input.
+/// * `previous` - The input's entry in the previous manifest, if any.
+/// * `hash` - Hash of the input's current contents.
+/// * `settings` - Hash of the input's current effective settings.
+/// * `path_out` - The output directory the manifest paths are relative to.
+fn is_up_to_date(
+    key: &str,
+    previous: &Option<CacheEntry>,
+    hash: &str,
+    settings: &str,
+    path_out: &Path,
+) -> bool {
+    let Some(entry) = previous else {
+        return false;
+    };
+    let up_to_date = entry.hash == hash
+        && entry.settings == settings
+        && entry
+            .outputs
+            .iter()
+            .all(|output| manifest_path(path_out, output).exists());
+
+    if up_to_date {
+        let log_queue = LogQueue::new();
+        log_queue.enqueue(
+            LogType::Println,
+            "--------------------------".to_string(),
+            Color::White,
+        );
+        log_queue.enqueue(
+            LogType::Println,
+            format!("[Unchanged, Skipping: {}]", key),
+            Color::Yellow,
+        );
+        log_queue.process_queue();
+    }
+    up_to_date
+}
+
+/// Carries the cache entries of inputs that a full run left out over to its manifest.
+///
+/// Inputs excluded by `max_depth` or a filter still exist, so their entries
+/// and outputs are kept. Entries of inputs that no longer exist on disk, and
+/// of packs missing a source, are dropped.
+///
+/// # Arguments
+///
+/// * `previous` - The manifest of the previous run.
+/// * `current` - The manifest of the current run.
+/// * `run_keys` - Manifest keys of the inputs of the current run.
+/// * `path_in` - The input directory the manifest keys are relative to.
+/// * `packs` - The configured packs.
+fn keep_excluded_entries(
+    previous: &CacheManifest,
+    current: &mut CacheManifest,
+    run_keys: &HashSet<String>,
+    path_in: &Path,
+    packs: &[PackConfig],
+) {
+    for (key, entry) in &previous.files {
+        if run_keys.contains(key) || current.files.contains_key(key) {
+            continue;
+        }
+        let path = manifest_path(path_in, key);
+        if InputSource::of(&path).exists() || pack_sources_exist(&path, packs) {
+            current.files.insert(key.clone(), entry.clone());
+        }
+    }
+}
+
+/// Deletes outputs recorded in the previous manifest that the current run no longer produces.
+///
+/// This covers outputs of inputs that were removed as well as outputs that
+/// changed name or are no longer generated after a settings change. A full
+/// run carries the entries of inputs it left out over with
+/// `keep_excluded_entries` first, so their outputs are kept.
+///
+/// # Arguments
+///
+/// * `previous` - The manifest of the previous run.
+/// * `current` - The manifest of the current run.
+/// * `path_out` - The output directory the manifest paths are relative to.
+fn remove_stale_outputs(previous: &CacheManifest, current: &CacheManifest, path_out: &Path) {
+    let live: HashSet<&String> = current
+        .files
+        .values()
+        .flat_map(|entry| entry.outputs.iter())
+        .collect();
+
+    for output in previous
+        .files
+        .values()
+        .flat_map(|entry| entry.outputs.iter())
+    {
+        if live.contains(output) {
+            continue;
+        }
+        let path = manifest_path(path_out, output);
+        match fs::remove_file(&path) {
+            Ok(()) => {
+                smnprintln(
+                    format!("Removed stale output '{}'", path.display()),
+                    Color::Yellow,
+                );
+                remove_empty_parents(&path, path_out);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => eprintln!("Failed to remove stale output '{}': {}", path.display(), e),
+        }
+    }
+}
+
+/// Removes the directories above a deleted output that are left empty, up to `path_out`.
+///
+/// Keeps a layout change from leaving a tree of empty folders behind.
+///
+/// # Arguments
+///
+/// * `path` - The deleted output.
+/// * `path_out` - The output directory, which is never removed.
+fn remove_empty_parents(path: &Path, path_out: &Path) {
+    let mut directory = path.parent();
+    while let Some(current) = directory {
+        // `remove_dir` fails on non-empty directories, which ends the walk.
+        if current == path_out || !current.starts_with(path_out) || fs::remove_dir(current).is_err()
+        {
+            break;
+        }
+        directory = current.parent();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::struct_sizespec::SizeSpec;
+
+    fn entry(outputs: &[&str]) -> CacheEntry {
+        CacheEntry {
+            hash: "hash".to_string(),
+            settings: "settings".to_string(),
+            outputs: outputs.iter().map(|output| output.to_string()).collect(),
+        }
+    }
+
+    fn pack() -> PackConfig {
+        toml::from_str("output = \"_rmaos\"\nr = \"_rough\"\ng = \"_metal\"").unwrap()
+    }
+
+    #[test]
+    fn keeps_entries_of_inputs_left_out_of_a_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let path_in = dir.path();
+        fs::create_dir(path_in.join("deep")).unwrap();
+        for name in [
+            "deep/kept_d.png",
+            "armor_rough.png",
+            "armor_metal.png",
+            "helmet_rough.png",
+        ] {
+            fs::write(path_in.join(name), b"").unwrap();
+        }
+
+        let mut previous = CacheManifest::new("in");
+        for key in [
+            "deep/kept_d.png",
+            "removed_d.png",
+            "processed_d.png",
+            "armor_rmaos.png",
+            "helmet_rmaos.png",
+        ] {
+            previous.files.insert(key.to_string(), entry(&[key]));
+        }
+
+        let mut current = CacheManifest::new("in");
+        current
+            .files
+            .insert("processed_d.png".to_string(), entry(&["new.dds"]));
+        let run_keys = HashSet::from(["processed_d.png".to_string()]);
+        keep_excluded_entries(&previous, &mut current, &run_keys, path_in, &[pack()]);
+
+        let keys: Vec<&str> = current.files.keys().map(String::as_str).collect();
+        assert_eq!(
+            keys,
+            ["armor_rmaos.png", "deep/kept_d.png", "processed_d.png"]
+        );
+        assert_eq!(current.files["processed_d.png"], entry(&["new.dds"]));
+    }
+
+    #[test]
+    fn drops_entries_of_run_inputs() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("armor_rough.png"), b"").unwrap();
+
+        // A source now consumed by a pack no longer has outputs of its own.
+        let mut previous = CacheManifest::new("in");
+        previous
+            .files
+            .insert("armor_rough.png".to_string(), entry(&["armor_rough.dds"]));
+        let mut current = CacheManifest::new("in");
+        let run_keys = HashSet::from(["armor_rough.png".to_string()]);
+        keep_excluded_entries(&previous, &mut current, &run_keys, dir.path(), &[pack()]);
+        assert!(current.files.is_empty());
+    }
+
+    #[test]
+    fn removes_stale_outputs_and_empty_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let path_out = dir.path();
+        fs::create_dir_all(path_out.join("old/nested")).unwrap();
+        for name in ["old/nested/stale.dds", "live.dds"] {
+            fs::write(path_out.join(name), b"").unwrap();
+        }
+
+        let mut previous = CacheManifest::new("in");
+        previous.files.insert(
+            "a.png".to_string(),
+            entry(&["old/nested/stale.dds", "live.dds"]),
+        );
+        let mut current = CacheManifest::new("in");
+        current
+            .files
+            .insert("a.png".to_string(), entry(&["live.dds"]));
+        remove_stale_outputs(&previous, &current, path_out);
+
+        assert!(path_out.join("live.dds").is_file());
+        assert!(!path_out.join("old").exists());
+    }
+
+    #[tokio::test]
+    async fn partial_runs_merge_into_the_run_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let (path_in, path_out) = (dir.path().join("in"), dir.path().join("out"));
+        fs::create_dir_all(&path_in).unwrap();
+        let inputs: Vec<PathBuf> = ["a_d.png", "b_d.png", "c_d.png"]
+            .iter()
+            .map(|name| path_in.join(name))
+            .collect();
+        for input in &inputs {
+            image::RgbaImage::from_pixel(8, 8, image::Rgba([90, 60, 30, 255]))
+                .save(input)
+                .unwrap();
+        }
+        let config = Config {
+            path_in: path_in.to_string_lossy().into_owned(),
+            path_out: path_out.to_string_lossy().into_owned(),
+            target_sizes: vec![SizeSpec::Width(8)],
+            ..Config::default()
+        };
+        let report_inputs = || {
+            load_run_report(&config.path_in, &path_out)
+                .unwrap()
+                .inputs
+                .into_iter()
+                .map(|report| (report.input, report.status))
+                .collect::<Vec<_>>()
+        };
+
+        process_files(&inputs, &config).await.unwrap();
+        assert_eq!(report_inputs().len(), 3);
+
+        // A batch touching one input and removing another keeps the third entry.
+        fs::remove_file(&inputs[2]).unwrap();
+        process_changed_files(&inputs[..1], &inputs[2..], &config)
+            .await
+            .unwrap();
+        assert_eq!(
+            report_inputs(),
+            [
+                ("a_d.png".to_string(), InputStatus::Unchanged),
+                ("b_d.png".to_string(), InputStatus::Processed),
+            ]
+        );
+    }
+}
@@ -1,12 +1,20 @@
 // D:/Work/Projects/Modding/Skyrim/dds-edit/src/processing/processing_dds.rs
 
-use std::path::{Path, PathBuf};
-use image_dds::ImageFormat;
-use image; // Ensure the `image` crate is in scope.
+use image::DynamicImage;
+use image_dds::{ImageFormat, Mipmaps};
+use std::path::PathBuf;
 
+use crate::io::io_dds::to_legacy_header;
+use crate::processing::processing_bc1::apply_punch_through_alpha;
+use crate::processing::processing_channels::apply_channel_ops;
+use crate::processing::processing_color::{convert_color_space, format_color_space};
+use crate::processing::processing_mips::mip_chain;
 use crate::structs::{
+    struct_config::Config,
     struct_ddsconfig::DdsConvertConfig,
+    struct_imageanalysis::{AlphaContent, ImageAnalysis},
     struct_imagedata::ImageData,
+    struct_texturerole::TextureRole,
 };
 
 /// Maps the number of image channels to the appropriate ImageFormat.
@@ -18,90 +26,232 @@ pub fn map_channels_format(channels: u8) -> ImageFormat {
     }
 }
 
-/// Converts an image to a DDS format based on the provided configuration.
+/// Maps the texture role and channel count to the appropriate ImageFormat.
+///
+/// Only color textures use sRGB formats; data textures such as normals,
+/// height maps and masks are stored linearly.
+///
+/// # Arguments
+///
+/// * `role` - The `TextureRole` of the image.
+/// * `channels` - Number of color channels in the image.
+///
+/// # Returns
+///
+/// * `ImageFormat` - The DDS format to compress the image to.
+pub fn map_role_format(role: TextureRole, channels: u8) -> ImageFormat {
+    match role {
+        _ if role.is_color() => map_channels_format(channels),
+        TextureRole::Normal => match channels {
+            4 => ImageFormat::BC7RgbaUnorm, // Specular stored in alpha
+            _ => ImageFormat::BC5RgUnorm,   // XY only, Z is reconstructed
+        },
+        TextureRole::Height => ImageFormat::BC4RUnorm,
+        _ => match channels {
+            1 | 2 => ImageFormat::BC4RUnorm, // Single-channel mask
+            _ => ImageFormat::BC1RgbaUnorm,  // Multi-channel mask
+        },
+    }
+}
+
+/// Maps the texture role and the analyzed pixel content to the cheapest suitable ImageFormat.
+///
+/// Unused alpha is dropped (BC1), cutout alpha is stored as BC1 1-bit alpha,
+/// and only alpha gradients pay for BC7, or BC3 when BC7 is not wanted.
+/// Grayscale masks without alpha are stored as single-channel BC4. Color
+/// textures never use BC4, since the game would read them as red.
+///
+/// # Arguments
+///
+/// * `role` - The `TextureRole` of the image.
+/// * `channels` - Number of meaningful channels in the image.
+/// * `analysis` - Reference to the `ImageAnalysis` of the image.
+/// * `prefer_bc7` - Whether alpha is stored as BC7 rather than the faster to encode BC3.
+///
+/// # Returns
+///
+/// * `ImageFormat` - The DDS format to compress the image to.
+pub fn map_analyzed_format(
+    role: TextureRole,
+    channels: u8,
+    analysis: &ImageAnalysis,
+    prefer_bc7: bool,
+) -> ImageFormat {
+    let alpha = match channels {
+        2 | 4 => analysis.alpha,
+        _ => AlphaContent::Opaque,
+    };
+    let (rgba, rgba_srgb) = if prefer_bc7 {
+        (ImageFormat::BC7RgbaUnorm, ImageFormat::BC7RgbaUnormSrgb)
+    } else {
+        (ImageFormat::BC3RgbaUnorm, ImageFormat::BC3RgbaUnormSrgb)
+    };
+    match role {
+        _ if role.is_color() => match alpha {
+            AlphaContent::Opaque | AlphaContent::Binary => ImageFormat::BC1RgbaUnormSrgb,
+            AlphaContent::Gradient => rgba_srgb,
+        },
+        TextureRole::Normal => match alpha {
+            AlphaContent::Opaque => ImageFormat::BC5RgUnorm,
+            _ => rgba,
+        },
+        TextureRole::Height => ImageFormat::BC4RUnorm,
+        _ => match alpha {
+            AlphaContent::Opaque if analysis.grayscale => ImageFormat::BC4RUnorm,
+            AlphaContent::Opaque | AlphaContent::Binary => ImageFormat::BC1RgbaUnorm,
+            AlphaContent::Gradient => rgba,
+        },
+    }
+}
+
+/// Selects the DDS format requested for an image, before the game profile is applied.
+///
+/// Alpha is stored as BC3 instead of BC7 when the game cannot load BC7 or
+/// the `fast` quality asks for speed over quality.
+///
+/// # Arguments
+///
+/// * `image_data` - Reference to the `ImageData` with the resolved role, channel count and analysis.
+/// * `dds_config` - Reference to the `DdsConvertConfig` with the configured format.
+///
+/// # Returns
+///
+/// * `ImageFormat` - The configured format, or the one picked from role and content.
+pub fn requested_format(image_data: &ImageData, dds_config: &DdsConvertConfig) -> ImageFormat {
+    dds_config
+        .format
+        .unwrap_or_else(|| match &image_data.analysis {
+            Some(analysis) => {
+                let prefer_bc7 = dds_config.game.supports_bc7()
+                    && !matches!(dds_config.quality, image_dds::Quality::Fast);
+                map_analyzed_format(image_data.role, image_data.channels, analysis, prefer_bc7)
+            }
+            None => map_role_format(image_data.role, image_data.channels),
+        })
+}
+
+/// Selects the DDS format an image is converted to.
+///
+/// # Arguments
+///
+/// * `image_data` - Reference to the `ImageData` with the resolved role and channel count.
+/// * `dds_config` - Reference to the `DdsConvertConfig` with the configured format and game.
+///
+/// # Returns
+///
+/// * `ImageFormat` - The requested format, or its fallback if the game cannot load it.
+pub fn select_format(image_data: &ImageData, dds_config: &DdsConvertConfig) -> ImageFormat {
+    dds_config
+        .game
+        .constrain_format(requested_format(image_data, dds_config))
+}
+
+/// Returns `true` if the format stores 4x4 pixel blocks (BC1-BC7).
+pub fn is_block_compressed(format: ImageFormat) -> bool {
+    matches!(
+        format,
+        ImageFormat::BC1RgbaUnorm
+            | ImageFormat::BC1RgbaUnormSrgb
+            | ImageFormat::BC2RgbaUnorm
+            | ImageFormat::BC2RgbaUnormSrgb
+            | ImageFormat::BC3RgbaUnorm
+            | ImageFormat::BC3RgbaUnormSrgb
+            | ImageFormat::BC4RUnorm
+            | ImageFormat::BC4RSnorm
+            | ImageFormat::BC5RgUnorm
+            | ImageFormat::BC5RgSnorm
+            | ImageFormat::BC6hRgbUfloat
+            | ImageFormat::BC6hRgbSfloat
+            | ImageFormat::BC7RgbaUnorm
+            | ImageFormat::BC7RgbaUnormSrgb
+    )
+}
+
+/// Converts an in-memory image to a DDS file based on the provided configuration.
+///
+/// The file is written through the output backend of `config`, so it ends up
+/// in the output directory or archive; missing parent directories are created.
 ///
 /// # Arguments
 ///
-/// * `image_path` - Path to the input image.
+/// * `image` - The resized image to encode.
+/// * `dds_path` - Path of the DDS file, as resolved by `io_naming::output_path`.
 /// * `image_data` - Reference to the `ImageData` struct containing image metadata.
+/// * `config` - Reference to the `Config` struct containing the DDS settings and output backend.
 ///
 /// # Returns
 ///
 /// * `Ok(PathBuf)` with the path to the saved DDS file.
 /// * `Err(String)` with an error message if conversion fails.
 pub async fn convert_to_dds(
-    image_path: &Path,
+    image: DynamicImage,
+    dds_path: PathBuf,
     image_data: &ImageData,
+    config: &Config,
 ) -> Result<PathBuf, String> {
-    // Extract the file stem (name without extension).
-    let file_name = image_path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .ok_or_else(|| {
-            format!("Invalid file name for path: {:?}", image_path)
-        })?
-        .to_string();
-
-    // Clone or convert references to owned data
-    let image_path_owned = image_path.to_owned(); // Convert to PathBuf
-    let file_path_out = image_data.file_path_out.clone();
-    let channels = image_data.channels;
+    let role = image_data.role;
+    let color_space = image_data.color_space;
+    let dds_config = DdsConvertConfig::from(config);
+    let image_format = select_format(image_data, &dds_config);
+    let legacy_header = dds_config.game.requires_legacy_headers();
+    let renormalize = role == TextureRole::Normal && dds_config.renormalize_normals;
+    let channel_ops = config.channel_ops.clone();
+    let output = config.output.clone();
+    let punch_through = matches!(
+        image_format,
+        ImageFormat::BC1RgbaUnorm | ImageFormat::BC1RgbaUnormSrgb
+    ) && matches!(image_data.channels, 2 | 4);
 
     // Perform the conversion in a blocking task.
     let result = tokio::task::spawn_blocking(move || {
-        // Use image_path_owned inside the closure
-        let image = image::open(&image_path_owned)
-            .map_err(|e| {
-                format!(
-                    "Failed to open image '{}': {}",
-                    image_path_owned.display(),
-                    e
-                )
-            })?
-            .to_rgba32f();
-
-        // Apply gamma correction to the image.
-        let image = apply_gamma_correction(&image);
-        
-        // Create default DDS conversion configuration.
-        let config = DdsConvertConfig::new();
-
-        // Determine the appropriate ImageFormat based on channel count.
-        let image_format = map_channels_format(channels);
-
-        // Convert the image to DDS format.
-        let dds_result = image_dds::dds_from_imagef32(
-            &image,
-            image_format,
-            config.quality,
-            config.mipmaps,
+        let mut image = image.to_rgba32f();
+        apply_channel_ops(&mut image, &channel_ops);
+
+        // Encode color data in the color space the target format expects.
+        // Non-color data is passed through untouched.
+        let image = if role.is_color() {
+            convert_color_space(image, color_space, format_color_space(image_format))
+        } else {
+            image
+        };
+
+        // Convert the image to DDS format, with our own mip chain so that the
+        // filter, normal renormalization and alpha coverage are under control.
+        let chain = mip_chain(
+            image,
+            dds_config.mipmaps,
+            dds_config.mip_filter,
+            dds_config.alpha_test_ref,
+            renormalize,
         );
+        let dds_result = chain
+            .encode(image_format, dds_config.quality, Mipmaps::FromSurface)
+            .map(|mut surface| {
+                // The encoder writes opaque BC1; restore cutouts as 1-bit alpha.
+                if punch_through {
+                    apply_punch_through_alpha(&mut surface.data, &chain);
+                }
+                surface
+            })
+            .map_err(|e| e.to_string())
+            .and_then(|surface| surface.to_dds().map_err(|e| e.to_string()))
+            .and_then(|dds| {
+                // Older games cannot read the DX10 header image_dds writes.
+                if legacy_header {
+                    to_legacy_header(dds, image_format)
+                } else {
+                    Ok(dds)
+                }
+            });
 
         match dds_result {
             Ok(dds) => {
-                // Define the output DDS file path.
-                let dds_path = file_path_out.join(format!("{}.dds", file_name));
-
-                // Create a buffered writer for the DDS file.
-                let mut writer = std::io::BufWriter::new(
-                    std::fs::File::create(&dds_path).map_err(|e| {
-                        format!(
-                            "Failed to create DDS file '{}': {}",
-                            dds_path.display(),
-                            e
-                        )
-                    })?,
-                );
-
-                // Write the DDS data to the file.
-                dds.write(&mut writer)
-                    .map_err(|e| {
-                        format!(
-                            "Failed to write DDS file '{}': {}",
-                            dds_path.display(),
-                            e
-                        )
-                    })?;
+                // Serialize the DDS data; archives store it without recompressing.
+                let mut bytes = Vec::new();
+                dds.write(&mut bytes).map_err(|e| {
+                    format!("Failed to write DDS file '{}': {}", dds_path.display(), e)
+                })?;
+                output.write(&dds_path, &bytes, false)?;
 
                 // Return the path to the DDS file.
                 Ok(dds_path)
@@ -118,15 +268,91 @@ pub async fn convert_to_dds(
     Ok(result)
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-fn apply_gamma_correction(image: &image::Rgba32FImage) -> image::Rgba32FImage {
-    image::Rgba32FImage::from_fn(image.width(), image.height(), |x, y| {
-        let pixel = image.get_pixel(x, y);
-        image::Rgba([
-            pixel[0].powf(1.0 / 2.2),
-            pixel[1].powf(1.0 / 2.2),
-            pixel[2].powf(1.0 / 2.2),
-            pixel[3],
-        ])
-    })
+    fn analysis(alpha: AlphaContent, grayscale: bool) -> ImageAnalysis {
+        ImageAnalysis { alpha, grayscale }
+    }
+
+    #[test]
+    fn gradient_alpha_uses_bc7_or_bc3() {
+        let gradient = analysis(AlphaContent::Gradient, false);
+        assert_eq!(
+            map_analyzed_format(TextureRole::Diffuse, 4, &gradient, true),
+            ImageFormat::BC7RgbaUnormSrgb
+        );
+        assert_eq!(
+            map_analyzed_format(TextureRole::Diffuse, 4, &gradient, false),
+            ImageFormat::BC3RgbaUnormSrgb
+        );
+        assert_eq!(
+            map_analyzed_format(TextureRole::Specular, 4, &gradient, false),
+            ImageFormat::BC3RgbaUnorm
+        );
+        assert_eq!(
+            map_analyzed_format(TextureRole::Normal, 4, &gradient, false),
+            ImageFormat::BC3RgbaUnorm
+        );
+    }
+
+    #[test]
+    fn unused_and_binary_alpha_use_bc1() {
+        for alpha in [AlphaContent::Opaque, AlphaContent::Binary] {
+            assert_eq!(
+                map_analyzed_format(TextureRole::Diffuse, 4, &analysis(alpha, true), true),
+                ImageFormat::BC1RgbaUnormSrgb
+            );
+        }
+        // Without an alpha channel the analyzed alpha is ignored.
+        assert_eq!(
+            map_analyzed_format(
+                TextureRole::Diffuse,
+                3,
+                &analysis(AlphaContent::Gradient, false),
+                true
+            ),
+            ImageFormat::BC1RgbaUnormSrgb
+        );
+    }
+
+    #[test]
+    fn grayscale_masks_use_bc4() {
+        assert_eq!(
+            map_analyzed_format(
+                TextureRole::Specular,
+                4,
+                &analysis(AlphaContent::Opaque, true),
+                true
+            ),
+            ImageFormat::BC4RUnorm
+        );
+        assert_eq!(
+            map_analyzed_format(
+                TextureRole::Specular,
+                3,
+                &analysis(AlphaContent::Opaque, false),
+                true
+            ),
+            ImageFormat::BC1RgbaUnorm
+        );
+        assert_eq!(
+            map_analyzed_format(
+                TextureRole::Normal,
+                3,
+                &analysis(AlphaContent::Opaque, false),
+                true
+            ),
+            ImageFormat::BC5RgUnorm
+        );
+    }
+
+    #[test]
+    fn block_compressed_formats() {
+        assert!(is_block_compressed(ImageFormat::BC1RgbaUnorm));
+        assert!(is_block_compressed(ImageFormat::BC7RgbaUnormSrgb));
+        assert!(!is_block_compressed(ImageFormat::Bgra8Unorm));
+        assert!(!is_block_compressed(ImageFormat::Rgba8UnormSrgb));
+    }
 }
@@ -1,45 +1,204 @@
 // D:/Work/Projects/Modding/Skyrim/dds-edit/src/processing/processing_img.rs
 
 use crate::{
-    io::io_out::save_image,
+    io::{
+        io_in::load_image,
+        io_out::{intermediate_format, save_image},
+    },
     logging::{LogQueue, LogType},
-    processing::processing_dds::convert_to_dds,
+    processing::{
+        processing_analysis::analyze_image,
+        processing_channels::{apply_channel_ops, output_channels},
+        processing_dds::convert_to_dds,
+        processing_normal::{height_to_normal, renormalized, resize_normal_map},
+        processing_plan::{plan_generated_normal, plan_outputs, resolve_role},
+        processing_sharpen::unsharp_mask,
+        processing_size::pad_image,
+    },
     structs::{
+        struct_colorspace::ColorSpace,
         struct_config::Config,
+        struct_ddsconfig::DdsConvertConfig,
         struct_filetype::FileType,
         struct_imagedata::ImageData,
+        struct_normalgen::NormalGenConfig,
+        struct_runreport::{ProcessedOutputs, ResizeAction, TargetRecord},
+        struct_texturerole::TextureRole,
     },
 };
-use image::{self, imageops::FilterType, GenericImageView};
 use colored::Color;
+use image::{self, imageops::FilterType, DynamicImage, GenericImageView};
 
 /// Processes an individual image based on its file type.
 ///
 /// # Arguments
 ///
 /// * `image_data` - The `ImageData` instance containing image information.
-/// * `_config` - Reference to the `Config` struct containing configuration parameters.
-pub async fn process_img(image_data: ImageData, _config: &Config) {
+/// * `config` - Reference to the `Config` struct containing configuration parameters.
+///
+/// # Returns
+///
+/// * `Some(ProcessedOutputs)` with the files written, if the image was processed without errors.
+/// * `None` if any step failed.
+pub async fn process_img(mut image_data: ImageData, config: &Config) -> Option<ProcessedOutputs> {
+    // Use the configured role, or classify it from the file name suffix.
+    image_data.role = resolve_role(&image_data.file_name, config);
+
+    // Decode mode only writes DDS inputs back out as PNG.
+    if config.decode_only {
+        return process_image_decode(image_data, config).await;
+    }
+
+    // Height maps may also produce a generated normal map.
+    let normal_from_height = config
+        .normal_from_height
+        .clone()
+        .filter(|_| image_data.role == TextureRole::Height);
+    let height_data = image_data.clone();
+
     // Determine the processing function based on the image type.
-    match image_data.file_type {
-        FileType::JPG => process_image_base(image_data, _config).await,
-        FileType::PNG => process_image_base(image_data, _config).await,
-        FileType::UNKNOWN => process_image_unknown(image_data, _config).await,
+    let outputs = match image_data.file_type {
+        FileType::JPG => process_image_base(image_data, config).await,
+        FileType::PNG => process_image_base(image_data, config).await,
+        FileType::DDS
+        | FileType::TGA
+        | FileType::BMP
+        | FileType::TIFF
+        | FileType::WEBP
+        | FileType::PSD => process_image_base(image_data, config).await,
+        FileType::UNKNOWN => process_image_unknown(image_data, config).await,
+    };
+
+    match normal_from_height {
+        Some(settings) => {
+            let mut outputs = outputs?;
+            outputs.extend(process_height_normal(height_data, &settings, config).await?);
+            Some(outputs)
+        }
+        None => outputs,
     }
 }
 
-/// Processes images with known file types (JPG and PNG).
+/// Generates a normal map from a height map and converts it like any other input.
+///
+/// The normal map is planned by `plan_generated_normal`; if an image of its
+/// name already exists next to the height map, it is kept and nothing is
+/// generated.
+///
+/// # Arguments
+///
+/// * `height_data` - The `ImageData` of the height map.
+/// * `settings` - Reference to the `NormalGenConfig` with the generator settings.
+/// * `config` - The configuration resolved for the height map.
+///
+/// # Returns
+///
+/// * `Some(ProcessedOutputs)` with the files written, which is empty if generation was skipped.
+/// * `None` if loading the height map or any conversion failed.
+pub async fn process_height_normal(
+    height_data: ImageData,
+    settings: &NormalGenConfig,
+    config: &Config,
+) -> Option<ProcessedOutputs> {
+    let log_queue = LogQueue::new();
+    let planned = plan_generated_normal(&height_data, settings, config);
+    let name = match &planned {
+        Ok((image_data, _)) => image_data.file_name.clone(),
+        Err(existing) => existing
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned(),
+    };
+
+    log_queue.enqueue(
+        LogType::Println,
+        "--------------------------".to_string(),
+        Color::White,
+    );
+    log_queue.enqueue(
+        LogType::Println,
+        format!("[Generating Normal Map: {}]", name),
+        Color::White,
+    );
+
+    // Prefer a normal map supplied by the artist.
+    let (image_data, config) = match planned {
+        Ok(planned) => planned,
+        Err(existing) => {
+            log_queue.enqueue(
+                LogType::Println,
+                format!("Skipping, '{}' already exists.", existing.display()),
+                Color::Yellow,
+            );
+            log_queue.process_queue();
+            return Some(ProcessedOutputs::default());
+        }
+    };
+
+    log_queue.enqueue(LogType::Print, "Height Map: ".to_string(), Color::White);
+    log_queue.enqueue(
+        LogType::Println,
+        format!("{}", height_data.input_path().display()),
+        Color::BrightCyan,
+    );
+    log_queue.enqueue(LogType::Print, "Generator: ".to_string(), Color::White);
+    log_queue.enqueue(
+        LogType::Println,
+        format!(
+            "{}, Strength = {}, Wrap = {}",
+            settings.kernel, settings.strength, settings.wrap
+        ),
+        Color::BrightCyan,
+    );
+
+    let input_path = height_data.input_path();
+    let file_type = height_data.file_type;
+    let generator = settings.clone();
+    let normal_map = match tokio::task::spawn_blocking(move || {
+        load_image(&input_path, file_type).map(|loaded| height_to_normal(&loaded.image, &generator))
+    })
+    .await
+    {
+        Ok(Ok(normal_map)) => normal_map,
+        Ok(Err(e)) => {
+            log_queue.enqueue(LogType::Println, e, Color::Red);
+            log_queue.process_queue();
+            return None;
+        }
+        Err(e) => {
+            log_queue.enqueue(
+                LogType::Println,
+                format!("Failed to spawn blocking task: {}", e),
+                Color::Red,
+            );
+            log_queue.process_queue();
+            return None;
+        }
+    };
+
+    let normal_map = DynamicImage::ImageRgb32F(DynamicImage::ImageRgba32F(normal_map).to_rgb32f());
+    resize_and_convert(image_data, normal_map, log_queue, &config).await
+}
+
+/// Processes images with known file types (JPG, PNG, DDS, TGA, BMP, TIFF, WebP and PSD).
 ///
 /// This function handles resizing the image to multiple target widths and converting each resized image to DDS format.
+/// Resized images are passed to the encoder in memory and only written to disk when `keep_intermediate` is set.
 ///
 /// # Arguments
 ///
 /// * `image_data` - The `ImageData` instance containing image information.
 /// * `config` - Reference to the `Config` struct containing configuration parameters.
+///
+/// # Returns
+///
+/// * `Some(ProcessedOutputs)` with the files written and a record per target size, if every target size succeeded.
+/// * `None` if loading the image or any conversion failed.
 pub async fn process_image_base(
     mut image_data: ImageData,
     config: &Config,
-) {
+) -> Option<ProcessedOutputs> {
     let log_queue = LogQueue::new(); // Create a new log queue.
 
     // Log the start of processing for this image.
@@ -60,11 +219,7 @@ pub async fn process_image_base(
     );
 
     // Log "Input Path: " in white and the actual path in bright cyan.
-    log_queue.enqueue(
-        LogType::Print,
-        "Input Path: ".to_string(),
-        Color::White,
-    );
+    log_queue.enqueue(LogType::Print, "Input Path: ".to_string(), Color::White);
     log_queue.enqueue(
         LogType::Println,
         format!("{}", image_data.file_path_in.display()),
@@ -72,11 +227,7 @@ pub async fn process_image_base(
     );
 
     // Log "Output Path: " in white and the actual path in bright cyan.
-    log_queue.enqueue(
-        LogType::Print,
-        "Output Path: ".to_string(),
-        Color::White,
-    );
+    log_queue.enqueue(LogType::Print, "Output Path: ".to_string(), Color::White);
     log_queue.enqueue(
         LogType::Println,
         format!("{}", image_data.file_path_out.display()),
@@ -84,21 +235,22 @@ pub async fn process_image_base(
     );
 
     // Construct the full input file path.
-    let input_file_path = image_data.file_path_in.join(format!(
-        "{}.{}",
-        image_data.file_name,
-        image_data.file_type.to_string().to_lowercase()
-    ));
+    let input_file_path = image_data.input_path();
 
-    // Clone input_file_path before moving into closure
+    // Clone input_file_path and file_type before moving into closure
     let input_file_path_clone = input_file_path.clone();
+    let input_file_type = image_data.file_type;
 
     // Attempt to open the image file.
-    let img = match tokio::task::spawn_blocking(move || image::open(&input_file_path_clone)).await {
+    let loaded = match tokio::task::spawn_blocking(move || {
+        load_image(&input_file_path_clone, input_file_type)
+    })
+    .await
+    {
         Ok(img_result) => match img_result {
-            Ok(img) => img,
+            Ok(loaded) => loaded,
             Err(e) => {
-                // Handle ImageError
+                // Handle decoding errors
                 log_queue.enqueue(
                     LogType::Println,
                     format!(
@@ -109,7 +261,7 @@ pub async fn process_image_base(
                     Color::Red,
                 );
                 log_queue.process_queue();
-                return;
+                return None;
             }
         },
         Err(e) => {
@@ -124,14 +276,55 @@ pub async fn process_image_base(
                 Color::Red,
             );
             log_queue.process_queue();
-            return;
+            return None;
         }
     };
 
-    // Retrieve image dimensions and channel count.
+    // Retrieve the channel count after channel operations.
+    image_data.channels = output_channels(&config.channel_ops, loaded.channels);
+    image_data.file_type = loaded.file_type; // Trust the detected type over the extension.
+
+    // Resolve the color space from the role, file metadata and configuration.
+    image_data.color_space =
+        ColorSpace::resolve(image_data.role, config.color_space, loaded.color_space);
+
+    resize_and_convert(image_data, loaded.image, log_queue, config).await
+}
+
+/// Resizes a loaded image to every target width and converts each size to DDS.
+///
+/// Shared by single inputs and packed textures. The channel count and color
+/// space in `image_data` must already be resolved.
+///
+/// # Arguments
+///
+/// * `image_data` - The `ImageData` describing the image and its output path.
+/// * `img` - The image at its full resolution.
+/// * `log_queue` - The log queue of the image, printed when processing ends.
+/// * `config` - Reference to the `Config` struct containing configuration parameters.
+///
+/// # Returns
+///
+/// * `Some(ProcessedOutputs)` with the files written and a record per target size, if every target size succeeded.
+/// * `None` if any conversion failed.
+pub async fn resize_and_convert(
+    mut image_data: ImageData,
+    img: DynamicImage,
+    log_queue: LogQueue,
+    config: &Config,
+) -> Option<ProcessedOutputs> {
+    let mut outputs = ProcessedOutputs::default();
+    let mut failed = false;
+
+    // Log the texture role.
+    log_queue.enqueue(LogType::Print, "Texture Role: ".to_string(), Color::White);
+    log_queue.enqueue(
+        LogType::Println,
+        image_data.role.to_string(),
+        Color::BrightCyan,
+    );
+
     let (width, height) = img.dimensions();
-    let channels = img.color().channel_count();
-    image_data.channels = channels; // Update the channel count in ImageData.
 
     // Log image dimensions.
     log_queue.enqueue(
@@ -145,37 +338,184 @@ pub async fn process_image_base(
         Color::BrightCyan,
     );
 
-    // Iterate over each target width specified in the configuration.
-    for &target_width in &config.target_sizes {
+    // Log the resolved color space.
+    log_queue.enqueue(LogType::Print, "Color Space: ".to_string(), Color::White);
+    log_queue.enqueue(
+        LogType::Println,
+        image_data.color_space.to_string(),
+        Color::BrightCyan,
+    );
+
+    // Log the resize filter and sharpening.
+    log_queue.enqueue(LogType::Print, "Resize Filter: ".to_string(), Color::White);
+    log_queue.enqueue(
+        LogType::Println,
+        match &config.sharpen {
+            Some(sharpen) => format!("{}, Sharpen = {}", config.resize_filter, sharpen.amount),
+            None => config.resize_filter.to_string(),
+        },
+        Color::BrightCyan,
+    );
+
+    // Log the channel operations, if any.
+    if !config.channel_ops.is_empty() {
+        log_queue.enqueue(
+            LogType::Print,
+            "Channel Operations: ".to_string(),
+            Color::White,
+        );
+        log_queue.enqueue(
+            LogType::Println,
+            config
+                .channel_ops
+                .iter()
+                .map(|op| op.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            Color::BrightCyan,
+        );
+    }
+
+    // Analyze the pixels to pick the cheapest format, unless one is configured.
+    let dds_config = DdsConvertConfig::from(config);
+    if dds_config.format.is_none() {
+        let img_clone = img.clone();
+        let channel_ops = config.channel_ops.clone();
+        image_data.analysis = tokio::task::spawn_blocking(move || {
+            let mut image = img_clone.to_rgba32f();
+            apply_channel_ops(&mut image, &channel_ops);
+            analyze_image(&image)
+        })
+        .await
+        .ok();
+    }
+    if let Some(analysis) = &image_data.analysis {
+        log_queue.enqueue(LogType::Print, "Content: ".to_string(), Color::White);
+        log_queue.enqueue(LogType::Println, analysis.to_string(), Color::BrightCyan);
+    }
+
+    // Log the selected format, and warn when the game profile replaces the requested one.
+    let plan = plan_outputs(&image_data, width, height, config);
+    let (requested, selected) = (plan.requested, plan.selected);
+    log_queue.enqueue(LogType::Print, "Format: ".to_string(), Color::White);
+    log_queue.enqueue(LogType::Println, selected.to_string(), Color::BrightCyan);
+    if requested != selected {
+        log_queue.enqueue(
+            LogType::Println,
+            format!(
+                "Warning: {} is not supported by {}, falling back to {}",
+                requested, config.game, selected
+            ),
+            Color::Yellow,
+        );
+    }
+
+    // Iterate over each target size specified in the configuration.
+    for &(spec, size) in &plan.targets {
         log_queue.enqueue(
             LogType::Println,
-            format!("[Started Processing for Width: {}]", target_width),
+            format!("[Started Processing for Size: {}]", spec),
             Color::BrightBlue,
         );
 
-        // Skip invalid target widths.
-        if target_width == 0 || target_width > width {
+        // Sharpening strength is configured per target size.
+        let sharpen = config
+            .sharpen
+            .as_ref()
+            .map(|sharpen| (sharpen.amount_for(spec), sharpen.radius, sharpen.threshold))
+            .filter(|(amount, _, _)| *amount > 0.0);
+
+        // The upscale policy may skip target sizes larger than the image.
+        let Some(size) = size else {
             log_queue.enqueue(
                 LogType::Println,
                 format!(
-                    "Skipping target width {} for image '{}'.",
-                    target_width, image_data.file_name
+                    "Skipping target size {} for image '{}'.",
+                    spec, image_data.file_name
                 ),
                 Color::Yellow,
             );
+            outputs.targets.push(TargetRecord {
+                image: image_data.file_name.clone(),
+                size: spec.to_string(),
+                upscale: config.upscale,
+                action: ResizeAction::Skipped,
+                width: None,
+                height: None,
+                format: None,
+                output: None,
+            });
             continue;
+        };
+        match size.action {
+            ResizeAction::Clamped => log_queue.enqueue(
+                LogType::Println,
+                format!(
+                    "Target size {} is larger than image '{}', clamping to the source size.",
+                    spec, image_data.file_name
+                ),
+                Color::Yellow,
+            ),
+            ResizeAction::Upscaled => log_queue.enqueue(
+                LogType::Println,
+                format!(
+                    "Target size {} is larger than image '{}', upscaling with {}.",
+                    spec, image_data.file_name, config.upscale_filter
+                ),
+                Color::Yellow,
+            ),
+            ResizeAction::Resized | ResizeAction::Skipped => {}
         }
-
-        // Calculate the new height to maintain the aspect ratio.
-        let new_height =
-            ((target_width as f32 / width as f32) * height as f32) as u32;
+        log_queue.enqueue(
+            LogType::Print,
+            "Output Dimensions: ".to_string(),
+            Color::White,
+        );
+        log_queue.enqueue(
+            LogType::Println,
+            if size.is_padded() {
+                format!(
+                    "{}x{} (resized to {}x{} and padded)",
+                    size.canvas_width, size.canvas_height, size.width, size.height
+                )
+            } else {
+                format!("{}x{}", size.width, size.height)
+            },
+            Color::BrightCyan,
+        );
 
         // Clone img before moving into closure
         let img_clone = img.clone();
+        let renormalize = image_data.role == TextureRole::Normal && config.renormalize_normals;
+        let filter = FilterType::from(if size.action == ResizeAction::Upscaled {
+            config.upscale_filter
+        } else {
+            config.resize_filter
+        });
 
         // Resize the image asynchronously.
         let resized_img = match tokio::task::spawn_blocking(move || {
-            img_clone.resize(target_width, new_height, FilterType::Triangle)
+            let resized = if renormalize {
+                resize_normal_map(&img_clone, size.width, size.height, filter)
+            } else {
+                img_clone.resize_exact(size.width, size.height, filter)
+            };
+            let resized = match sharpen {
+                Some((amount, radius, threshold)) => {
+                    let sharpened = unsharp_mask(resized, amount, radius, threshold);
+                    if renormalize {
+                        renormalized(sharpened)
+                    } else {
+                        sharpened
+                    }
+                }
+                None => resized,
+            };
+            if size.is_padded() {
+                pad_image(resized, size.canvas_width, size.canvas_height)
+            } else {
+                resized
+            }
         })
         .await
         {
@@ -186,115 +526,190 @@ pub async fn process_image_base(
                     format!("Failed to resize image '{}': {}", image_data.file_name, e),
                     Color::Red,
                 );
+                failed = true;
                 continue;
             }
         };
 
-        // Prepare variables for output path and file type
-        let file_type_string = image_data.file_type.to_string().to_lowercase();
-        let output_path = image_data.file_path_out.join(format!(
-            "{}.{}",
-            image_data.file_name,
-            file_type_string
-        ));
-
-        // Prepare variables to move into the closure
-        let resized_img_clone = resized_img.clone();
-        let output_path_clone = output_path.clone();
-        let target_width_string = target_width.to_string();
-        let file_type = image_data.file_type; // FileType is now Copy
-
-        // Save the resized image.
-        let save_result = match tokio::task::spawn_blocking(move || {
-            save_image(
-                &resized_img_clone,
-                &output_path_clone,
-                &target_width_string,
-                file_type, // Pass by value
-            )
-        })
-        .await
-        {
-            Ok(save_result) => save_result,
-            Err(e) => {
-                log_queue.enqueue(
-                    LogType::Println,
-                    format!(
-                        "Failed to spawn blocking task for saving image '{}': {}",
-                        image_data.file_name, e
-                    ),
-                    Color::Red,
-                );
-                continue;
+        // Optionally save the resized image alongside the DDS output.
+        if config.keep_intermediate {
+            // Prepare variables for output path and file type
+            let file_type = image_data.file_type; // FileType is now Copy
+            let (extension, _) = intermediate_format(file_type);
+            let output_path = plan.output_path(config, &image_data, spec, &size, extension);
+
+            // Prepare variables to move into the closure
+            let resized_img_clone = resized_img.clone();
+            let output = config.output.clone();
+
+            let save_result = tokio::task::spawn_blocking(move || {
+                save_image(&resized_img_clone, &output_path, file_type, &output)
+            })
+            .await
+            .map_err(|e| format!("Failed to spawn blocking task: {}", e))
+            .and_then(|result| result);
+
+            match save_result {
+                Ok(saved_path) => {
+                    log_queue.enqueue(
+                        LogType::Print,
+                        "Saved resized image: ".to_string(),
+                        Color::White,
+                    );
+                    log_queue.enqueue(
+                        LogType::Println,
+                        format!("'{}'", saved_path.display()),
+                        Color::Green,
+                    );
+                    outputs.files.push(saved_path);
+                }
+                Err(e) => {
+                    // A failed intermediate does not prevent the DDS conversion.
+                    log_queue.enqueue(
+                        LogType::Println,
+                        format!(
+                            "Failed to save resized image for '{}': {}",
+                            image_data.file_name, e
+                        ),
+                        Color::Red,
+                    );
+                    failed = true;
+                }
             }
-        };
+        }
 
-        match save_result {
-            Ok(saved_path) => {
+        // Convert the resized image to DDS format directly from memory.
+        // Name the outputs from the layout and naming template.
+        let dds_path = plan.output_path(config, &image_data, spec, &size, "dds");
+        let convert_result = convert_to_dds(resized_img, dds_path, &image_data, config).await;
+        match convert_result {
+            Ok(dds_path) => {
                 log_queue.enqueue(
                     LogType::Print,
-                    "Saved resized image: ".to_string(),
+                    "Converted to DDS: ".to_string(),
                     Color::White,
                 );
                 log_queue.enqueue(
                     LogType::Println,
-                    format!("'{}'", saved_path.display()),
+                    format!("'{}'", dds_path.display()),
                     Color::Green,
                 );
-
-                // Define the output path for the DDS conversion.
-                let output_path_dds = image_data.file_path_out.join(format!(
-                    "{}_{}.{}",
-                    image_data.file_name,
-                    target_width,
-                    file_type_string
-                ));
-
-                // Clone output_path_dds and image_data for moving into closure
-                let output_path_dds_clone = output_path_dds.clone();
-                let image_data_clone = image_data.clone();
-
-                // Convert the resized image to DDS format.
-                let convert_result =
-                    convert_to_dds(&output_path_dds_clone, &image_data_clone).await;
-                match convert_result {
-                    Ok(dds_path) => {
-                        log_queue.enqueue(
-                            LogType::Print,
-                            "Converted to DDS: ".to_string(),
-                            Color::White,
-                        );
-                        log_queue.enqueue(
-                            LogType::Println,
-                            format!("'{}'", dds_path.display()),
-                            Color::Green,
-                        );
-                    }
-                    Err(e) => {
-                        log_queue.enqueue(
-                            LogType::Println,
-                            format!("Failed to convert image to DDS: {}", e),
-                            Color::Red,
-                        );
-                    }
-                }
+                outputs.targets.push(TargetRecord {
+                    image: image_data.file_name.clone(),
+                    size: spec.to_string(),
+                    upscale: config.upscale,
+                    action: size.action,
+                    width: Some(size.canvas_width),
+                    height: Some(size.canvas_height),
+                    format: Some(selected.to_string()),
+                    output: Some(dds_path.clone()),
+                });
+                outputs.files.push(dds_path);
             }
             Err(e) => {
-                // Log the error if saving the resized image fails.
                 log_queue.enqueue(
                     LogType::Println,
-                    format!(
-                        "Failed to save resized image for '{}': {}",
-                        image_data.file_name, e
-                    ),
+                    format!("Failed to convert image to DDS: {}", e),
                     Color::Red,
                 );
+                failed = true;
             }
         }
     }
 
     // Process and print all accumulated logs.
     log_queue.process_queue();
+
+    (!failed).then_some(outputs)
+}
+
+/// Decodes a DDS image to a PNG file of the same name, without resizing.
+///
+/// Inputs that are not DDS files are logged and skipped.
+///
+/// # Arguments
+///
+/// * `image_data` - The `ImageData` instance containing image information.
+/// * `config` - Reference to the `Config` struct holding the output backend.
+///
+/// # Returns
+///
+/// * `Some(ProcessedOutputs)` with the decoded PNG, or no files for skipped inputs.
+/// * `None` if decoding failed.
+pub async fn process_image_decode(
+    image_data: ImageData,
+    config: &Config,
+) -> Option<ProcessedOutputs> {
+    let log_queue = LogQueue::new(); // Create a new log queue.
+
+    log_queue.enqueue(
+        LogType::Println,
+        "--------------------------".to_string(),
+        Color::White,
+    );
+    log_queue.enqueue(
+        LogType::Println,
+        format!("[Decoding Image: {}]", image_data.file_name),
+        Color::White,
+    );
+
+    if !matches!(image_data.file_type, FileType::DDS) {
+        log_queue.enqueue(
+            LogType::Println,
+            format!(
+                "Skipping non-DDS file '{}'.",
+                image_data.input_path().display()
+            ),
+            Color::Yellow,
+        );
+        log_queue.process_queue();
+        return Some(ProcessedOutputs::default());
+    }
+
+    let input_file_path = image_data.input_path();
+    let output_file_path = image_data
+        .file_path_out
+        .join(format!("{}.png", image_data.file_name));
+
+    // Decode and save in a blocking task.
+    let input_clone = input_file_path.clone();
+    let output_clone = output_file_path.clone();
+    let output = config.output.clone();
+    let decode_result = tokio::task::spawn_blocking(move || {
+        let loaded = load_image(&input_clone, FileType::DDS)?;
+        save_image(&loaded.image, &output_clone, FileType::PNG, &output).map(|_| ())
+    })
+    .await
+    .map_err(|e| format!("Failed to spawn blocking task: {}", e))
+    .and_then(|result| result);
+
+    let outputs = match decode_result {
+        Ok(()) => {
+            log_queue.enqueue(LogType::Print, "Decoded to PNG: ".to_string(), Color::White);
+            log_queue.enqueue(
+                LogType::Println,
+                format!("'{}'", output_file_path.display()),
+                Color::Green,
+            );
+            Some(ProcessedOutputs {
+                files: vec![output_file_path],
+                ..ProcessedOutputs::default()
+            })
+        }
+        Err(e) => {
+            log_queue.enqueue(
+                LogType::Println,
+                format!("Failed to decode '{}': {}", input_file_path.display(), e),
+                Color::Red,
+            );
+            None
+        }
+    };
+
+    // Process and print all accumulated logs.
+    log_queue.process_queue();
+
+    outputs
 }
 
 /// Processes images with unknown file types.
@@ -305,10 +720,14 @@ pub async fn process_image_base(
 ///
 /// * `image_data` - The `ImageData` instance containing image information.
 /// * `_config` - Reference to the `Config` struct containing configuration parameters.
+///
+/// # Returns
+///
+/// * `Some(ProcessedOutputs)` - Always empty, as nothing is written.
 pub async fn process_image_unknown(
     image_data: ImageData,
     _config: &Config,
-) {
+) -> Option<ProcessedOutputs> {
     let log_queue = LogQueue::new(); // Create a new log queue.
 
     // Log details about the image with an unknown file type.
@@ -317,21 +736,13 @@ pub async fn process_image_unknown(
         "[Processing Image: Unknown]".to_string(),
         Color::White,
     );
-    log_queue.enqueue(
-        LogType::Print,
-        "Input Path: ".to_string(),
-        Color::White,
-    );
+    log_queue.enqueue(LogType::Print, "Input Path: ".to_string(), Color::White);
     log_queue.enqueue(
         LogType::Println,
         format!("{}", image_data.file_path_in.display()),
         Color::BrightCyan,
     );
-    log_queue.enqueue(
-        LogType::Print,
-        "Output Path: ".to_string(),
-        Color::White,
-    );
+    log_queue.enqueue(LogType::Print, "Output Path: ".to_string(), Color::White);
     log_queue.enqueue(
         LogType::Println,
         format!("{}", image_data.file_path_out.display()),
@@ -345,4 +756,5 @@ pub async fn process_image_unknown(
 
     // Process and print all accumulated logs.
     log_queue.process_queue();
+    Some(ProcessedOutputs::default())
 }
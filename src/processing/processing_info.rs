@@ -0,0 +1,386 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/processing/processing_info.rs
+
+use colored::Color;
+use image::GenericImageView;
+use image_dds::ddsfile::Dds;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    io::{
+        io_dds::{
+            dds_channel_count, dds_expected_data_size, dds_image_format, is_cubemap, read_dds,
+        },
+        io_in::{detect_file_type, load_image},
+        io_source::InputSource,
+    },
+    logging::{smnprint, smnprintln},
+    structs::{
+        struct_fileinfo::{DdsInfo, FileInfo, ImageInfo},
+        struct_filetype::FileType,
+        struct_texturerole::TextureRole,
+    },
+};
+
+/// Prints header details for each file, either human-readable or as a JSON array.
+///
+/// DDS files are described from their header without decoding the pixel data;
+/// other images are decoded to report their dimensions and channels.
+///
+/// # Arguments
+///
+/// * `paths` - Files to describe.
+/// * `json` - Print a JSON array instead of the human-readable listing.
+///
+/// # Returns
+///
+/// * `true` if every file could be read, `false` otherwise.
+pub fn print_info(paths: &[PathBuf], json: bool) -> bool {
+    let infos: Vec<FileInfo> = paths.iter().map(|path| file_info(path)).collect();
+    let all_ok = !infos
+        .iter()
+        .any(|info| matches!(info, FileInfo::Error { .. }));
+
+    if json {
+        match serde_json::to_string_pretty(&infos) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                eprintln!("Failed to serialize file info: {}", e);
+                return false;
+            }
+        }
+    } else {
+        for info in &infos {
+            print_file_info(info);
+        }
+    }
+
+    all_ok
+}
+
+/// Collects the details of a single file.
+///
+/// # Arguments
+///
+/// * `path` - Path to the file.
+///
+/// # Returns
+///
+/// * `FileInfo` - The file's details, or the error that prevented reading it.
+fn file_info(path: &Path) -> FileInfo {
+    let extension = path.extension().unwrap_or_default().to_string_lossy();
+    let file_type = detect_file_type(path).unwrap_or(FileType::from(extension.as_ref()));
+    let file_size = InputSource::of(path).size();
+
+    let result = match file_type {
+        FileType::DDS => {
+            read_dds(path).map(|dds| FileInfo::Dds(Box::new(dds_info(path, file_size, &dds))))
+        }
+        _ => image_info(path, file_size, file_type).map(FileInfo::Image),
+    };
+
+    result.unwrap_or_else(|error| FileInfo::Error {
+        path: path.display().to_string(),
+        error,
+    })
+}
+
+/// Collects the header fields of a DDS file.
+///
+/// # Arguments
+///
+/// * `path` - Path to the file.
+/// * `file_size` - Size of the file in bytes.
+/// * `dds` - Reference to the parsed `Dds`.
+fn dds_info(path: &Path, file_size: u64, dds: &Dds) -> DdsInfo {
+    let header = &dds.header;
+    let spf = &header.spf;
+    let header10 = dds.header10.as_ref();
+
+    DdsInfo {
+        path: path.display().to_string(),
+        file_size,
+        header: if header10.is_some() { "DX10" } else { "Legacy" }.to_string(),
+        width: dds.get_width(),
+        height: dds.get_height(),
+        depth: dds.get_depth(),
+        mipmap_count: dds.get_num_mipmap_levels(),
+        array_size: dds.get_num_array_layers(),
+        format: dds_image_format(dds).ok().map(|format| format.to_string()),
+        fourcc: spf.fourcc.as_ref().map(|fourcc| fourcc_string(fourcc.0)),
+        dxgi_format: header10.map(|header10| format!("{:?}", header10.dxgi_format)),
+        d3d_format: dds.get_d3d_format().map(|format| format!("{:?}", format)),
+        pixel_format_flags: flag_names(spf.flags.iter_names()),
+        rgb_bit_count: spf.rgb_bit_count,
+        bit_masks: spf.rgb_bit_count.map(|_| {
+            [
+                spf.r_bit_mask.unwrap_or(0),
+                spf.g_bit_mask.unwrap_or(0),
+                spf.b_bit_mask.unwrap_or(0),
+                spf.a_bit_mask.unwrap_or(0),
+            ]
+        }),
+        caps: flag_names(header.caps.iter_names()),
+        caps2: flag_names(header.caps2.iter_names()),
+        cubemap: is_cubemap(dds),
+        resource_dimension: header10.map(|header10| format!("{:?}", header10.resource_dimension)),
+        misc_flags: header10
+            .map(|header10| flag_names(header10.misc_flag.iter_names()))
+            .unwrap_or_default(),
+        alpha_mode: header10.map(|header10| format!("{:?}", header10.alpha_mode)),
+        pitch: header.pitch,
+        linear_size: header.linear_size,
+        channels: dds_channel_count(dds),
+        data_size: dds.data.len(),
+        expected_data_size: dds_expected_data_size(dds),
+    }
+}
+
+/// Collects the details of a non-DDS image by decoding it.
+///
+/// # Arguments
+///
+/// * `path` - Path to the image.
+/// * `file_size` - Size of the file in bytes.
+/// * `file_type` - The detected `FileType` of the image.
+fn image_info(path: &Path, file_size: u64, file_type: FileType) -> Result<ImageInfo, String> {
+    let loaded = load_image(path, file_type)?;
+    let (width, height) = loaded.image.dimensions();
+    let file_name = path.file_stem().unwrap_or_default().to_string_lossy();
+
+    Ok(ImageInfo {
+        path: path.display().to_string(),
+        file_size,
+        file_type: loaded.file_type.to_string(),
+        width,
+        height,
+        channels: loaded.channels,
+        color_space: loaded
+            .color_space
+            .map(|color_space| color_space.to_string()),
+        role: TextureRole::classify(&file_name, &HashMap::new()).to_string(),
+    })
+}
+
+/// Prints the details of a file in human-readable form.
+fn print_file_info(info: &FileInfo) {
+    smnprintln("--------------------------", Color::White);
+    match info {
+        FileInfo::Dds(info) => {
+            smnprintln(format!("[{}]", info.path), Color::White);
+            print_field("Type", "DDS".to_string());
+            print_field("File Size", format!("{} bytes", info.file_size));
+            print_field("Header", info.header.clone());
+            print_field(
+                "Dimensions",
+                format!(
+                    "Width = {}, Height = {}, Depth = {}",
+                    info.width, info.height, info.depth
+                ),
+            );
+            print_field("Format", optional(&info.format, "Unsupported"));
+            print_field("FourCC", optional(&info.fourcc, "None"));
+            print_field("DXGI Format", optional(&info.dxgi_format, "None"));
+            print_field("D3D Format", optional(&info.d3d_format, "None"));
+            print_field("Pixel Format Flags", info.pixel_format_flags.join(" | "));
+            if let (Some(bit_count), Some(masks)) = (info.rgb_bit_count, info.bit_masks) {
+                print_field(
+                    "Bit Masks",
+                    format!(
+                        "{} bits, R {:#010x}, G {:#010x}, B {:#010x}, A {:#010x}",
+                        bit_count, masks[0], masks[1], masks[2], masks[3]
+                    ),
+                );
+            }
+            print_field("Channels", info.channels.to_string());
+            print_field("Mipmaps", info.mipmap_count.to_string());
+            print_field("Array Size", info.array_size.to_string());
+            print_field(
+                "Cubemap",
+                if info.cubemap { "Yes" } else { "No" }.to_string(),
+            );
+            print_field("Caps", info.caps.join(" | "));
+            print_field("Caps2", info.caps2.join(" | "));
+            if info.header == "DX10" {
+                print_field(
+                    "Resource Dimension",
+                    optional(&info.resource_dimension, "None"),
+                );
+                print_field("Misc Flags", info.misc_flags.join(" | "));
+                print_field("Alpha Mode", optional(&info.alpha_mode, "None"));
+            }
+            print_field("Pitch", optional(&info.pitch, "None"));
+            print_field("Linear Size", optional(&info.linear_size, "None"));
+            print_field("Data Size", format!("{} bytes", info.data_size));
+
+            // Truncated or oversized data is a common cause of black textures in-game.
+            if let Some(expected) = info.expected_data_size {
+                if expected != info.data_size as u64 {
+                    smnprintln(
+                        format!(
+                            "Warning: data size does not match the header ({} bytes expected)",
+                            expected
+                        ),
+                        Color::Yellow,
+                    );
+                }
+            }
+        }
+        FileInfo::Image(info) => {
+            smnprintln(format!("[{}]", info.path), Color::White);
+            print_field("Type", info.file_type.clone());
+            print_field("File Size", format!("{} bytes", info.file_size));
+            print_field(
+                "Dimensions",
+                format!("Width = {}, Height = {}", info.width, info.height),
+            );
+            print_field("Channels", info.channels.to_string());
+            print_field("Color Space", optional(&info.color_space, "Not declared"));
+            print_field("Texture Role", info.role.clone());
+        }
+        FileInfo::Error { path, error } => {
+            smnprintln(format!("[{}]", path), Color::White);
+            smnprintln(error, Color::Red);
+        }
+    }
+}
+
+/// Prints a labelled value in the style of the other log output.
+fn print_field(label: &str, value: String) {
+    smnprint(format!("{}: ", label), Color::White);
+    smnprintln(value, Color::BrightCyan);
+}
+
+/// Formats an optional value, using `fallback` when it is unset.
+fn optional<T: std::fmt::Display>(value: &Option<T>, fallback: &str) -> String {
+    match value {
+        Some(value) => value.to_string(),
+        None => fallback.to_string(),
+    }
+}
+
+/// Collects the names of the set bits of a flags value.
+fn flag_names<'a, T>(names: impl Iterator<Item = (&'a str, T)>) -> Vec<String> {
+    names.map(|(name, _)| name.to_string()).collect()
+}
+
+/// Converts a FourCC code to its four-character string.
+fn fourcc_string(fourcc: u32) -> String {
+    fourcc
+        .to_le_bytes()
+        .iter()
+        .map(|&b| if b.is_ascii_graphic() { b as char } else { '?' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::io_dds::to_legacy_header;
+    use image_dds::{ImageFormat, Mipmaps, Quality};
+
+    /// Encodes a 16x16 texture with a full mip chain, with a legacy header if set.
+    fn encode_dds(format: ImageFormat, legacy: bool) -> Dds {
+        let image = image::RgbaImage::from_pixel(16, 16, image::Rgba([90, 90, 90, 255]));
+        let dds =
+            image_dds::dds_from_image(&image, format, Quality::Fast, Mipmaps::GeneratedAutomatic)
+                .unwrap();
+        if legacy {
+            to_legacy_header(dds, format).unwrap()
+        } else {
+            dds
+        }
+    }
+
+    fn write_dds(path: &Path, dds: &Dds) {
+        let mut bytes = Vec::new();
+        dds.write(&mut bytes).unwrap();
+        std::fs::write(path, bytes).unwrap();
+    }
+
+    fn dds_file_info(path: &Path) -> DdsInfo {
+        match file_info(path) {
+            FileInfo::Dds(info) => *info,
+            other => panic!("not a DDS: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn legacy_bc4_reports_the_stored_fourcc() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("height_h.dds");
+        write_dds(&path, &encode_dds(ImageFormat::BC4RUnorm, true));
+
+        let info = dds_file_info(&path);
+        assert_eq!(info.header, "Legacy");
+        assert_eq!(info.fourcc.as_deref(), Some("ATI1"));
+        assert_eq!(info.format.as_deref(), Some("BC4RUnorm"));
+        assert_eq!(info.channels, 1);
+    }
+
+    #[test]
+    fn dx10_header_reports_dxgi_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("albedo.dds");
+        write_dds(&path, &encode_dds(ImageFormat::BC7RgbaUnorm, false));
+
+        let info = dds_file_info(&path);
+        assert_eq!(info.header, "DX10");
+        assert_eq!(info.fourcc.as_deref(), Some("DX10"));
+        assert_eq!(info.dxgi_format.as_deref(), Some("BC7_UNorm"));
+        assert_eq!(info.format.as_deref(), Some("BC7RgbaUnorm"));
+        assert_eq!((info.width, info.height), (16, 16));
+        assert_eq!(info.mipmap_count, 5);
+        assert_eq!(info.expected_data_size, Some(info.data_size as u64));
+    }
+
+    #[test]
+    fn legacy_header_reports_fourcc_without_dxgi_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("albedo.dds");
+        write_dds(&path, &encode_dds(ImageFormat::BC1RgbaUnorm, true));
+
+        let info = dds_file_info(&path);
+        assert_eq!(info.header, "Legacy");
+        assert_eq!(info.fourcc.as_deref(), Some("DXT1"));
+        assert_eq!(info.dxgi_format, None);
+        assert_eq!(info.resource_dimension, None);
+        assert_eq!(info.mipmap_count, 5);
+        assert_eq!(info.expected_data_size, Some(info.data_size as u64));
+    }
+
+    #[test]
+    fn truncated_data_does_not_match_the_expected_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("albedo.dds");
+        let mut dds = encode_dds(ImageFormat::BC3RgbaUnorm, true);
+        let full_size = dds.data.len();
+        dds.data.truncate(full_size - 16);
+        write_dds(&path, &dds);
+
+        let info = dds_file_info(&path);
+        assert_eq!(info.data_size, full_size - 16);
+        assert_eq!(info.expected_data_size, Some(full_size as u64));
+    }
+
+    #[test]
+    fn unreadable_file_reports_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("broken.dds");
+        std::fs::write(&path, b"DDS not really").unwrap();
+
+        assert!(matches!(file_info(&path), FileInfo::Error { .. }));
+    }
+
+    #[test]
+    fn fourcc_string_replaces_unprintable_bytes() {
+        assert_eq!(fourcc_string(u32::from_le_bytes(*b"DXT5")), "DXT5");
+        assert_eq!(fourcc_string(u32::from_le_bytes(*b"ATI2")), "ATI2");
+        assert_eq!(
+            fourcc_string(u32::from_le_bytes([b'A', 0, b' ', 0x7f])),
+            "A???"
+        );
+    }
+}
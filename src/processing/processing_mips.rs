@@ -0,0 +1,337 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/processing/processing_mips.rs
+
+use std::f32::consts::PI;
+
+use image::Rgba32FImage;
+use image_dds::{Mipmaps, SurfaceRgba32Float};
+
+use crate::structs::struct_ddsconfig::MipFilter;
+
+use super::processing_normal::renormalize;
+
+/// Shape parameter of the Kaiser window.
+const KAISER_ALPHA: f32 = 4.0;
+
+/// Builds the mip chain of an image.
+///
+/// Each level is downsampled from the previous one with `filter`. Normal maps
+/// are renormalized on every level, so that distant surfaces keep their detail
+/// instead of fading towards flat. With an alpha test reference, the alpha of
+/// every level is rescaled so that the same share of pixels passes the alpha
+/// test as on the base level; otherwise cutout textures such as foliage and
+/// hair thin out with distance.
+///
+/// # Arguments
+///
+/// * `image` - The base level.
+/// * `mipmaps` - The requested mipmap setting.
+/// * `filter` - The downsampling filter.
+/// * `alpha_test_ref` - Alpha test reference whose coverage is preserved, if set.
+/// * `renormalize_normals` - Treat RGB as normal vectors and renormalize every level.
+///
+/// # Returns
+///
+/// * `SurfaceRgba32Float<Vec<f32>>` - The surface with all levels, to be encoded
+///   with `Mipmaps::FromSurface`.
+pub fn mip_chain(
+    image: Rgba32FImage,
+    mipmaps: Mipmaps,
+    filter: MipFilter,
+    alpha_test_ref: Option<f32>,
+    renormalize_normals: bool,
+) -> SurfaceRgba32Float<Vec<f32>> {
+    let (width, height) = image.dimensions();
+    let max_levels = u32::BITS - width.max(height).leading_zeros();
+    let levels = match mipmaps {
+        Mipmaps::Disabled | Mipmaps::FromSurface => 1,
+        Mipmaps::GeneratedExact(count) => count.clamp(1, max_levels),
+        Mipmaps::GeneratedAutomatic => max_levels,
+    };
+    let coverage = alpha_test_ref.map(|reference| (reference, alpha_coverage(&image, reference)));
+
+    let mut data = image.as_raw().clone();
+    let mut level = image;
+    for mipmap in 1..levels {
+        let mip_width = (width >> mipmap).max(1);
+        let mip_height = (height >> mipmap).max(1);
+        level = downsample(&level, mip_width, mip_height, filter);
+        if renormalize_normals {
+            renormalize(&mut level);
+        }
+
+        // Scale a copy, so the next level is filtered from the original alpha.
+        match coverage {
+            Some((reference, coverage)) => {
+                let mut scaled = level.clone();
+                scale_alpha_to_coverage(&mut scaled, reference, coverage);
+                data.extend_from_slice(scaled.as_raw());
+            }
+            None => data.extend_from_slice(level.as_raw()),
+        }
+    }
+
+    SurfaceRgba32Float {
+        width,
+        height,
+        depth: 1,
+        layers: 1,
+        mipmaps: levels,
+        data,
+    }
+}
+
+/// Downsamples an image with a separable filter.
+///
+/// # Arguments
+///
+/// * `image` - The image to downsample.
+/// * `width` - Target width.
+/// * `height` - Target height.
+/// * `filter` - The filter kernel.
+///
+/// # Returns
+///
+/// * `Rgba32FImage` - The downsampled image, clamped to `[0, 1]`.
+fn downsample(image: &Rgba32FImage, width: u32, height: u32, filter: MipFilter) -> Rgba32FImage {
+    let (source_width, source_height) = image.dimensions();
+
+    let columns = axis_weights(source_width, width, filter);
+    let horizontal = Rgba32FImage::from_fn(width, source_height, |x, y| {
+        let (start, weights) = &columns[x as usize];
+        let mut sum = [0.0; 4];
+        for (offset, weight) in weights.iter().enumerate() {
+            let pixel = image.get_pixel((start + offset) as u32, y);
+            for channel in 0..4 {
+                sum[channel] += pixel[channel] * weight;
+            }
+        }
+        image::Rgba(sum)
+    });
+
+    let rows = axis_weights(source_height, height, filter);
+    Rgba32FImage::from_fn(width, height, |x, y| {
+        let (start, weights) = &rows[y as usize];
+        let mut sum = [0.0; 4];
+        for (offset, weight) in weights.iter().enumerate() {
+            let pixel = horizontal.get_pixel(x, (start + offset) as u32);
+            for channel in 0..4 {
+                sum[channel] += pixel[channel] * weight;
+            }
+        }
+        // Windowed sinc filters overshoot at hard edges.
+        image::Rgba(sum.map(|value| value.clamp(0.0, 1.0)))
+    })
+}
+
+/// Computes the normalized filter weights of every output pixel along one axis.
+///
+/// # Arguments
+///
+/// * `source` - Number of source pixels.
+/// * `target` - Number of output pixels.
+/// * `filter` - The filter kernel.
+///
+/// # Returns
+///
+/// * `Vec<(usize, Vec<f32>)>` - Per output pixel, the first source pixel and the
+///   weights of the consecutive source pixels starting there.
+fn axis_weights(source: u32, target: u32, filter: MipFilter) -> Vec<(usize, Vec<f32>)> {
+    let scale = (source as f32 / target as f32).max(1.0);
+    let support = filter_radius(filter) * scale;
+
+    (0..target)
+        .map(|i| {
+            let center = (i as f32 + 0.5) * source as f32 / target as f32;
+            let start = (center - support).floor().max(0.0) as usize;
+            let end = ((center + support).ceil() as usize).min(source as usize);
+            let mut weights: Vec<f32> = (start..end)
+                .map(|j| filter_kernel(filter, (j as f32 + 0.5 - center) / scale))
+                .collect();
+
+            let total: f32 = weights.iter().sum();
+            if total.abs() > f32::EPSILON {
+                weights.iter_mut().for_each(|weight| *weight /= total);
+            }
+            (start, weights)
+        })
+        .collect()
+}
+
+/// Returns the radius of a filter kernel, in output pixels.
+fn filter_radius(filter: MipFilter) -> f32 {
+    match filter {
+        MipFilter::Box => 0.5,
+        MipFilter::Kaiser | MipFilter::Lanczos => 3.0,
+    }
+}
+
+/// Evaluates a filter kernel at distance `t`, in output pixels.
+fn filter_kernel(filter: MipFilter, t: f32) -> f32 {
+    let radius = filter_radius(filter);
+    if t.abs() > radius {
+        return 0.0;
+    }
+    match filter {
+        MipFilter::Box => 1.0,
+        MipFilter::Kaiser => {
+            let window = (1.0 - (t / radius).powi(2)).sqrt();
+            sinc(t) * bessel_i0(KAISER_ALPHA * window) / bessel_i0(KAISER_ALPHA)
+        }
+        MipFilter::Lanczos => sinc(t) * sinc(t / radius),
+    }
+}
+
+/// Normalized sinc function, `sin(pi x) / (pi x)`.
+fn sinc(x: f32) -> f32 {
+    if x.abs() < f32::EPSILON {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+/// Zeroth-order modified Bessel function of the first kind, by its power series.
+fn bessel_i0(x: f32) -> f32 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let half_squared = x * x / 4.0;
+    for k in 1..32 {
+        term *= half_squared / (k * k) as f32;
+        sum += term;
+        if term < sum * 1e-7 {
+            break;
+        }
+    }
+    sum
+}
+
+/// Returns the share of pixels whose alpha passes the alpha test.
+///
+/// # Arguments
+///
+/// * `image` - The image.
+/// * `reference` - The alpha test reference.
+fn alpha_coverage(image: &Rgba32FImage, reference: f32) -> f32 {
+    let passed = image.pixels().filter(|pixel| pixel[3] > reference).count();
+    passed as f32 / image.pixels().len().max(1) as f32
+}
+
+/// Scales alpha so that the given share of pixels passes the alpha test.
+///
+/// Searches for the threshold at which the current alpha has the target
+/// coverage, then scales alpha so that this threshold maps to `reference`.
+///
+/// # Arguments
+///
+/// * `image` - The mip level to modify.
+/// * `reference` - The alpha test reference.
+/// * `coverage` - The coverage of the base level.
+fn scale_alpha_to_coverage(image: &mut Rgba32FImage, reference: f32, coverage: f32) {
+    let (mut low, mut high) = (0.0f32, 1.0f32);
+    for _ in 0..16 {
+        let threshold = (low + high) / 2.0;
+        if alpha_coverage(image, threshold) > coverage {
+            low = threshold;
+        } else {
+            high = threshold;
+        }
+    }
+
+    let threshold = (low + high) / 2.0;
+    if threshold <= f32::EPSILON {
+        return;
+    }
+    let scale = reference / threshold;
+    for pixel in image.pixels_mut() {
+        pixel[3] = (pixel[3] * scale).min(1.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILTERS: [MipFilter; 3] = [MipFilter::Box, MipFilter::Kaiser, MipFilter::Lanczos];
+
+    /// Returns the pixels of one level of a mip chain.
+    fn level(surface: &SurfaceRgba32Float<Vec<f32>>, mipmap: u32) -> Vec<[f32; 4]> {
+        let offset: usize = (0..mipmap)
+            .map(|m| ((surface.width >> m).max(1) * (surface.height >> m).max(1)) as usize * 4)
+            .sum();
+        let size = ((surface.width >> mipmap).max(1) * (surface.height >> mipmap).max(1)) as usize;
+        surface.data[offset..offset + size * 4]
+            .chunks_exact(4)
+            .map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3]])
+            .collect()
+    }
+
+    #[test]
+    fn counts_levels() {
+        let image = || Rgba32FImage::new(16, 4);
+        let levels = |mipmaps| mip_chain(image(), mipmaps, MipFilter::Box, None, false).mipmaps;
+        assert_eq!(levels(Mipmaps::GeneratedAutomatic), 5);
+        assert_eq!(levels(Mipmaps::GeneratedExact(3)), 3);
+        assert_eq!(levels(Mipmaps::GeneratedExact(12)), 5);
+        assert_eq!(levels(Mipmaps::Disabled), 1);
+
+        let surface = mip_chain(
+            image(),
+            Mipmaps::GeneratedAutomatic,
+            MipFilter::Box,
+            None,
+            false,
+        );
+        assert_eq!(surface.data.len(), (64 + 16 + 4 + 2 + 1) * 4);
+    }
+
+    #[test]
+    fn constant_images_stay_constant() {
+        let color = image::Rgba([0.2, 0.4, 0.6, 0.8]);
+        for filter in FILTERS {
+            let image = Rgba32FImage::from_pixel(16, 16, color);
+            let surface = mip_chain(image, Mipmaps::GeneratedAutomatic, filter, None, false);
+            for pixel in level(&surface, 2).iter().chain(&level(&surface, 4)) {
+                for (channel, expected) in pixel.iter().zip(color.0) {
+                    assert!(
+                        (channel - expected).abs() < 1e-4,
+                        "{:?}: {:?}",
+                        filter,
+                        pixel
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn preserves_alpha_test_coverage() {
+        // Sparse opaque pixels, like foliage, which blur into mostly transparent mips.
+        let image = Rgba32FImage::from_fn(32, 32, |x, y| {
+            let hash = (x * 73 + y * 151).wrapping_mul(2_654_435_761) >> 24;
+            let alpha = if hash % 10 < 3 { 1.0 } else { 0.0 };
+            image::Rgba([1.0, 1.0, 1.0, alpha])
+        });
+        let coverage = alpha_coverage(&image, 0.5);
+
+        let plain = mip_chain(
+            image.clone(),
+            Mipmaps::GeneratedExact(3),
+            MipFilter::Box,
+            None,
+            false,
+        );
+        let kept = mip_chain(
+            image,
+            Mipmaps::GeneratedExact(3),
+            MipFilter::Box,
+            Some(0.5),
+            false,
+        );
+        let level_coverage = |surface| {
+            let pixels = level(surface, 2);
+            pixels.iter().filter(|pixel| pixel[3] > 0.5).count() as f32 / pixels.len() as f32
+        };
+        assert!((level_coverage(&kept) - coverage).abs() < 0.1);
+        assert!((level_coverage(&plain) - coverage).abs() > 0.1);
+    }
+}
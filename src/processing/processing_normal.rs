@@ -0,0 +1,201 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/processing/processing_normal.rs
+
+use image::{imageops::FilterType, DynamicImage, Rgba32FImage};
+
+use crate::structs::struct_normalgen::NormalGenConfig;
+
+/// Resizes a normal map, treating its RGB channels as unit vectors.
+///
+/// Filtering averages neighbouring vectors, which shortens them and flattens
+/// the lighting. Every resized pixel is renormalized to restore unit length.
+/// Alpha (e.g., specular) is filtered like any other channel.
+///
+/// # Arguments
+///
+/// * `image` - The normal map to resize.
+/// * `width` - Target width.
+/// * `height` - Target height.
+/// * `filter` - Resampling filter.
+///
+/// # Returns
+///
+/// * `DynamicImage` - The resized normal map with 32-bit float channels.
+pub fn resize_normal_map(
+    image: &DynamicImage,
+    width: u32,
+    height: u32,
+    filter: FilterType,
+) -> DynamicImage {
+    let resized = image::imageops::resize(&image.to_rgba32f(), width, height, filter);
+    let resized = if image.color().has_alpha() {
+        DynamicImage::ImageRgba32F(resized)
+    } else {
+        DynamicImage::ImageRgb32F(DynamicImage::ImageRgba32F(resized).to_rgb32f())
+    };
+    renormalized(resized)
+}
+
+/// Renormalizes a normal map of any color type.
+///
+/// # Arguments
+///
+/// * `image` - The normal map.
+///
+/// # Returns
+///
+/// * `DynamicImage` - The renormalized normal map with 32-bit float channels,
+///   with alpha only if `image` has alpha.
+pub fn renormalized(image: DynamicImage) -> DynamicImage {
+    let mut normals = image.to_rgba32f();
+    renormalize(&mut normals);
+
+    if image.color().has_alpha() {
+        DynamicImage::ImageRgba32F(normals)
+    } else {
+        DynamicImage::ImageRgb32F(DynamicImage::ImageRgba32F(normals).to_rgb32f())
+    }
+}
+
+/// Renormalizes the RGB channels of a normal map in place.
+///
+/// Channels are unpacked from `[0, 1]` to `[-1, 1]`, scaled to unit length and
+/// repacked. Zero-length vectors become the flat normal `(0, 0, 1)`.
+///
+/// # Arguments
+///
+/// * `image` - The normal map to renormalize.
+pub fn renormalize(image: &mut Rgba32FImage) {
+    for pixel in image.pixels_mut() {
+        let x = pixel[0] * 2.0 - 1.0;
+        let y = pixel[1] * 2.0 - 1.0;
+        let z = pixel[2] * 2.0 - 1.0;
+        let length = (x * x + y * y + z * z).sqrt();
+
+        let (x, y, z) = if length > f32::EPSILON {
+            (x / length, y / length, z / length)
+        } else {
+            (0.0, 0.0, 1.0)
+        };
+        pixel[0] = x * 0.5 + 0.5;
+        pixel[1] = y * 0.5 + 0.5;
+        pixel[2] = z * 0.5 + 0.5;
+    }
+}
+
+/// Derives a tangent-space normal map from a height map.
+///
+/// Slopes are taken with a 3x3 gradient kernel on the luminance of `image`.
+/// The result follows the DirectX convention used by Skyrim and Fallout 4,
+/// with green pointing down the image; add `invert:g` for OpenGL.
+///
+/// # Arguments
+///
+/// * `image` - The height map; brighter is higher.
+/// * `settings` - Reference to the `NormalGenConfig` with strength, kernel and edge mode.
+///
+/// # Returns
+///
+/// * `Rgba32FImage` - The normal map, with opaque alpha.
+pub fn height_to_normal(image: &DynamicImage, settings: &NormalGenConfig) -> Rgba32FImage {
+    let heights = image.to_luma32f();
+    let (width, height) = heights.dimensions();
+    let (outer, center) = settings.kernel.weights();
+
+    // Border pixels either sample the opposite edge (tiling) or repeat the edge.
+    let sample = |x: i64, y: i64| -> f32 {
+        let (x, y) = if settings.wrap {
+            (x.rem_euclid(width as i64), y.rem_euclid(height as i64))
+        } else {
+            (x.clamp(0, width as i64 - 1), y.clamp(0, height as i64 - 1))
+        };
+        heights.get_pixel(x as u32, y as u32)[0]
+    };
+
+    Rgba32FImage::from_fn(width, height, |x, y| {
+        let (x, y) = (x as i64, y as i64);
+        let dx = outer * (sample(x + 1, y - 1) - sample(x - 1, y - 1))
+            + center * (sample(x + 1, y) - sample(x - 1, y))
+            + outer * (sample(x + 1, y + 1) - sample(x - 1, y + 1));
+        let dy = outer * (sample(x - 1, y + 1) - sample(x - 1, y - 1))
+            + center * (sample(x, y + 1) - sample(x, y - 1))
+            + outer * (sample(x + 1, y + 1) - sample(x + 1, y - 1));
+
+        let (nx, ny, nz) = (-dx * settings.strength, -dy * settings.strength, 1.0);
+        let length = (nx * nx + ny * ny + nz * nz).sqrt();
+        image::Rgba([
+            nx / length * 0.5 + 0.5,
+            ny / length * 0.5 + 0.5,
+            nz / length * 0.5 + 0.5,
+            1.0,
+        ])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::struct_normalgen::GradientKernel;
+
+    fn ramp(width: u32, height: u32) -> DynamicImage {
+        DynamicImage::ImageLuma8(image::GrayImage::from_fn(width, height, |x, _| {
+            image::Luma([(x * 16) as u8])
+        }))
+    }
+
+    #[test]
+    fn renormalizes_to_unit_length() {
+        let mut image = Rgba32FImage::from_pixel(1, 1, image::Rgba([0.75, 0.5, 0.75, 1.0]));
+        let mut flat = Rgba32FImage::from_pixel(1, 1, image::Rgba([0.5, 0.5, 0.5, 0.3]));
+        renormalize(&mut image);
+        renormalize(&mut flat);
+
+        let pixel = image.get_pixel(0, 0);
+        let expected = 0.5 / 2f32.sqrt() + 0.5;
+        assert!((pixel[0] - expected).abs() < 1e-5 && (pixel[2] - expected).abs() < 1e-5);
+        assert_eq!(flat.get_pixel(0, 0).0, [0.5, 0.5, 1.0, 0.3]);
+    }
+
+    #[test]
+    fn flat_height_maps_give_flat_normals() {
+        let image = DynamicImage::ImageLuma8(image::GrayImage::from_pixel(4, 4, image::Luma([90])));
+        let normals = height_to_normal(&image, &NormalGenConfig::default());
+        assert!(normals
+            .pixels()
+            .all(|pixel| pixel.0 == [0.5, 0.5, 1.0, 1.0]));
+    }
+
+    #[test]
+    fn slopes_tilt_away_from_higher_ground() {
+        for kernel in [GradientKernel::Sobel, GradientKernel::Scharr] {
+            let settings = NormalGenConfig {
+                kernel,
+                ..NormalGenConfig::default()
+            };
+            let normals = height_to_normal(&ramp(8, 8), &settings);
+            let pixel = normals.get_pixel(4, 4);
+            assert!(pixel[0] < 0.5, "{:?}", kernel);
+            assert!((pixel[1] - 0.5).abs() < 1e-6, "{:?}", kernel);
+        }
+
+        // Both kernels measure the same slope.
+        let (outer, center) = GradientKernel::Scharr.weights();
+        assert!((2.0 * outer + center - 0.5).abs() < 1e-6);
+        let (outer, center) = GradientKernel::Sobel.weights();
+        assert!((2.0 * outer + center - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn wrapping_samples_across_the_edge() {
+        let clamped = height_to_normal(&ramp(8, 8), &NormalGenConfig::default());
+        let settings = NormalGenConfig {
+            wrap: true,
+            ..NormalGenConfig::default()
+        };
+        let wrapped = height_to_normal(&ramp(8, 8), &settings);
+
+        // At the right edge the wrapped ramp drops back to 0, tilting the other way.
+        assert!(clamped.get_pixel(7, 4)[0] < 0.5);
+        assert!(wrapped.get_pixel(7, 4)[0] > 0.5);
+        assert_eq!(clamped.get_pixel(4, 4), wrapped.get_pixel(4, 4));
+    }
+}
@@ -0,0 +1,351 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/processing/processing_pack.rs
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use colored::Color;
+use image::{imageops::FilterType, DynamicImage, GenericImageView, Rgba32FImage};
+
+use crate::{
+    io::{io_in::load_image, io_source::list_input_directory},
+    logging::{smnprintln, LogQueue, LogType},
+    structs::{
+        struct_colorspace::ColorSpace,
+        struct_config::Config,
+        struct_filetype::FileType,
+        struct_imagedata::ImageData,
+        struct_packconfig::{PackConfig, PackSource},
+        struct_runreport::ProcessedOutputs,
+    },
+};
+
+use super::{
+    processing_img::resize_and_convert,
+    processing_plan::{pack_channels, resolve_role},
+};
+
+/// Source maps found for one packed texture.
+#[derive(Debug, Clone)]
+pub struct PackGroup {
+    /// The pack definition.
+    pub pack: PackConfig,
+
+    /// Path of the packed texture as if it were an input (e.g., `textures/armor_rmaos.png`).
+    /// Outputs, rules and the cache manifest key are derived from it.
+    pub path: PathBuf,
+
+    /// Paths of the source maps, keyed by suffix.
+    pub sources: BTreeMap<String, PathBuf>,
+}
+
+/// Groups input files into packed textures.
+///
+/// Every input whose name ends with a source suffix of a pack belongs to the
+/// pack named after the rest of its name. Missing sources are looked up next
+/// to the found ones, so that a single changed source is enough to rebuild
+/// its pack in watch mode. Incomplete groups are reported and skipped.
+///
+/// # Arguments
+///
+/// * `paths` - The input files.
+/// * `packs` - The configured packs.
+///
+/// # Returns
+///
+/// * `Vec<PackGroup>` - The complete groups.
+/// * `HashSet<PathBuf>` - Inputs used as pack sources, which are not converted on
+///   their own unless the pack sets `keep_sources`.
+pub fn find_pack_groups(
+    paths: &[PathBuf],
+    packs: &[PackConfig],
+) -> (Vec<PackGroup>, HashSet<PathBuf>) {
+    let mut groups: BTreeMap<(usize, PathBuf), PackGroup> = BTreeMap::new();
+    let mut consumed = HashSet::new();
+
+    for path in paths {
+        for (index, pack) in packs.iter().enumerate() {
+            let Some(packed_path) = packed_path(path, pack) else {
+                continue;
+            };
+            if !pack.keep_sources {
+                consumed.insert(path.clone());
+            }
+            groups
+                .entry((index, packed_path.clone()))
+                .or_insert_with(|| PackGroup {
+                    pack: pack.clone(),
+                    path: packed_path,
+                    sources: BTreeMap::new(),
+                });
+        }
+    }
+
+    // Resolve every source of every group from the files next to it.
+    let mut directories: HashMap<PathBuf, HashMap<String, PathBuf>> = HashMap::new();
+    let mut complete = Vec::new();
+    for (_, mut group) in groups {
+        let directory = group.path.parent().unwrap_or(Path::new("")).to_path_buf();
+        let files = directories
+            .entry(directory.clone())
+            .or_insert_with(|| list_images(&directory));
+        let base = base_name(&group.path, &group.pack);
+
+        let mut missing = Vec::new();
+        for suffix in group.pack.source_suffixes() {
+            match files.get(&format!("{}{}", base, suffix).to_lowercase()) {
+                Some(source) => {
+                    group.sources.insert(suffix.to_string(), source.clone());
+                }
+                None => missing.push(format!("{}{}", base, suffix)),
+            }
+        }
+
+        if missing.is_empty() {
+            complete.push(group);
+        } else {
+            smnprintln(
+                format!(
+                    "Skipping pack '{}': missing {}",
+                    group.path.display(),
+                    missing.join(", ")
+                ),
+                Color::Yellow,
+            );
+        }
+    }
+
+    (complete, consumed)
+}
+
+/// Returns the packed texture path an input contributes to, if it is a source of `pack`.
+///
+/// # Arguments
+///
+/// * `path` - Path to the input file.
+/// * `pack` - The pack definition.
+pub fn packed_path(path: &Path, pack: &PackConfig) -> Option<PathBuf> {
+    let stem = path.file_stem()?.to_string_lossy();
+    let base = pack.source_suffixes().into_iter().find_map(|suffix| {
+        let split = stem
+            .len()
+            .checked_sub(suffix.len())
+            .filter(|&split| split > 0)?;
+        (stem.is_char_boundary(split) && stem[split..].eq_ignore_ascii_case(suffix))
+            .then(|| stem[..split].to_string())
+    })?;
+    Some(path.with_file_name(format!("{}{}.png", base, pack.output)))
+}
+
+/// Returns whether every source map of a packed texture still exists.
+///
+/// Used to tell the cache entries of packs that are no longer produced from
+/// those of packs that were only left out of a run.
+///
+/// # Arguments
+///
+/// * `packed_path` - Path of the packed texture as if it were an input.
+/// * `packs` - The configured packs.
+pub fn pack_sources_exist(packed_path: &Path, packs: &[PackConfig]) -> bool {
+    let stem = packed_path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let directory = packed_path.parent().unwrap_or(Path::new(""));
+    packs.iter().any(|pack| {
+        let split = stem.len().saturating_sub(pack.output.len());
+        if split == 0
+            || !stem.is_char_boundary(split)
+            || !stem[split..].eq_ignore_ascii_case(&pack.output)
+        {
+            return false;
+        }
+        let files = list_images(directory);
+        pack.source_suffixes().into_iter().all(|suffix| {
+            files.contains_key(&format!("{}{}", &stem[..split], suffix).to_lowercase())
+        })
+    })
+}
+
+/// Packs the source maps of a group into one image and converts it like any other input.
+///
+/// Sources are resized to the size of the largest one. Channels without a
+/// source are 0 and alpha defaults to opaque.
+///
+/// # Arguments
+///
+/// * `group` - The pack group to process.
+/// * `output_path` - Mirrored output path of the packed texture.
+/// * `config` - The configuration resolved for the packed texture.
+///
+/// # Returns
+///
+/// * `Some(ProcessedOutputs)` with the files written, if packing and conversion succeeded.
+/// * `None` if any step failed.
+pub async fn process_pack(
+    group: PackGroup,
+    output_path: PathBuf,
+    config: &Config,
+) -> Option<ProcessedOutputs> {
+    let log_queue = LogQueue::new();
+    let mut image_data = ImageData::from((group.path.clone(), output_path));
+
+    log_queue.enqueue(
+        LogType::Println,
+        "--------------------------".to_string(),
+        Color::White,
+    );
+    log_queue.enqueue(
+        LogType::Println,
+        format!("[Processing Pack: {}]", image_data.file_name),
+        Color::White,
+    );
+    for (name, source) in ["Red", "Green", "Blue", "Alpha"]
+        .iter()
+        .zip(group.pack.channels())
+    {
+        let Some(source) = source else {
+            continue;
+        };
+        log_queue.enqueue(LogType::Print, format!("{}: ", name), Color::White);
+        let description = match source {
+            PackSource::Map { suffix, channel } => {
+                format!("{} ({})", group.sources[suffix].display(), channel)
+            }
+            PackSource::Constant(value) => value.to_string(),
+        };
+        log_queue.enqueue(LogType::Println, description, Color::BrightCyan);
+    }
+    log_queue.enqueue(LogType::Print, "Output Path: ".to_string(), Color::White);
+    log_queue.enqueue(
+        LogType::Println,
+        format!("{}", image_data.file_path_out.display()),
+        Color::BrightCyan,
+    );
+
+    let pack = group.pack.clone();
+    let sources = group.sources.clone();
+    let packed = match tokio::task::spawn_blocking(move || pack_sources(&pack, &sources)).await {
+        Ok(Ok(packed)) => packed,
+        Ok(Err(e)) => {
+            log_queue.enqueue(LogType::Println, e, Color::Red);
+            log_queue.process_queue();
+            return None;
+        }
+        Err(e) => {
+            log_queue.enqueue(
+                LogType::Println,
+                format!("Failed to spawn blocking task: {}", e),
+                Color::Red,
+            );
+            log_queue.process_queue();
+            return None;
+        }
+    };
+
+    image_data.role = resolve_role(&image_data.file_name, config);
+    image_data.channels = pack_channels(&group, config);
+    image_data.file_type = FileType::PNG;
+    image_data.color_space = ColorSpace::resolve(image_data.role, config.color_space, None);
+
+    resize_and_convert(
+        image_data,
+        DynamicImage::ImageRgba32F(packed),
+        log_queue,
+        config,
+    )
+    .await
+}
+
+/// Loads the source maps of a pack and writes them into the channels of one image.
+///
+/// # Arguments
+///
+/// * `pack` - The pack definition.
+/// * `sources` - Paths of the source maps, keyed by suffix.
+///
+/// # Returns
+///
+/// * `Ok(Rgba32FImage)` with the packed channels.
+/// * `Err(String)` if a source cannot be loaded.
+pub fn pack_sources(
+    pack: &PackConfig,
+    sources: &BTreeMap<String, PathBuf>,
+) -> Result<Rgba32FImage, String> {
+    let mut images = HashMap::new();
+    for (suffix, path) in sources {
+        let file_type = FileType::from(
+            path.extension()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .as_ref(),
+        );
+        let loaded = load_image(path, file_type)?;
+        images.insert(suffix.as_str(), loaded.image);
+    }
+
+    // Use the size of the largest source for the packed texture.
+    let (width, height) = images
+        .values()
+        .map(|image| image.dimensions())
+        .max_by_key(|(width, height)| *width as u64 * *height as u64)
+        .ok_or_else(|| format!("Pack '{}' has no source maps", pack.output))?;
+    let images: HashMap<&str, Rgba32FImage> = images
+        .into_iter()
+        .map(|(suffix, image)| {
+            let image = if image.dimensions() == (width, height) {
+                image.to_rgba32f()
+            } else {
+                image::imageops::resize(&image.to_rgba32f(), width, height, FilterType::Triangle)
+            };
+            (suffix, image)
+        })
+        .collect();
+
+    let mut packed = Rgba32FImage::from_pixel(width, height, image::Rgba([0.0, 0.0, 0.0, 1.0]));
+    for (target, source) in pack.channels().into_iter().enumerate() {
+        match source {
+            Some(PackSource::Map { suffix, channel }) => {
+                let image = &images[suffix.as_str()];
+                for (pixel, source_pixel) in packed.pixels_mut().zip(image.pixels()) {
+                    pixel[target] = source_pixel[channel.index()];
+                }
+            }
+            Some(PackSource::Constant(value)) => {
+                for pixel in packed.pixels_mut() {
+                    pixel[target] = *value;
+                }
+            }
+            None => {}
+        }
+    }
+
+    Ok(packed)
+}
+
+/// Returns the name of a packed texture without the pack's output suffix.
+fn base_name(packed_path: &Path, pack: &PackConfig) -> String {
+    let stem = packed_path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy();
+    stem[..stem.len() - pack.output.len()].to_string()
+}
+
+/// Lists the supported images in a directory or archive directory, keyed by lowercase file stem.
+fn list_images(directory: &Path) -> HashMap<String, PathBuf> {
+    list_input_directory(directory)
+        .into_iter()
+        .filter(|path| {
+            path.extension().is_some_and(|ext| {
+                FileType::supported_extensions()
+                    .contains(&ext.to_string_lossy().to_lowercase().as_str())
+            })
+        })
+        .filter_map(|path| {
+            let stem = path.file_stem()?.to_string_lossy().to_lowercase();
+            Some((stem, path))
+        })
+        .collect()
+}
@@ -0,0 +1,426 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/processing/processing_package.rs
+
+use std::path::{Path, PathBuf};
+
+use colored::Color;
+
+use crate::{
+    io::io_backend::OutputBackend,
+    logging::{smnprint, smnprintln},
+    structs::{
+        struct_config::Config, struct_packageconfig::PackageConfig, struct_sizespec::SizeSpec,
+    },
+};
+
+/// Directory of the installer files inside the package.
+pub const FOMOD_DIRECTORY: &str = "fomod";
+
+/// Name of the installer page and of its option group.
+const STEP_NAME: &str = "Texture Resolution";
+
+/// One installable resolution of the package.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackageOption {
+    /// The target size of the option.
+    pub spec: SizeSpec,
+
+    /// The per-size folder holding the option's files, relative to the package root.
+    pub folder: String,
+}
+
+/// Generates the FOMOD installer of a package built with the per-size layout.
+///
+/// Every target size, global or from a rule, whose folder exists in `path_out`
+/// becomes one option of a single select-exactly-one group, installing the
+/// folder's contents into the game's data directory. The metadata comes from the `[package]` table.
+/// The installer is written through the output backend, so archives get it too.
+///
+/// # Arguments
+///
+/// * `config` - The configuration of the run, with `package` set.
+///
+/// # Returns
+///
+/// * `Ok(Vec<PathBuf>)` with the installer files written.
+/// * `Err(String)` with an error message if there is nothing to package or writing fails.
+pub fn write_fomod(config: &Config) -> Result<Vec<PathBuf>, String> {
+    let package = config.package.clone().unwrap_or_default();
+    let path_out = Path::new(&config.path_out);
+
+    let options = package_options(&config.all_target_sizes(), path_out, &config.output);
+    if options.is_empty() {
+        return Err(format!(
+            "No per-size folders to package in '{}'",
+            config.path_out
+        ));
+    }
+
+    // Preselect the configured size, falling back to the first option.
+    let default_folder = match package.default_size {
+        Some(spec) if options.iter().any(|option| option.spec == spec) => spec.folder_name(),
+        Some(spec) => {
+            smnprintln(
+                format!(
+                    "Default size {} is not a package option, preselecting {}.",
+                    spec, options[0].folder
+                ),
+                Color::Yellow,
+            );
+            options[0].folder.clone()
+        }
+        None => options[0].folder.clone(),
+    };
+
+    let name = if package.name.is_empty() {
+        Path::new(&config.path_in)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Textures".to_string())
+    } else {
+        package.name.clone()
+    };
+
+    let fomod_path = path_out.join(FOMOD_DIRECTORY);
+    let files = [
+        (fomod_path.join("info.xml"), info_xml(&package, &name)),
+        (
+            fomod_path.join("ModuleConfig.xml"),
+            module_config_xml(&name, &options, &default_folder),
+        ),
+    ];
+    for (path, content) in &files {
+        config.output.write(path, content.as_bytes(), true)?;
+    }
+
+    smnprint("Package Options: ", Color::White);
+    smnprintln(
+        options
+            .iter()
+            .map(|option| option.folder.as_str())
+            .collect::<Vec<_>>()
+            .join(", "),
+        Color::BrightCyan,
+    );
+
+    Ok(files.into_iter().map(|(path, _)| path).collect())
+}
+
+/// Lists the package options in the order of the target sizes.
+///
+/// Sizes without a folder in `path_out`, e.g. because every input was
+/// smaller and the upscale policy skipped them, are left out with a warning.
+///
+/// # Arguments
+///
+/// * `target_sizes` - The target sizes of the run, including those added by rules.
+/// * `path_out` - The package root holding the per-size folders.
+/// * `output` - The backend the folders were written to.
+pub fn package_options(
+    target_sizes: &[SizeSpec],
+    path_out: &Path,
+    output: &OutputBackend,
+) -> Vec<PackageOption> {
+    let mut options: Vec<PackageOption> = Vec::new();
+    for &spec in target_sizes {
+        let folder = spec.folder_name();
+        if options.iter().any(|option| option.folder == folder) {
+            continue;
+        }
+        if !output.contains_directory(&path_out.join(&folder)) {
+            smnprintln(
+                format!(
+                    "No outputs for target size {}, leaving it out of the package.",
+                    spec
+                ),
+                Color::Yellow,
+            );
+            continue;
+        }
+        options.push(PackageOption { spec, folder });
+    }
+    options
+}
+
+/// Builds `fomod/info.xml` with the mod metadata.
+fn info_xml(package: &PackageConfig, name: &str) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<fomod>\n");
+    xml.push_str(&format!("  <Name>{}</Name>\n", escape_xml(name)));
+    xml.push_str(&format!(
+        "  <Author>{}</Author>\n",
+        escape_xml(&package.author)
+    ));
+    xml.push_str(&format!(
+        "  <Version>{}</Version>\n",
+        escape_xml(&package.version)
+    ));
+    xml.push_str(&format!(
+        "  <Description>{}</Description>\n",
+        escape_xml(&package.description)
+    ));
+    if let Some(website) = &package.website {
+        xml.push_str(&format!("  <Website>{}</Website>\n", escape_xml(website)));
+    }
+    xml.push_str("</fomod>\n");
+    xml
+}
+
+/// Builds `fomod/ModuleConfig.xml` with one radio-button option per resolution.
+fn module_config_xml(name: &str, options: &[PackageOption], default_folder: &str) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <config xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:noNamespaceSchemaLocation=\"http://qconsulting.ca/fo3/ModConfig5.0.xsd\">\n",
+    );
+    xml.push_str(&format!(
+        "  <moduleName>{}</moduleName>\n",
+        escape_xml(name)
+    ));
+    xml.push_str("  <installSteps order=\"Explicit\">\n");
+    xml.push_str(&format!("    <installStep name=\"{}\">\n", STEP_NAME));
+    xml.push_str("      <optionalFileGroups order=\"Explicit\">\n");
+    xml.push_str(&format!(
+        "        <group name=\"{}\" type=\"SelectExactlyOne\">\n",
+        STEP_NAME
+    ));
+    xml.push_str("          <plugins order=\"Explicit\">\n");
+    for option in options {
+        let folder = escape_xml(&option.folder);
+        let type_name = if option.folder == default_folder {
+            "Recommended"
+        } else {
+            "Optional"
+        };
+        xml.push_str(&format!("            <plugin name=\"{}\">\n", folder));
+        xml.push_str(&format!(
+            "              <description>{}</description>\n",
+            escape_xml(&describe(option.spec))
+        ));
+        xml.push_str("              <files>\n");
+        xml.push_str(&format!(
+            "                <folder source=\"{}\" destination=\"\" priority=\"0\" />\n",
+            folder
+        ));
+        xml.push_str("              </files>\n");
+        xml.push_str("              <typeDescriptor>\n");
+        xml.push_str(&format!(
+            "                <type name=\"{}\" />\n",
+            type_name
+        ));
+        xml.push_str("              </typeDescriptor>\n");
+        xml.push_str("            </plugin>\n");
+    }
+    xml.push_str("          </plugins>\n");
+    xml.push_str("        </group>\n");
+    xml.push_str("      </optionalFileGroups>\n");
+    xml.push_str("    </installStep>\n");
+    xml.push_str("  </installSteps>\n");
+    xml.push_str("</config>\n");
+    xml
+}
+
+/// Describes the textures of an option for the installer.
+fn describe(spec: SizeSpec) -> String {
+    match spec {
+        SizeSpec::Width(width) => format!("Textures {} pixels wide.", width),
+        SizeSpec::Height(height) => format!("Textures {} pixels high.", height),
+        SizeSpec::Max(max) => format!("Textures up to {} pixels on the longer side.", max),
+        SizeSpec::Scale(percent) => format!("Textures at {}% of the original size.", percent),
+    }
+}
+
+/// Escapes the characters that are not allowed in XML text and attribute values.
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::struct_pathrule::PathRule;
+
+    /// Creates a package root holding the given per-size folders.
+    fn package_root(folders: &[&str]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for folder in folders {
+            std::fs::create_dir_all(dir.path().join(folder)).unwrap();
+        }
+        dir
+    }
+
+    fn package_config(dir: &Path, target_sizes: Vec<SizeSpec>, package: PackageConfig) -> Config {
+        Config {
+            path_in: "Armor Retexture".to_string(),
+            path_out: dir.display().to_string(),
+            target_sizes,
+            package: Some(package),
+            ..Config::default()
+        }
+    }
+
+    /// Returns the type name of the option installing `folder`.
+    fn option_type(module_config: &str, folder: &str) -> String {
+        let plugin = &module_config[module_config
+            .find(&format!("<plugin name=\"{}\">", folder))
+            .unwrap()..];
+        let start = plugin.find("<type name=\"").unwrap() + "<type name=\"".len();
+        plugin[start..start + plugin[start..].find('"').unwrap()].to_string()
+    }
+
+    fn read_module_config(dir: &Path) -> String {
+        std::fs::read_to_string(dir.join(FOMOD_DIRECTORY).join("ModuleConfig.xml")).unwrap()
+    }
+
+    #[test]
+    fn options_follow_target_sizes_and_skip_missing_folders() {
+        let dir = package_root(&["2K", "1K"]);
+        let target_sizes = [
+            SizeSpec::Width(2048),
+            SizeSpec::Width(512),
+            SizeSpec::Width(2048),
+            SizeSpec::Width(1024),
+        ];
+
+        let options = package_options(&target_sizes, dir.path(), &OutputBackend::Directory);
+        let folders: Vec<&str> = options
+            .iter()
+            .map(|option| option.folder.as_str())
+            .collect();
+        assert_eq!(folders, ["2K", "1K"]);
+        assert_eq!(options[1].spec, SizeSpec::Width(1024));
+    }
+
+    #[test]
+    fn options_include_target_sizes_of_rules() {
+        let dir = package_root(&["2K", "512"]);
+        let rule: PathRule =
+            toml::from_str("pattern = \"**/*_n.png\"\ntarget_sizes = [512]").unwrap();
+        let config = Config {
+            rules: vec![rule],
+            ..package_config(
+                dir.path(),
+                vec![SizeSpec::Width(2048)],
+                PackageConfig::default(),
+            )
+        };
+
+        write_fomod(&config).unwrap();
+        let module_config = read_module_config(dir.path());
+        assert!(module_config.contains("<folder source=\"2K\""));
+        assert!(module_config.contains("<folder source=\"512\""));
+    }
+
+    #[test]
+    fn default_size_is_preselected() {
+        let dir = package_root(&["2K", "1K"]);
+        let package = PackageConfig {
+            default_size: Some(SizeSpec::Width(1024)),
+            ..PackageConfig::default()
+        };
+        let config = package_config(
+            dir.path(),
+            vec![SizeSpec::Width(2048), SizeSpec::Width(1024)],
+            package,
+        );
+
+        let files = write_fomod(&config).unwrap();
+        assert_eq!(files.len(), 2);
+        let module_config = read_module_config(dir.path());
+        assert_eq!(option_type(&module_config, "1K"), "Recommended");
+        assert_eq!(option_type(&module_config, "2K"), "Optional");
+    }
+
+    #[test]
+    fn unknown_default_size_falls_back_to_the_first_option() {
+        let dir = package_root(&["2K", "1K"]);
+        let package = PackageConfig {
+            default_size: Some(SizeSpec::Width(4096)),
+            ..PackageConfig::default()
+        };
+        let config = package_config(
+            dir.path(),
+            vec![SizeSpec::Width(2048), SizeSpec::Width(1024)],
+            package,
+        );
+
+        write_fomod(&config).unwrap();
+        let module_config = read_module_config(dir.path());
+        assert_eq!(option_type(&module_config, "2K"), "Recommended");
+        assert_eq!(option_type(&module_config, "1K"), "Optional");
+    }
+
+    #[test]
+    fn nothing_to_package_is_an_error() {
+        let dir = package_root(&[]);
+        let config = package_config(
+            dir.path(),
+            vec![SizeSpec::Width(2048)],
+            PackageConfig::default(),
+        );
+
+        assert!(write_fomod(&config).is_err());
+        assert!(!dir.path().join(FOMOD_DIRECTORY).exists());
+    }
+
+    #[test]
+    fn info_xml_names_the_mod_after_the_input_directory() {
+        let dir = package_root(&["2K"]);
+        let package = PackageConfig {
+            author: "Smith & Sons".to_string(),
+            website: Some("https://example.com/?a=1&b=2".to_string()),
+            ..PackageConfig::default()
+        };
+        let config = package_config(dir.path(), vec![SizeSpec::Width(2048)], package);
+
+        write_fomod(&config).unwrap();
+        let info =
+            std::fs::read_to_string(dir.path().join(FOMOD_DIRECTORY).join("info.xml")).unwrap();
+        assert!(info.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<fomod>\n"));
+        assert!(info.contains("  <Name>Armor Retexture</Name>\n"));
+        assert!(info.contains("  <Author>Smith &amp; Sons</Author>\n"));
+        assert!(info.contains("  <Version>1.0</Version>\n"));
+        assert!(info.contains("  <Website>https://example.com/?a=1&amp;b=2</Website>\n"));
+        assert!(info.ends_with("</fomod>\n"));
+    }
+
+    #[test]
+    fn info_xml_leaves_out_an_unset_website() {
+        let info = info_xml(&PackageConfig::default(), "Textures");
+        assert!(!info.contains("<Website>"));
+    }
+
+    #[test]
+    fn module_config_lists_one_plugin_per_option() {
+        let options = [
+            PackageOption {
+                spec: SizeSpec::Width(2048),
+                folder: "2K".to_string(),
+            },
+            PackageOption {
+                spec: SizeSpec::Max(1024),
+                folder: "max1K".to_string(),
+            },
+        ];
+
+        let xml = module_config_xml("A <B>", &options, "2K");
+        assert!(xml.contains("  <moduleName>A &lt;B&gt;</moduleName>\n"));
+        assert!(xml.contains("<group name=\"Texture Resolution\" type=\"SelectExactlyOne\">"));
+        assert_eq!(xml.matches("<plugin name=").count(), 2);
+        assert!(xml.contains("<folder source=\"max1K\" destination=\"\" priority=\"0\" />"));
+        assert!(xml.contains("Textures up to 1024 pixels on the longer side."));
+        assert!(xml.find("\"2K\"").unwrap() < xml.find("\"max1K\"").unwrap());
+        assert!(xml.ends_with("</config>\n"));
+    }
+
+    #[test]
+    fn escape_xml_replaces_reserved_characters() {
+        assert_eq!(
+            escape_xml("<a href=\"x\">Tom's & Jerry's</a>"),
+            "&lt;a href=&quot;x&quot;&gt;Tom&apos;s &amp; Jerry&apos;s&lt;/a&gt;"
+        );
+        assert_eq!(escape_xml("2K"), "2K");
+    }
+}
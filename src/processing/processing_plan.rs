@@ -0,0 +1,333 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/processing/processing_plan.rs
+
+use image_dds::ImageFormat;
+use std::path::PathBuf;
+
+use crate::{
+    io::{
+        io_naming::{output_path, OutputName},
+        io_source::InputSource,
+    },
+    structs::{
+        struct_colorspace::ColorSpace, struct_config::Config, struct_ddsconfig::DdsConvertConfig,
+        struct_filetype::FileType, struct_imagedata::ImageData, struct_normalgen::NormalGenConfig,
+        struct_sizespec::SizeSpec, struct_texturerole::TextureRole,
+    },
+};
+
+use super::{
+    processing_channels::output_channels,
+    processing_dds::{is_block_compressed, requested_format, select_format},
+    processing_pack::PackGroup,
+    processing_size::{target_dimensions, TargetSize},
+};
+
+/// The DDS outputs `convert` writes for an image.
+///
+/// Shared by `convert`, which writes the outputs, and `verify`, which
+/// checks them, so that both agree on formats, sizes and names.
+#[derive(Debug, Clone)]
+pub struct OutputPlan {
+    /// The format picked from the role and content, or configured.
+    pub requested: ImageFormat,
+
+    /// The format the outputs are encoded in, after the game profile is applied.
+    pub selected: ImageFormat,
+
+    /// The format a DDS reader reports for the outputs.
+    pub header_format: ImageFormat,
+
+    /// Every target size with its output dimensions, `None` if it is skipped.
+    pub targets: Vec<(SizeSpec, Option<TargetSize>)>,
+}
+
+impl OutputPlan {
+    /// Resolves the path of the output for one target size.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The configuration the plan was made with.
+    /// * `image_data` - The image the plan was made for.
+    /// * `spec` - The target size.
+    /// * `size` - The output dimensions of the target size.
+    /// * `extension` - Extension of the output without the leading dot (e.g., `dds`).
+    ///
+    /// # Returns
+    ///
+    /// * `PathBuf` - The full path of the output.
+    pub fn output_path(
+        &self,
+        config: &Config,
+        image_data: &ImageData,
+        spec: SizeSpec,
+        size: &TargetSize,
+        extension: &str,
+    ) -> PathBuf {
+        let output_name = OutputName {
+            stem: &image_data.file_name,
+            spec,
+            width: size.canvas_width,
+            height: size.canvas_height,
+            role: image_data.role,
+            format: self.selected.to_string(),
+        };
+        output_path(config, image_data, &output_name, extension)
+    }
+}
+
+/// Plans the DDS outputs of an image for every target size.
+///
+/// # Arguments
+///
+/// * `image_data` - The `ImageData` with the resolved role, channel count and analysis.
+/// * `width` - Width of the image.
+/// * `height` - Height of the image.
+/// * `config` - The configuration resolved for the image.
+///
+/// # Returns
+///
+/// * `OutputPlan` - The formats and the output dimensions of every target size.
+pub fn plan_outputs(
+    image_data: &ImageData,
+    width: u32,
+    height: u32,
+    config: &Config,
+) -> OutputPlan {
+    let dds_config = DdsConvertConfig::from(config);
+    let requested = requested_format(image_data, &dds_config);
+    let selected = select_format(image_data, &dds_config);
+
+    // Block compressed formats need dimensions that are multiples of the block size.
+    let multiple_of_4 = config.multiple_of_4 && is_block_compressed(selected);
+    let targets = config
+        .target_sizes
+        .iter()
+        .map(|&spec| {
+            let size = target_dimensions(
+                width,
+                height,
+                spec,
+                config.upscale,
+                config.pow2,
+                multiple_of_4,
+            );
+            (spec, size)
+        })
+        .collect();
+
+    OutputPlan {
+        requested,
+        selected,
+        header_format: dds_config.game.header_format(selected),
+        targets,
+    }
+}
+
+/// Returns the texture role of an image: the configured role, or the one
+/// classified from the file name suffix.
+///
+/// # Arguments
+///
+/// * `file_name` - File name of the image without extension.
+/// * `config` - The configuration resolved for the image.
+pub fn resolve_role(file_name: &str, config: &Config) -> TextureRole {
+    config
+        .role
+        .unwrap_or_else(|| TextureRole::classify(file_name, &config.role_suffixes))
+}
+
+/// Returns the configuration of a packed texture, with the role and format of
+/// the pack taking precedence over the rules.
+///
+/// # Arguments
+///
+/// * `group` - The pack group.
+/// * `config` - The configuration resolved for the packed texture's path.
+pub fn pack_config(group: &PackGroup, config: &Config) -> Config {
+    let mut config = config.clone();
+    if group.pack.role.is_some() {
+        config.role = group.pack.role;
+    }
+    if group.pack.format.is_some() {
+        config.format = group.pack.format;
+    }
+    config
+}
+
+/// Returns the number of channels written for a packed texture.
+///
+/// # Arguments
+///
+/// * `group` - The pack group.
+/// * `config` - The configuration of the packed texture.
+pub fn pack_channels(group: &PackGroup, config: &Config) -> u8 {
+    let channels = if group.pack.has_alpha() { 4 } else { 3 };
+    output_channels(&config.channel_ops, channels)
+}
+
+/// Plans the normal map generated from a height map.
+///
+/// The normal map is named after the height map with its suffix replaced by
+/// the generator's `output` suffix (e.g., `armor_h` becomes `armor_n`). The
+/// role, format and channel operations of the height map do not apply to it.
+///
+/// # Arguments
+///
+/// * `height_data` - The `ImageData` of the height map.
+/// * `settings` - Reference to the `NormalGenConfig` with the generator settings.
+/// * `config` - The configuration resolved for the height map.
+///
+/// # Returns
+///
+/// * `Ok((ImageData, Config))` with the normal map and its configuration.
+/// * `Err(PathBuf)` with the path of an image of that name next to the height
+///   map, which is kept, so nothing is generated.
+pub fn plan_generated_normal(
+    height_data: &ImageData,
+    settings: &NormalGenConfig,
+    config: &Config,
+) -> Result<(ImageData, Config), PathBuf> {
+    let mut image_data = height_data.clone();
+    image_data.file_name = generated_normal_name(&height_data.file_name, &settings.output);
+
+    // Prefer a normal map supplied by the artist.
+    let existing = FileType::supported_extensions().iter().find_map(|ext| {
+        let path = image_data
+            .file_path_in
+            .join(format!("{}.{}", image_data.file_name, ext));
+        InputSource::of(&path).exists().then_some(path)
+    });
+    if let Some(existing) = existing {
+        return Err(existing);
+    }
+
+    image_data.role = TextureRole::Normal;
+    image_data.channels = 3;
+    image_data.file_type = FileType::PNG;
+    image_data.color_space = ColorSpace::Linear;
+    image_data.analysis = None;
+
+    let mut config = config.clone();
+    config.role = None;
+    config.format = settings.format;
+    config.channel_ops.clear();
+    Ok((image_data, config))
+}
+
+/// Returns the name of the normal map generated from a height map.
+///
+/// # Arguments
+///
+/// * `height_name` - File name of the height map without extension (e.g., `armor_h`).
+/// * `output` - Suffix of the normal map (e.g., `_n`).
+pub fn generated_normal_name(height_name: &str, output: &str) -> String {
+    let base = height_name
+        .rsplit_once('_')
+        .map(|(base, _)| base)
+        .unwrap_or(height_name);
+    format!("{}{}", base, output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::{struct_ddsconfig::DdsFormat, struct_packconfig::PackConfig};
+    use std::path::Path;
+
+    fn image(path_in: &Path, name: &str) -> ImageData {
+        ImageData::from((path_in.join(name), Path::new("out").join(name)))
+    }
+
+    #[test]
+    fn plans_every_target_size() {
+        let config = Config {
+            path_out: "out".to_string(),
+            target_sizes: vec![SizeSpec::Width(512), SizeSpec::Width(4096)],
+            ..Config::default()
+        };
+        let mut image_data = image(Path::new("in"), "wood_d.png");
+        image_data.channels = 3;
+        let plan = plan_outputs(&image_data, 1024, 512, &config);
+
+        assert_eq!(plan.selected, ImageFormat::BC1RgbaUnormSrgb);
+        assert_eq!(plan.targets.len(), 2);
+        let (spec, size) = plan.targets[0];
+        let size = size.unwrap();
+        assert_eq!((size.canvas_width, size.canvas_height), (512, 256));
+        assert_eq!(
+            plan.output_path(&config, &image_data, spec, &size, "dds"),
+            Path::new("out").join("wood_d_512.dds")
+        );
+        assert!(plan.targets[1].1.is_none());
+    }
+
+    #[test]
+    fn legacy_headers_read_back_linear() {
+        let config = Config {
+            game: "skyrim-le".parse().unwrap(),
+            ..Config::default()
+        };
+        let mut image_data = image(Path::new("in"), "wood_d.png");
+        image_data.channels = 4;
+        let plan = plan_outputs(&image_data, 64, 64, &config);
+        assert_eq!(plan.requested, ImageFormat::BC7RgbaUnormSrgb);
+        assert_eq!(plan.selected, ImageFormat::BC3RgbaUnormSrgb);
+        assert_eq!(plan.header_format, ImageFormat::BC3RgbaUnorm);
+    }
+
+    #[test]
+    fn pack_settings_override_rules() {
+        let group = PackGroup {
+            pack: PackConfig {
+                output: "_rmaos".to_string(),
+                r: None,
+                g: None,
+                b: None,
+                a: None,
+                role: Some(TextureRole::Specular),
+                format: Some(DdsFormat(ImageFormat::BC7RgbaUnorm)),
+                keep_sources: false,
+            },
+            path: PathBuf::from("in/armor_rmaos.png"),
+            sources: Default::default(),
+        };
+        let config = pack_config(&group, &Config::default());
+        assert_eq!(config.role, Some(TextureRole::Specular));
+        assert_eq!(config.format, Some(DdsFormat(ImageFormat::BC7RgbaUnorm)));
+        assert_eq!(pack_channels(&group, &config), 3);
+    }
+
+    #[test]
+    fn generated_normal_replaces_the_height_suffix() {
+        assert_eq!(generated_normal_name("armor_h", "_n"), "armor_n");
+        assert_eq!(
+            generated_normal_name("armor_height_h", "_n"),
+            "armor_height_n"
+        );
+        assert_eq!(generated_normal_name("armor", "_n"), "armor_n");
+    }
+
+    #[test]
+    fn generated_normal_keeps_an_existing_normal_map() {
+        let dir = tempfile::tempdir().unwrap();
+        let height_data = image(dir.path(), "armor_h.png");
+        let settings = NormalGenConfig::default();
+        let config = Config {
+            channel_ops: vec!["invert:g".parse().unwrap()],
+            ..Config::default()
+        };
+
+        let (normal_data, normal_config) =
+            plan_generated_normal(&height_data, &settings, &config).unwrap();
+        assert_eq!(normal_data.file_name, "armor_n");
+        assert_eq!(normal_data.role, TextureRole::Normal);
+        assert!(normal_config.channel_ops.is_empty());
+
+        let existing = dir.path().join("armor_n.tga");
+        std::fs::write(&existing, b"").unwrap();
+        assert_eq!(
+            plan_generated_normal(&height_data, &settings, &config).err(),
+            Some(existing)
+        );
+    }
+}
@@ -0,0 +1,44 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/processing/processing_sharpen.rs
+
+use image::{DynamicImage, Rgba32FImage};
+
+use super::processing_channels::to_color_type;
+
+/// Sharpens an image with an unsharp mask.
+///
+/// Each color channel is pushed away from a blurred copy of the image by
+/// `amount` times the difference. Alpha is left untouched, so cutouts keep
+/// their coverage. The result keeps the color type of the input.
+///
+/// # Arguments
+///
+/// * `image` - The resized image.
+/// * `amount` - Strength of the effect; 0 returns the image unchanged.
+/// * `radius` - Standard deviation of the blur, in pixels.
+/// * `threshold` - Minimum difference (0 to 1) from the blurred image a pixel needs to be sharpened.
+///
+/// # Returns
+///
+/// * `DynamicImage` - The sharpened image.
+pub fn unsharp_mask(image: DynamicImage, amount: f32, radius: f32, threshold: f32) -> DynamicImage {
+    if amount <= 0.0 {
+        return image;
+    }
+
+    let source = image.to_rgba32f();
+    let blurred = image::imageops::blur(&source, radius);
+    let sharpened = Rgba32FImage::from_fn(source.width(), source.height(), |x, y| {
+        let mut pixel = *source.get_pixel(x, y);
+        let blurred_pixel = blurred.get_pixel(x, y);
+        for channel in 0..3 {
+            let difference = pixel[channel] - blurred_pixel[channel];
+            if difference.abs() > threshold {
+                pixel[channel] = (pixel[channel] + amount * difference).clamp(0.0, 1.0);
+            }
+        }
+        pixel
+    });
+
+    // Keep the color type, so intermediate files are written like unsharpened ones.
+    to_color_type(sharpened, &image)
+}
@@ -0,0 +1,321 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/processing/processing_size.rs
+
+use image::{DynamicImage, Rgba32FImage};
+
+use crate::structs::{
+    struct_runreport::ResizeAction,
+    struct_sizespec::{Pow2Policy, SizeSpec, UpscalePolicy},
+};
+
+use super::processing_channels::to_color_type;
+
+/// Dimensions of one output: the size the image is resized to and the
+/// canvas it is placed on, which is larger only when padding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TargetSize {
+    /// Width of the resized image.
+    pub width: u32,
+
+    /// Height of the resized image.
+    pub height: u32,
+
+    /// Width of the output.
+    pub canvas_width: u32,
+
+    /// Height of the output.
+    pub canvas_height: u32,
+
+    /// Whether the image is downscaled, kept at the source size or enlarged.
+    pub action: ResizeAction,
+}
+
+impl TargetSize {
+    /// Returns `true` if the resized image is padded to a larger canvas.
+    pub fn is_padded(&self) -> bool {
+        (self.width, self.height) != (self.canvas_width, self.canvas_height)
+    }
+}
+
+/// Calculates the output dimensions of an image for a size spec.
+///
+/// The spec gives the size keeping the aspect ratio. The power-of-two policy
+/// then rounds each dimension, or pads the canvas with `Pow2Policy::Pad`.
+/// Block compressed formats store 4x4 blocks, so with `multiple_of_4` each
+/// dimension is finally rounded to the nearest multiple of 4 (padded up
+/// when padding).
+///
+/// The upscale policy is applied to the rounded size, so rounding never
+/// enlarges an image behind the policy's back. If the rounded size is larger
+/// than the source, `Upscale` keeps it, while `Skip` and `Clamp` round down
+/// instead: `Skip` when the spec itself fits the source and skips otherwise,
+/// `Clamp` starting from the source size when the spec does not fit. Sources
+/// smaller than one 4x4 block are still enlarged to 4 pixels with `Clamp`,
+/// reported as upscaled.
+///
+/// # Arguments
+///
+/// * `width` - Width of the source image.
+/// * `height` - Height of the source image.
+/// * `spec` - The requested size.
+/// * `upscale` - The policy for sizes larger than the source.
+/// * `pow2` - The power-of-two policy.
+/// * `multiple_of_4` - Enforce dimensions that are multiples of 4.
+///
+/// # Returns
+///
+/// * `Some(TargetSize)` with the output dimensions.
+/// * `None` if the spec is larger than the source in either dimension and the policy is `Skip`.
+pub fn target_dimensions(
+    width: u32,
+    height: u32,
+    spec: SizeSpec,
+    upscale: UpscalePolicy,
+    pow2: Pow2Policy,
+    multiple_of_4: bool,
+) -> Option<TargetSize> {
+    let fits = |new_width: u32, new_height: u32| new_width <= width && new_height <= height;
+    let round = |size: u32, down: bool| round_dimension(size, pow2, multiple_of_4, down);
+
+    let (spec_width, spec_height) = spec.dimensions(width, height);
+    let (rounded_width, rounded_height) = (round(spec_width, false), round(spec_height, false));
+
+    let (new_width, new_height, action) = if fits(rounded_width, rounded_height) {
+        (rounded_width, rounded_height, ResizeAction::Resized)
+    } else {
+        match upscale {
+            UpscalePolicy::Upscale => (rounded_width, rounded_height, ResizeAction::Upscaled),
+            UpscalePolicy::Skip if !fits(spec_width, spec_height) => return None,
+            UpscalePolicy::Skip => (
+                round(spec_width, true),
+                round(spec_height, true),
+                ResizeAction::Resized,
+            ),
+            UpscalePolicy::Clamp if fits(spec_width, spec_height) => (
+                round(spec_width, true),
+                round(spec_height, true),
+                ResizeAction::Resized,
+            ),
+            UpscalePolicy::Clamp => (
+                round(width, true),
+                round(height, true),
+                ResizeAction::Clamped,
+            ),
+        }
+    };
+
+    // Only the 4 pixel minimum can still exceed the source after rounding down.
+    let action = match (fits(new_width, new_height), upscale) {
+        (true, _) | (false, UpscalePolicy::Upscale) => action,
+        (false, UpscalePolicy::Skip) => return None,
+        (false, UpscalePolicy::Clamp) => ResizeAction::Upscaled,
+    };
+
+    let mut size = TargetSize {
+        width: new_width,
+        height: new_height,
+        canvas_width: new_width,
+        canvas_height: new_height,
+        action,
+    };
+    if pow2 == Pow2Policy::Pad {
+        size.canvas_width = new_width.next_power_of_two();
+        size.canvas_height = new_height.next_power_of_two();
+        if multiple_of_4 {
+            size.canvas_width = size.canvas_width.next_multiple_of(4);
+            size.canvas_height = size.canvas_height.next_multiple_of(4);
+        }
+    }
+
+    Some(size)
+}
+
+/// Applies the power-of-two policy and the multiple-of-4 rule to one dimension.
+///
+/// Padding keeps the dimension, since only the canvas is enlarged. With
+/// `down`, rounding never increases the dimension, except to the minimum of 4.
+fn round_dimension(size: u32, pow2: Pow2Policy, multiple_of_4: bool, down: bool) -> u32 {
+    if pow2 == Pow2Policy::Pad {
+        return size;
+    }
+    let pow2 = match pow2 {
+        Pow2Policy::Up | Pow2Policy::Nearest if down => Pow2Policy::Down,
+        pow2 => pow2,
+    };
+    let size = pow2.round(size);
+    match (multiple_of_4, down) {
+        (false, _) => size,
+        (true, false) => nearest_multiple_of_4(size),
+        (true, true) => (size / 4 * 4).max(4),
+    }
+}
+
+/// Rounds a dimension to the nearest multiple of 4, at least 4.
+fn nearest_multiple_of_4(size: u32) -> u32 {
+    ((size + 2) / 4 * 4).max(4)
+}
+
+/// Places an image on a larger canvas, extending its edge pixels into the padding.
+///
+/// Repeating the edges instead of filling with black keeps the padding from
+/// bleeding into the image on smaller mip levels.
+///
+/// # Arguments
+///
+/// * `image` - The resized image, placed at the top left.
+/// * `width` - Width of the canvas.
+/// * `height` - Height of the canvas.
+///
+/// # Returns
+///
+/// * `DynamicImage` - The padded image, with the color type of `image`.
+pub fn pad_image(image: DynamicImage, width: u32, height: u32) -> DynamicImage {
+    let source = image.to_rgba32f();
+    let (source_width, source_height) = source.dimensions();
+    let padded = Rgba32FImage::from_fn(width, height, |x, y| {
+        *source.get_pixel(x.min(source_width - 1), y.min(source_height - 1))
+    });
+    to_color_type(padded, &image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dims(size: Option<TargetSize>) -> Option<(u32, u32, ResizeAction)> {
+        size.map(|size| (size.width, size.height, size.action))
+    }
+
+    #[test]
+    fn downscales_within_source() {
+        let size = target_dimensions(
+            2048,
+            1024,
+            SizeSpec::Width(1024),
+            UpscalePolicy::Skip,
+            Pow2Policy::None,
+            true,
+        );
+        assert_eq!(dims(size), Some((1024, 512, ResizeAction::Resized)));
+    }
+
+    #[test]
+    fn pow2_up_past_source_rounds_down_unless_upscaling() {
+        let resize = |upscale| {
+            target_dimensions(
+                700,
+                700,
+                SizeSpec::Width(600),
+                upscale,
+                Pow2Policy::Up,
+                true,
+            )
+        };
+        assert_eq!(
+            dims(resize(UpscalePolicy::Skip)),
+            Some((512, 512, ResizeAction::Resized))
+        );
+        assert_eq!(
+            dims(resize(UpscalePolicy::Clamp)),
+            Some((512, 512, ResizeAction::Resized))
+        );
+        assert_eq!(
+            dims(resize(UpscalePolicy::Upscale)),
+            Some((1024, 1024, ResizeAction::Upscaled))
+        );
+    }
+
+    #[test]
+    fn multiple_of_4_past_source_rounds_down() {
+        let size = target_dimensions(
+            1023,
+            1023,
+            SizeSpec::Width(1023),
+            UpscalePolicy::Skip,
+            Pow2Policy::None,
+            true,
+        );
+        assert_eq!(dims(size), Some((1020, 1020, ResizeAction::Resized)));
+    }
+
+    #[test]
+    fn spec_larger_than_source_follows_policy() {
+        let resize = |upscale| {
+            target_dimensions(
+                100,
+                60,
+                SizeSpec::Width(200),
+                upscale,
+                Pow2Policy::None,
+                true,
+            )
+        };
+        assert_eq!(dims(resize(UpscalePolicy::Skip)), None);
+        assert_eq!(
+            dims(resize(UpscalePolicy::Clamp)),
+            Some((100, 60, ResizeAction::Clamped))
+        );
+        assert_eq!(
+            dims(resize(UpscalePolicy::Upscale)),
+            Some((200, 120, ResizeAction::Upscaled))
+        );
+    }
+
+    #[test]
+    fn clamp_rounds_source_size_down() {
+        let size = target_dimensions(
+            700,
+            300,
+            SizeSpec::Width(2048),
+            UpscalePolicy::Clamp,
+            Pow2Policy::Nearest,
+            true,
+        );
+        assert_eq!(dims(size), Some((512, 256, ResizeAction::Clamped)));
+    }
+
+    #[test]
+    fn sources_below_one_block() {
+        let resize =
+            |upscale| target_dimensions(2, 2, SizeSpec::Width(2), upscale, Pow2Policy::None, true);
+        assert_eq!(dims(resize(UpscalePolicy::Skip)), None);
+        assert_eq!(
+            dims(resize(UpscalePolicy::Clamp)),
+            Some((4, 4, ResizeAction::Upscaled))
+        );
+        let size = target_dimensions(
+            2,
+            2,
+            SizeSpec::Width(2),
+            UpscalePolicy::Skip,
+            Pow2Policy::None,
+            false,
+        );
+        assert_eq!(dims(size), Some((2, 2, ResizeAction::Resized)));
+    }
+
+    #[test]
+    fn pad_keeps_image_and_grows_canvas() {
+        let size = target_dimensions(
+            600,
+            300,
+            SizeSpec::Width(600),
+            UpscalePolicy::Skip,
+            Pow2Policy::Pad,
+            true,
+        )
+        .unwrap();
+        assert_eq!((size.width, size.height), (600, 300));
+        assert_eq!((size.canvas_width, size.canvas_height), (1024, 512));
+        assert!(size.is_padded());
+    }
+
+    #[test]
+    fn pow2_policies_round_each_dimension() {
+        assert_eq!(Pow2Policy::Up.round(600), 1024);
+        assert_eq!(Pow2Policy::Down.round(600), 512);
+        assert_eq!(Pow2Policy::Nearest.round(600), 512);
+        assert_eq!(Pow2Policy::Nearest.round(800), 1024);
+        assert_eq!(Pow2Policy::None.round(600), 600);
+        assert_eq!(Pow2Policy::Down.round(1), 1);
+    }
+}
@@ -0,0 +1,276 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/processing/processing_verify.rs
+
+use colored::Color;
+use image::{DynamicImage, GenericImageView};
+use std::path::{Path, PathBuf};
+
+use crate::{
+    io::{
+        io_dds::{dds_image_format, read_dds},
+        io_in::load_image,
+        io_pathtree::mirror_path,
+        io_source::relative_input_path,
+    },
+    logging::{LogQueue, LogType},
+    structs::{
+        struct_config::Config, struct_imageanalysis::ImageAnalysis, struct_imagedata::ImageData,
+        struct_texturerole::TextureRole,
+    },
+};
+
+use super::{
+    processing_analysis::analyze_image,
+    processing_channels::{apply_channel_ops, output_channels},
+    processing_pack::{find_pack_groups, pack_sources, PackGroup},
+    processing_plan::{
+        pack_channels, pack_config, plan_generated_normal, plan_outputs, resolve_role,
+    },
+};
+
+/// Checks that every input has the DDS outputs `convert` would produce for it.
+///
+/// For each target width the output must exist in the mirrored output
+/// directory, parse as DDS, and have the expected dimensions and format.
+///
+/// # Arguments
+///
+/// * `filtered_paths` - Input files to check.
+/// * `config` - Reference to the `Config` struct containing configuration parameters.
+///
+/// # Returns
+///
+/// * `true` if every check passed, `false` otherwise.
+pub async fn verify_files(filtered_paths: &[PathBuf], config: &Config) -> bool {
+    let path_in = Path::new(&config.path_in);
+    let mut failures = 0;
+    let mut checked = 0;
+
+    // Pack sources are checked through the packed texture they feed.
+    let (pack_groups, pack_sources) = find_pack_groups(filtered_paths, &config.packs);
+    let inputs = filtered_paths
+        .iter()
+        .filter(|path| !pack_sources.contains(*path))
+        .map(|path| (path.clone(), None))
+        .chain(
+            pack_groups
+                .into_iter()
+                .map(|group| (group.path.clone(), Some(group))),
+        );
+
+    for (input_path, group) in inputs {
+        let relative_path =
+            relative_input_path(path_in, &input_path).unwrap_or_else(|_| input_path.clone());
+        let file_config = config.resolve_for(&relative_path);
+        let input_clone = input_path.clone();
+
+        let results = tokio::task::spawn_blocking(move || match group {
+            Some(group) => verify_pack(&group, &pack_config(&group, &file_config)),
+            None => verify_file(&input_clone, &file_config),
+        })
+        .await
+        .unwrap_or_else(|e| vec![Err(format!("Failed to spawn blocking task: {}", e))]);
+
+        let log_queue = LogQueue::new();
+        log_queue.enqueue(
+            LogType::Println,
+            format!("[Verifying: {}]", input_path.display()),
+            Color::White,
+        );
+        for result in results {
+            checked += 1;
+            match result {
+                Ok(message) => log_queue.enqueue(LogType::Println, message, Color::Green),
+                Err(message) => {
+                    failures += 1;
+                    log_queue.enqueue(LogType::Println, message, Color::Red);
+                }
+            }
+        }
+        log_queue.process_queue();
+    }
+
+    println!("--------------------------");
+    println!("Verified {} outputs, {} failed.", checked, failures);
+    println!("--------------------------");
+
+    failures == 0
+}
+
+/// Checks the DDS outputs of a single input.
+///
+/// # Arguments
+///
+/// * `input_path` - Path to the input image.
+/// * `config` - The configuration resolved for this input.
+///
+/// # Returns
+///
+/// * `Vec<Result<String, String>>` - One message per check, `Err` for failures.
+fn verify_file(input_path: &Path, config: &Config) -> Vec<Result<String, String>> {
+    let output_path = match mirror_path(
+        Path::new(&config.path_in),
+        Path::new(&config.path_out),
+        input_path,
+    ) {
+        Ok(output_path) => output_path,
+        Err(e) => return vec![Err(format!("Error mirroring path: {}", e))],
+    };
+    let mut image_data = ImageData::from((input_path.to_path_buf(), output_path));
+
+    let loaded = match load_image(input_path, image_data.file_type) {
+        Ok(loaded) => loaded,
+        Err(e) => return vec![Err(format!("Failed to open input: {}", e))],
+    };
+    let (width, height) = loaded.image.dimensions();
+    image_data.role = resolve_role(&image_data.file_name, config);
+    image_data.channels = output_channels(&config.channel_ops, loaded.channels);
+    image_data.analysis = analyze_for_format(&loaded.image, config);
+
+    let generated = config
+        .normal_from_height
+        .as_ref()
+        .filter(|_| image_data.role == TextureRole::Height)
+        .and_then(|settings| plan_generated_normal(&image_data, settings, config).ok());
+
+    let mut results = verify_targets(image_data, width, height, config);
+    if let Some((normal_data, normal_config)) = generated {
+        results.extend(verify_targets(normal_data, width, height, &normal_config));
+    }
+    results
+}
+
+/// Checks the DDS outputs of a packed texture.
+///
+/// # Arguments
+///
+/// * `group` - The pack group.
+/// * `config` - The configuration resolved for the packed texture.
+///
+/// # Returns
+///
+/// * `Vec<Result<String, String>>` - One message per check, `Err` for failures.
+fn verify_pack(group: &PackGroup, config: &Config) -> Vec<Result<String, String>> {
+    let output_path = match mirror_path(
+        Path::new(&config.path_in),
+        Path::new(&config.path_out),
+        &group.path,
+    ) {
+        Ok(output_path) => output_path,
+        Err(e) => return vec![Err(format!("Error mirroring path: {}", e))],
+    };
+    let mut image_data = ImageData::from((group.path.clone(), output_path));
+    image_data.role = resolve_role(&image_data.file_name, config);
+
+    let packed = match pack_sources(&group.pack, &group.sources) {
+        Ok(packed) => DynamicImage::ImageRgba32F(packed),
+        Err(e) => return vec![Err(format!("Failed to open pack source: {}", e))],
+    };
+    let (width, height) = packed.dimensions();
+    image_data.channels = pack_channels(group, config);
+    image_data.analysis = analyze_for_format(&packed, config);
+
+    verify_targets(image_data, width, height, config)
+}
+
+/// Analyzes an input like `convert` does, if its format is picked automatically.
+///
+/// # Arguments
+///
+/// * `image` - The input image.
+/// * `config` - The configuration resolved for this input.
+///
+/// # Returns
+///
+/// * `Some(ImageAnalysis)` if no format is configured, otherwise `None`.
+fn analyze_for_format(image: &DynamicImage, config: &Config) -> Option<ImageAnalysis> {
+    if config.format.is_some() {
+        return None;
+    }
+    let mut image = image.to_rgba32f();
+    apply_channel_ops(&mut image, &config.channel_ops);
+    Some(analyze_image(&image))
+}
+
+/// Checks the DDS outputs of every target size of an image.
+///
+/// # Arguments
+///
+/// * `image_data` - The `ImageData` with the resolved role, channel count and output path.
+/// * `width` - Width of the input.
+/// * `height` - Height of the input.
+/// * `config` - The configuration resolved for this input.
+///
+/// # Returns
+///
+/// * `Vec<Result<String, String>>` - One message per check, `Err` for failures.
+fn verify_targets(
+    image_data: ImageData,
+    width: u32,
+    height: u32,
+    config: &Config,
+) -> Vec<Result<String, String>> {
+    let plan = plan_outputs(&image_data, width, height, config);
+    plan.targets
+        .iter()
+        .filter_map(|&(spec, size)| {
+            let size = size?;
+            let dds_path = plan.output_path(config, &image_data, spec, &size, "dds");
+            Some(verify_output(
+                &dds_path,
+                size.canvas_width,
+                size.canvas_height,
+                plan.header_format,
+            ))
+        })
+        .collect()
+}
+
+/// Checks a single DDS output against its expected dimensions and format.
+///
+/// # Arguments
+///
+/// * `dds_path` - Path to the DDS output.
+/// * `width` - Expected width.
+/// * `height` - Expected height.
+/// * `format` - Expected format.
+///
+/// # Returns
+///
+/// * `Ok(String)` describing the passed check.
+/// * `Err(String)` describing the failure.
+fn verify_output(
+    dds_path: &Path,
+    width: u32,
+    height: u32,
+    format: image_dds::ImageFormat,
+) -> Result<String, String> {
+    if !dds_path.exists() {
+        return Err(format!("Missing: '{}'", dds_path.display()));
+    }
+    let dds = read_dds(dds_path)?;
+
+    if (dds.get_width(), dds.get_height()) != (width, height) {
+        return Err(format!(
+            "Wrong dimensions: '{}' is {}x{}, expected {}x{}",
+            dds_path.display(),
+            dds.get_width(),
+            dds.get_height(),
+            width,
+            height
+        ));
+    }
+    match dds_image_format(&dds) {
+        Ok(actual) if actual == format => Ok(format!("OK: '{}'", dds_path.display())),
+        Ok(actual) => Err(format!(
+            "Wrong format: '{}' is {}, expected {}",
+            dds_path.display(),
+            actual,
+            format
+        )),
+        Err(e) => Err(format!(
+            "Unsupported format: '{}': {}",
+            dds_path.display(),
+            e
+        )),
+    }
+}
@@ -0,0 +1,353 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/processing/processing_watch.rs
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use colored::Color;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use crate::{logging::smnprintln, structs::struct_config::Config};
+
+use super::processing_core::process_changed_files;
+
+/// Watches `path_in` and reprocesses images as they are created, modified or deleted.
+///
+/// Changes are batched by `InputWatcher`, so that the several writes an editor
+/// makes while saving trigger a single rebuild. Runs until the process is
+/// interrupted.
+///
+/// # Arguments
+///
+/// * `config` - Reference to the `Config` struct containing configuration parameters.
+/// * `supported_types` - File extensions that are picked up as inputs.
+///
+/// # Returns
+///
+/// * `Err(String)` with an error message if the watcher cannot be set up.
+pub async fn watch_files(config: &Config, supported_types: &[&str]) -> Result<(), String> {
+    let mut watcher = InputWatcher::new(config, supported_types)?;
+
+    smnprintln(
+        format!(
+            "Watching '{}' for changes (Ctrl+C to stop)...",
+            config.path_in
+        ),
+        Color::Yellow,
+    );
+
+    while let Some(batch) = watcher.next_batch().await {
+        for removed_path in &batch.removed_paths {
+            smnprintln(
+                format!("Removed: '{}'", removed_path.display()),
+                Color::Yellow,
+            );
+        }
+        if let Err(e) =
+            process_changed_files(&batch.changed_paths, &batch.removed_paths, config).await
+        {
+            eprintln!("Error during processing: {}", e);
+        }
+        smnprintln(
+            format!(
+                "Watching '{}' for changes (Ctrl+C to stop)...",
+                config.path_in
+            ),
+            Color::Yellow,
+        );
+    }
+
+    Ok(())
+}
+
+/// Inputs changed together, collected by `InputWatcher::next_batch`.
+#[derive(Debug, Default, PartialEq)]
+pub struct WatchBatch {
+    /// Input files that were created or modified, sorted.
+    pub changed_paths: Vec<PathBuf>,
+
+    /// Input files that were deleted, sorted.
+    pub removed_paths: Vec<PathBuf>,
+}
+
+/// Watches the inputs of a run and groups their changes into batches.
+pub struct InputWatcher {
+    /// The file system watcher; events stop when it is dropped.
+    _watcher: RecommendedWatcher,
+
+    /// Events forwarded from the watcher thread.
+    receiver: UnboundedReceiver<notify::Result<Event>>,
+
+    /// The configured input path, which batched paths are relative to.
+    config_path_in: PathBuf,
+
+    /// Canonical input path, to compare event paths against.
+    path_in: PathBuf,
+
+    /// Canonical output path, whose changes are ignored.
+    path_out: PathBuf,
+
+    /// File extensions that are picked up as inputs.
+    supported_types: Vec<String>,
+
+    /// Maximum directory traversal depth.
+    max_depth: usize,
+
+    /// Quiet interval that ends a batch.
+    debounce: Duration,
+}
+
+impl InputWatcher {
+    /// Starts watching `path_in` recursively.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Reference to the `Config` with the input and output paths,
+    ///   `max_depth` and `watch_debounce_ms`.
+    /// * `supported_types` - File extensions that are picked up as inputs.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(InputWatcher)` watching the inputs.
+    /// * `Err(String)` with an error message if the watcher cannot be set up.
+    pub fn new(config: &Config, supported_types: &[&str]) -> Result<Self, String> {
+        // Event paths are absolute, so compare against canonical directories.
+        let path_in = Path::new(&config.path_in)
+            .canonicalize()
+            .map_err(|e| format!("Failed to resolve input path '{}': {}", config.path_in, e))?;
+        let path_out = Path::new(&config.path_out)
+            .canonicalize()
+            .map_err(|e| format!("Failed to resolve output path '{}': {}", config.path_out, e))?;
+
+        // Forward watcher events from the notify thread to this task.
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+            let _ = sender.send(result);
+        })
+        .map_err(|e| format!("Failed to create file watcher: {}", e))?;
+        watcher
+            .watch(&path_in, RecursiveMode::Recursive)
+            .map_err(|e| format!("Failed to watch '{}': {}", path_in.display(), e))?;
+
+        Ok(InputWatcher {
+            _watcher: watcher,
+            receiver,
+            config_path_in: PathBuf::from(&config.path_in),
+            path_in,
+            path_out,
+            supported_types: supported_types
+                .iter()
+                .map(|ext| ext.to_lowercase())
+                .collect(),
+            max_depth: config.max_depth,
+            debounce: Duration::from_millis(config.watch_debounce_ms),
+        })
+    }
+
+    /// Waits for the next batch of input changes.
+    ///
+    /// Events are collected until no new event arrives for the debounce
+    /// interval. Changes inside `path_out` are ignored in case it is nested in
+    /// `path_in`, as are files that are not inputs of the batch run.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(WatchBatch)` with at least one changed or removed input.
+    /// * `None` once the watcher has stopped.
+    pub async fn next_batch(&mut self) -> Option<WatchBatch> {
+        loop {
+            // Collect events until the input has been quiet for the debounce interval.
+            let mut touched = HashSet::new();
+            collect_event_paths(self.receiver.recv().await?, &mut touched);
+            while let Ok(Some(result)) =
+                tokio::time::timeout(self.debounce, self.receiver.recv()).await
+            {
+                collect_event_paths(result, &mut touched);
+            }
+
+            let batch = self.batch(touched);
+            if !batch.changed_paths.is_empty() || !batch.removed_paths.is_empty() {
+                return Some(batch);
+            }
+        }
+    }
+
+    /// Sorts the touched paths into changed and removed inputs.
+    ///
+    /// # Arguments
+    ///
+    /// * `touched` - Absolute paths reported by the watcher.
+    fn batch(&self, touched: HashSet<PathBuf>) -> WatchBatch {
+        let supported_types: Vec<&str> = self.supported_types.iter().map(String::as_str).collect();
+        let mut batch = WatchBatch::default();
+        for path in touched {
+            if path.starts_with(&self.path_out) {
+                continue;
+            }
+            let relative_path = match path.strip_prefix(&self.path_in) {
+                Ok(relative_path) => relative_path,
+                Err(_) => continue,
+            };
+            if !is_watched_input(relative_path, &supported_types, self.max_depth) {
+                continue;
+            }
+
+            // Map the event back onto the configured input path so manifest keys match.
+            let input_path = self.config_path_in.join(relative_path);
+            if path.is_file() {
+                batch.changed_paths.push(input_path);
+            } else if !path.exists() {
+                batch.removed_paths.push(input_path);
+            }
+        }
+        batch.changed_paths.sort();
+        batch.removed_paths.sort();
+        batch
+    }
+}
+
+/// Adds the paths of a content-changing watcher event to `touched`.
+///
+/// Access events are ignored; watcher errors are logged.
+///
+/// # Arguments
+///
+/// * `result` - The event or error reported by the watcher.
+/// * `touched` - Set of paths with pending changes.
+fn collect_event_paths(result: notify::Result<Event>, touched: &mut HashSet<PathBuf>) {
+    match result {
+        Ok(event) => match event.kind {
+            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) | EventKind::Any => {
+                touched.extend(event.paths);
+            }
+            EventKind::Access(_) | EventKind::Other => {}
+        },
+        Err(e) => eprintln!("Watch error: {}", e),
+    }
+}
+
+/// Returns `true` if a path relative to `path_in` is an input of the batch run.
+///
+/// # Arguments
+///
+/// * `relative_path` - The path relative to `path_in`.
+/// * `supported_types` - File extensions that are picked up as inputs.
+/// * `max_depth` - Maximum directory traversal depth.
+fn is_watched_input(relative_path: &Path, supported_types: &[&str], max_depth: usize) -> bool {
+    let supported = relative_path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| supported_types.contains(&ext.as_str()));
+    supported && relative_path.components().count() <= max_depth
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, CreateKind, ModifyKind, RemoveKind};
+
+    fn event(kind: EventKind, path: &str) -> notify::Result<Event> {
+        Ok(Event::new(kind).add_path(PathBuf::from(path)))
+    }
+
+    #[test]
+    fn watches_supported_inputs_within_depth() {
+        let types = ["png", "dds"];
+        assert!(is_watched_input(Path::new("a.png"), &types, 1));
+        assert!(is_watched_input(Path::new("textures/a.PNG"), &types, 2));
+        assert!(!is_watched_input(Path::new("textures/a.png"), &types, 1));
+        assert!(!is_watched_input(Path::new("a.txt"), &types, 1));
+        assert!(!is_watched_input(Path::new("textures"), &types, 1));
+    }
+
+    #[test]
+    fn collects_content_changes_only() {
+        let mut touched = HashSet::new();
+        collect_event_paths(
+            event(EventKind::Create(CreateKind::File), "a.png"),
+            &mut touched,
+        );
+        collect_event_paths(
+            event(EventKind::Modify(ModifyKind::Any), "b.png"),
+            &mut touched,
+        );
+        collect_event_paths(
+            event(EventKind::Remove(RemoveKind::File), "c.png"),
+            &mut touched,
+        );
+        collect_event_paths(
+            event(EventKind::Modify(ModifyKind::Any), "a.png"),
+            &mut touched,
+        );
+        collect_event_paths(
+            event(EventKind::Access(AccessKind::Any), "d.png"),
+            &mut touched,
+        );
+        collect_event_paths(event(EventKind::Other, "e.png"), &mut touched);
+        collect_event_paths(Err(notify::Error::generic("lost")), &mut touched);
+
+        let expected: HashSet<PathBuf> = ["a.png", "b.png", "c.png"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        assert_eq!(touched, expected);
+    }
+
+    /// Waits a little longer than the debounce for the next batch.
+    async fn next_batch(watcher: &mut InputWatcher) -> Option<WatchBatch> {
+        tokio::time::timeout(Duration::from_secs(2), watcher.next_batch())
+            .await
+            .ok()
+            .flatten()
+    }
+
+    #[tokio::test]
+    async fn batches_repeated_writes_after_the_debounce() {
+        let dir = tempfile::tempdir().unwrap();
+        let (path_in, path_out) = (dir.path().join("in"), dir.path().join("in").join("out"));
+        std::fs::create_dir_all(&path_out).unwrap();
+        let config = Config {
+            path_in: path_in.to_string_lossy().into_owned(),
+            path_out: path_out.to_string_lossy().into_owned(),
+            watch_debounce_ms: 200,
+            ..Config::default()
+        };
+        let mut watcher = InputWatcher::new(&config, &["png"]).unwrap();
+
+        // Several writes, an unsupported file and an output form one batch.
+        let input = path_in.join("wood.png");
+        for contents in ["a", "ab", "abc"] {
+            std::fs::write(&input, contents).unwrap();
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        std::fs::write(path_in.join("notes.txt"), "").unwrap();
+        std::fs::write(path_out.join("wood.png"), "").unwrap();
+
+        let batch = next_batch(&mut watcher)
+            .await
+            .expect("no batch after writing an input");
+        assert_eq!(
+            batch,
+            WatchBatch {
+                changed_paths: vec![input.clone()],
+                removed_paths: vec![]
+            }
+        );
+        assert_eq!(next_batch(&mut watcher).await, None);
+
+        std::fs::remove_file(&input).unwrap();
+        let batch = next_batch(&mut watcher)
+            .await
+            .expect("no batch after removing an input");
+        assert_eq!(
+            batch,
+            WatchBatch {
+                changed_paths: vec![],
+                removed_paths: vec![input]
+            }
+        );
+    }
+}
@@ -1,5 +1,20 @@
-pub mod struct_imagedata;
-pub mod struct_filetype;
+pub mod struct_cachemanifest;
+pub mod struct_channelop;
+pub mod struct_colorspace;
 pub mod struct_config;
+pub mod struct_configloader;
 pub mod struct_ddsconfig;
-pub mod struct_configloader;
\ No newline at end of file
+pub mod struct_fileinfo;
+pub mod struct_filetype;
+pub mod struct_gameprofile;
+pub mod struct_imageanalysis;
+pub mod struct_imagedata;
+pub mod struct_normalgen;
+pub mod struct_outputlayout;
+pub mod struct_packageconfig;
+pub mod struct_packconfig;
+pub mod struct_pathrule;
+pub mod struct_resizeconfig;
+pub mod struct_runreport;
+pub mod struct_sizespec;
+pub mod struct_texturerole;
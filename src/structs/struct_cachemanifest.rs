@@ -0,0 +1,55 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/structs/struct_cachemanifest.rs
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Current version of the cache manifest layout.
+///
+/// Manifests written with a different version are discarded.
+pub const CACHE_MANIFEST_VERSION: u32 = 1;
+
+/// Record of a previous run, stored in the output directory.
+///
+/// Maps every processed input to the hash of its contents, the settings it
+/// was processed with, and the files it produced, so unchanged inputs can be
+/// skipped and outputs of removed inputs can be cleaned up.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheManifest {
+    /// Layout version of the manifest.
+    pub version: u32,
+
+    /// Input directory the manifest was written for.
+    pub path_in: String,
+
+    /// Entries keyed by the input path relative to `path_in`, using `/` separators.
+    pub files: BTreeMap<String, CacheEntry>,
+}
+
+/// Cache record for a single input file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    /// SHA-256 hash of the input file's contents.
+    pub hash: String,
+
+    /// SHA-256 hash of the effective settings the file was processed with.
+    pub settings: String,
+
+    /// Produced files relative to `path_out`, using `/` separators.
+    pub outputs: Vec<String>,
+}
+
+impl CacheManifest {
+    /// Creates an empty manifest for the given input directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `path_in` - The input directory the manifest describes.
+    pub fn new(path_in: &str) -> Self {
+        Self {
+            version: CACHE_MANIFEST_VERSION,
+            path_in: path_in.to_string(),
+            files: BTreeMap::new(),
+        }
+    }
+}
@@ -0,0 +1,194 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/structs/struct_channelop.rs
+
+use serde::{Deserialize, Serialize};
+
+/// A color channel of an RGBA image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    /// Red.
+    R,
+
+    /// Green.
+    G,
+
+    /// Blue.
+    B,
+
+    /// Alpha.
+    A,
+}
+
+impl Channel {
+    /// Returns the index of the channel within an RGBA pixel.
+    pub fn index(&self) -> usize {
+        match self {
+            Channel::R => 0,
+            Channel::G => 1,
+            Channel::B => 2,
+            Channel::A => 3,
+        }
+    }
+
+    /// Parses a channel from its letter (`r`, `g`, `b` or `a`).
+    fn from_char(c: char) -> Option<Self> {
+        match c.to_ascii_lowercase() {
+            'r' => Some(Channel::R),
+            'g' => Some(Channel::G),
+            'b' => Some(Channel::B),
+            'a' => Some(Channel::A),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Channel {
+    /// Formats the `Channel` as its letter.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Channel::R => write!(f, "r"),
+            Channel::G => write!(f, "g"),
+            Channel::B => write!(f, "b"),
+            Channel::A => write!(f, "a"),
+        }
+    }
+}
+
+/// A per-pixel channel operation applied before DDS conversion.
+///
+/// Written as `invert:g`, `swizzle:bgra` or `fill:a=1.0`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum ChannelOp {
+    /// Inverts a channel (`1 - value`), e.g. `invert:g` to convert between
+    /// OpenGL and DirectX normal maps.
+    Invert(Channel),
+
+    /// Reorders the channels; each output channel takes the listed source channel.
+    Swizzle([Channel; 4]),
+
+    /// Sets a channel to a constant value between 0 and 1.
+    Fill(Channel, f32),
+}
+
+impl TryFrom<String> for ChannelOp {
+    type Error = String;
+
+    /// Converts an operation string to a `ChannelOp`.
+    fn try_from(op: String) -> Result<Self, Self::Error> {
+        op.parse()
+    }
+}
+
+impl std::str::FromStr for ChannelOp {
+    type Err = String;
+
+    /// Parses a channel operation (`invert:<c>`, `swizzle:<cccc>` or `fill:<c>=<value>`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "Invalid channel operation '{}' (e.g. invert:g, swizzle:bgra, fill:a=1.0)",
+                s
+            )
+        };
+        let (name, argument) = s.trim().split_once(':').ok_or_else(invalid)?;
+        let channel = |arg: &str| {
+            let mut chars = arg.trim().chars();
+            match (chars.next().and_then(Channel::from_char), chars.next()) {
+                (Some(channel), None) => Ok(channel),
+                _ => Err(invalid()),
+            }
+        };
+
+        match name.trim().to_lowercase().as_str() {
+            "invert" => Ok(ChannelOp::Invert(channel(argument)?)),
+            "swizzle" => {
+                let channels: Vec<Channel> = argument
+                    .trim()
+                    .chars()
+                    .map(Channel::from_char)
+                    .collect::<Option<_>>()
+                    .ok_or_else(invalid)?;
+                let channels: [Channel; 4] = channels.try_into().map_err(|_| invalid())?;
+                Ok(ChannelOp::Swizzle(channels))
+            }
+            "fill" => {
+                let (target, value) = argument.split_once('=').ok_or_else(invalid)?;
+                let value = value.trim().parse::<f32>().map_err(|_| invalid())?;
+                if !(0.0..=1.0).contains(&value) {
+                    return Err(format!("Fill value in '{}' must be between 0 and 1", s));
+                }
+                Ok(ChannelOp::Fill(channel(target)?, value))
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl std::fmt::Display for ChannelOp {
+    /// Formats the `ChannelOp` in the syntax it is parsed from.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ChannelOp::Invert(channel) => write!(f, "invert:{}", channel),
+            ChannelOp::Swizzle(channels) => {
+                write!(f, "swizzle:")?;
+                channels
+                    .iter()
+                    .try_for_each(|channel| write!(f, "{}", channel))
+            }
+            ChannelOp::Fill(channel, value) => write!(f, "fill:{}={}", channel, value),
+        }
+    }
+}
+
+impl From<ChannelOp> for String {
+    /// Converts the `ChannelOp` to its display form, which is how it is serialized.
+    fn from(op: ChannelOp) -> Self {
+        op.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_every_operation() {
+        assert_eq!("invert:g".parse(), Ok(ChannelOp::Invert(Channel::G)));
+        assert_eq!(
+            " Swizzle:BGRA ".parse(),
+            Ok(ChannelOp::Swizzle([
+                Channel::B,
+                Channel::G,
+                Channel::R,
+                Channel::A
+            ]))
+        );
+        assert_eq!("fill:a=0.5".parse(), Ok(ChannelOp::Fill(Channel::A, 0.5)));
+    }
+
+    #[test]
+    fn rejects_invalid_operations() {
+        for op in [
+            "invert",
+            "invert:x",
+            "invert:rg",
+            "swizzle:bgr",
+            "swizzle:bgrax",
+            "fill:a",
+            "fill:a=1.5",
+            "fill:a=one",
+            "blur:r",
+        ] {
+            assert!(op.parse::<ChannelOp>().is_err(), "{}", op);
+        }
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for op in ["invert:g", "swizzle:abgr", "fill:b=0.25", "fill:a=1"] {
+            let parsed: ChannelOp = op.parse().unwrap();
+            assert_eq!(parsed.to_string(), op);
+            assert_eq!(String::from(parsed), op);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/structs/struct_colorspace.rs
+
+use serde::{Deserialize, Serialize};
+
+use super::struct_texturerole::TextureRole;
+
+/// Enumeration of color spaces an image's pixel values can be encoded in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorSpace {
+    /// Gamma encoded with the sRGB transfer function.
+    Srgb,
+
+    /// Linear values, either linear light or non-color data.
+    Linear,
+}
+
+impl ColorSpace {
+    /// Resolves the color space of an input image.
+    ///
+    /// Non-color roles are always linear data. For color roles the configured
+    /// override wins, then the color space detected from the file, and finally sRGB.
+    ///
+    /// # Arguments
+    ///
+    /// * `role` - The `TextureRole` of the image.
+    /// * `configured` - Optional color space override from the configuration.
+    /// * `detected` - Optional color space detected from file metadata.
+    ///
+    /// # Returns
+    ///
+    /// * `ColorSpace` - The color space the image data is encoded in.
+    pub fn resolve(
+        role: TextureRole,
+        configured: Option<ColorSpace>,
+        detected: Option<ColorSpace>,
+    ) -> Self {
+        if !role.is_color() {
+            return ColorSpace::Linear;
+        }
+        configured.or(detected).unwrap_or(ColorSpace::Srgb)
+    }
+}
+
+impl std::fmt::Display for ColorSpace {
+    /// Formats the `ColorSpace` for display purposes.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The formatter.
+    ///
+    /// # Returns
+    ///
+    /// * `std::fmt::Result` - The result of the formatting operation.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ColorSpace::Srgb => write!(f, "sRGB"),
+            ColorSpace::Linear => write!(f, "Linear"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_roles_are_always_linear() {
+        let resolved = ColorSpace::resolve(
+            TextureRole::Normal,
+            Some(ColorSpace::Srgb),
+            Some(ColorSpace::Srgb),
+        );
+        assert_eq!(resolved, ColorSpace::Linear);
+    }
+
+    #[test]
+    fn color_roles_prefer_the_configured_then_the_detected_space() {
+        let role = TextureRole::Diffuse;
+        assert_eq!(ColorSpace::resolve(role, None, None), ColorSpace::Srgb);
+        assert_eq!(
+            ColorSpace::resolve(role, None, Some(ColorSpace::Linear)),
+            ColorSpace::Linear
+        );
+        assert_eq!(
+            ColorSpace::resolve(role, Some(ColorSpace::Srgb), Some(ColorSpace::Linear)),
+            ColorSpace::Srgb
+        );
+    }
+}
@@ -1,30 +1,149 @@
 // D:/Work/Projects/Modding/Skyrim/dds-edit/src/structs/struct_config.rs
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, path::Path};
+
+use crate::io::io_backend::OutputBackend;
+
+use super::{
+    struct_channelop::ChannelOp,
+    struct_colorspace::ColorSpace,
+    struct_ddsconfig::{DdsFormat, DdsQuality, MipFilter, MipmapSetting},
+    struct_gameprofile::GameProfile,
+    struct_normalgen::NormalGenConfig,
+    struct_outputlayout::{validate_name_collisions, OutputLayout},
+    struct_packageconfig::PackageConfig,
+    struct_packconfig::PackConfig,
+    struct_pathrule::PathRule,
+    struct_resizeconfig::{ResizeFilter, SharpenConfig},
+    struct_sizespec::{Pow2Policy, SizeSpec, UpscalePolicy},
+    struct_texturerole::TextureRole,
+};
 
 /// Structure to hold parsed command-line arguments or configuration file parameters.
 ///
 /// Contains all configuration parameters required for processing images.
-#[derive(Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct Config {
     /// Input directory path containing images to be processed.
     pub path_in: String,
 
-    /// Output directory path where processed images will be saved.
+    /// Output directory path where processed images will be saved, or a `.zip` archive to write them into.
     pub path_out: String,
 
     /// Maximum directory traversal depth.
     pub max_depth: usize,
 
-    /// List of target widths for resizing images.
-    pub target_sizes: Vec<u32>,
+    /// How outputs are arranged inside `path_out`.
+    pub layout: OutputLayout,
+
+    /// Template for output file names (e.g., `{stem}_{width}`); the layout's default when unset.
+    pub name_template: Option<String>,
+
+    /// List of target sizes for resizing images (e.g., `1024`, `h:512`, `max:2048`, `50%`).
+    pub target_sizes: Vec<SizeSpec>,
+
+    /// Policy for target sizes larger than the source image (`skip`, `clamp` or `upscale`).
+    pub upscale: UpscalePolicy,
+
+    /// Filter used to enlarge images with the `upscale` policy.
+    pub upscale_filter: ResizeFilter,
+
+    /// Power-of-two policy for the target dimensions.
+    pub pow2: Pow2Policy,
+
+    /// Round the target dimensions of block compressed outputs to multiples of 4.
+    pub multiple_of_4: bool,
 
     /// Number of threads to use for concurrent processing.
     pub threads: usize,
 
+    /// Filter used to resize images to the target widths.
+    pub resize_filter: ResizeFilter,
+
+    /// Unsharp mask applied after resizing, if set.
+    pub sharpen: Option<SharpenConfig>,
+
+    /// Compression quality for DDS encoding (`fast`, `normal` or `slow`).
+    pub quality: DdsQuality,
+
+    /// Mipmap generation (`auto`, `none` or a fixed level count).
+    pub mipmaps: MipmapSetting,
+
+    /// Filter used to generate the mip levels (`box`, `kaiser` or `lanczos`).
+    pub mip_filter: MipFilter,
+
+    /// Alpha test reference (0 to 1) of cutout textures. When set, alpha is
+    /// rescaled on every mip level so the share of pixels passing the test
+    /// stays the same as on the base level.
+    pub alpha_test_ref: Option<f32>,
+
+    /// Optional explicit DDS format overriding the role-based selection.
+    pub format: Option<DdsFormat>,
+
+    /// Target game, constraining the DDS formats and header type of the outputs.
+    pub game: GameProfile,
+
+    /// Additional file name suffixes mapped to texture roles (e.g., `"_nm" = "normal"`).
+    /// These take precedence over the built-in Bethesda suffix table.
+    pub role_suffixes: HashMap<String, TextureRole>,
+
+    /// Optional color space override for color textures (`srgb` or `linear`).
+    /// When unset, the color space is detected from the file and defaults to sRGB.
+    pub color_space: Option<ColorSpace>,
+
+    /// Treat the RGB channels of normal maps as vectors and renormalize them
+    /// after resizing and on every mip level.
+    pub renormalize_normals: bool,
+
+    /// Generate a normal map from every height map, if set.
+    pub normal_from_height: Option<NormalGenConfig>,
+
+    /// Channel operations (invert, swizzle, fill) applied before DDS conversion.
+    pub channel_ops: Vec<ChannelOp>,
+
+    /// Optional texture role applied to every file instead of the suffix-based classification.
+    pub role: Option<TextureRole>,
+
+    /// Ordered list of per-path rules; the first rule matching a file wins.
+    #[serde(skip_serializing)]
+    pub rules: Vec<PathRule>,
+
+    /// Textures packed from the channels of several source maps.
+    #[serde(skip_serializing)]
+    pub packs: Vec<PackConfig>,
+
+    /// Generate a FOMOD installer with one option per target size, if set.
+    #[serde(skip_serializing)]
+    pub package: Option<PackageConfig>,
+
+    /// Only decode DDS inputs to PNG instead of resizing and converting them.
+    pub decode_only: bool,
+
+    /// Also save each resized image in its source format (or PNG) next to the DDS output.
+    pub keep_intermediate: bool,
+
+    /// Ignore the cache manifest and reprocess every input.
+    pub force: bool,
+
+    /// Keep running after the initial pass and reprocess inputs as they change.
+    pub watch: bool,
+
+    /// Quiet period in milliseconds that watch mode waits for before reprocessing.
+    pub watch_debounce_ms: u64,
+
+    /// Print the output of the `info` command as JSON.
+    #[serde(skip)]
+    pub json_output: bool,
+
     /// Optional path to the configuration file.
     #[serde(skip)]
     pub config_file: Option<String>,
+
+    /// Backend the outputs are written to, set up from `path_out` when a run starts.
+    #[serde(skip)]
+    pub output: OutputBackend,
 }
 
 impl Default for Config {
@@ -33,9 +152,336 @@ impl Default for Config {
             path_in: String::new(),
             path_out: "./images/output".to_string(),
             max_depth: usize::MAX,
-            target_sizes: vec![2048],
+            layout: OutputLayout::Mirrored,
+            name_template: None,
+            target_sizes: vec![SizeSpec::Width(2048)],
+            upscale: UpscalePolicy::Skip,
+            upscale_filter: ResizeFilter::CatmullRom,
+            pow2: Pow2Policy::None,
+            multiple_of_4: true,
             threads: 4,
+            resize_filter: ResizeFilter::Triangle,
+            sharpen: None,
+            quality: DdsQuality::Normal,
+            mipmaps: MipmapSetting::Auto,
+            mip_filter: MipFilter::Box,
+            alpha_test_ref: None,
+            format: None,
+            game: GameProfile::Dx10,
+            role_suffixes: HashMap::new(),
+            color_space: None,
+            renormalize_normals: true,
+            channel_ops: Vec::new(),
+            normal_from_height: None,
+            role: None,
+            rules: Vec::new(),
+            packs: Vec::new(),
+            package: None,
+            decode_only: false,
+            keep_intermediate: false,
+            force: false,
+            watch: false,
+            watch_debounce_ms: 500,
+            json_output: false,
             config_file: None,
+            output: OutputBackend::Directory,
+        }
+    }
+}
+
+impl Config {
+    /// Resolves the effective configuration for a single input file.
+    ///
+    /// The first rule whose pattern matches the file overrides the settings it
+    /// specifies; all other settings are inherited from this configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `relative_path` - The input path relative to `path_in`.
+    ///
+    /// # Returns
+    ///
+    /// * `Config` - The configuration to process the file with.
+    pub fn resolve_for(&self, relative_path: &Path) -> Config {
+        let mut config = self.clone();
+
+        if let Some(rule) = self.rules.iter().find(|rule| rule.matches(relative_path)) {
+            if let Some(target_sizes) = &rule.target_sizes {
+                config.target_sizes = target_sizes.clone();
+            }
+            if let Some(upscale) = rule.upscale {
+                config.upscale = upscale;
+            }
+            if let Some(upscale_filter) = rule.upscale_filter {
+                config.upscale_filter = upscale_filter;
+            }
+            if let Some(pow2) = rule.pow2 {
+                config.pow2 = pow2;
+            }
+            if let Some(resize_filter) = rule.resize_filter {
+                config.resize_filter = resize_filter;
+            }
+            if rule.sharpen.is_some() {
+                config.sharpen = rule.sharpen.clone();
+            }
+            if let Some(quality) = rule.quality {
+                config.quality = quality;
+            }
+            if let Some(mipmaps) = rule.mipmaps {
+                config.mipmaps = mipmaps;
+            }
+            if let Some(mip_filter) = rule.mip_filter {
+                config.mip_filter = mip_filter;
+            }
+            if rule.alpha_test_ref.is_some() {
+                config.alpha_test_ref = rule.alpha_test_ref;
+            }
+            if rule.format.is_some() {
+                config.format = rule.format;
+            }
+            if rule.role.is_some() {
+                config.role = rule.role;
+            }
+            if rule.color_space.is_some() {
+                config.color_space = rule.color_space;
+            }
+            if let Some(channel_ops) = &rule.channel_ops {
+                config.channel_ops = channel_ops.clone();
+            }
+            if rule.normal_from_height.is_some() {
+                config.normal_from_height = rule.normal_from_height.clone();
+            }
+        }
+
+        config
+    }
+
+    /// Lists every target size an output can be written at.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<SizeSpec>` - The global target sizes followed by the ones only rules add,
+    ///   in configuration order and without duplicates.
+    pub fn all_target_sizes(&self) -> Vec<SizeSpec> {
+        let mut target_sizes = self.target_sizes.clone();
+        for rule in &self.rules {
+            for &spec in rule.target_sizes.iter().flatten() {
+                if !target_sizes.contains(&spec) {
+                    target_sizes.push(spec);
+                }
+            }
         }
+        target_sizes
+    }
+
+    /// Checks that the naming template gives every target size of every rule its own output.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` if no outputs collide.
+    /// * `Err(String)` with an error message if several sizes would share a file.
+    pub fn validate_naming(&self) -> Result<(), String> {
+        let template = self
+            .name_template
+            .as_deref()
+            .unwrap_or_else(|| self.layout.default_template());
+        validate_name_collisions(self.layout, template, &self.target_sizes)?;
+        for rule in &self.rules {
+            if let Some(target_sizes) = &rule.target_sizes {
+                validate_name_collisions(self.layout, template, target_sizes)
+                    .map_err(|e| format!("Rule '{}': {}", rule.pattern, e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Describes the settings that affect the produced outputs.
+    ///
+    /// Used by the cache manifest to detect inputs that must be rebuilt because
+    /// their effective settings changed. Paths, threading and the rule list
+    /// (already applied by `resolve_for`), the packs and the package metadata are left out.
+    /// The settings are serialized to JSON rather than formatted with `Debug`,
+    /// so the fingerprint only changes when a setting does.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - A stable description of the output-relevant settings.
+    pub fn settings_fingerprint(&self) -> String {
+        let mut settings = self.clone();
+        settings.path_in.clear();
+        settings.path_out.clear();
+        settings.max_depth = 0;
+        settings.threads = 0;
+        settings.force = false;
+        settings.watch = false;
+        settings.watch_debounce_ms = 0;
+
+        // Serialized through `Value`, whose maps are sorted, so the
+        // HashMap of role suffixes is listed in a stable order.
+        let settings = serde_json::to_value(&settings)
+            .map(|value| value.to_string())
+            .unwrap_or_default();
+        format!("{}|{}", env!("CARGO_PKG_VERSION"), settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a configuration with the global settings and rules of a TOML snippet.
+    fn config_with_rules(toml: &str) -> Config {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn fingerprint_ignores_run_settings() {
+        let config = Config::default();
+        let other = Config {
+            path_in: "textures".to_string(),
+            path_out: "out".to_string(),
+            threads: 16,
+            force: true,
+            ..Config::default()
+        };
+        assert_eq!(config.settings_fingerprint(), other.settings_fingerprint());
+    }
+
+    #[test]
+    fn fingerprint_changes_with_output_settings() {
+        let config = Config::default();
+        let other = Config {
+            target_sizes: vec![SizeSpec::Width(1024)],
+            ..Config::default()
+        };
+        assert_ne!(config.settings_fingerprint(), other.settings_fingerprint());
+
+        let other = Config {
+            sharpen: Some(SharpenConfig::default()),
+            ..Config::default()
+        };
+        assert_ne!(config.settings_fingerprint(), other.settings_fingerprint());
+    }
+
+    #[test]
+    fn fingerprint_lists_role_suffixes_in_a_stable_order() {
+        let suffixes = ["_a", "_b", "_c", "_d", "_e", "_f", "_g", "_h"];
+        let first = Config {
+            role_suffixes: suffixes
+                .iter()
+                .map(|s| (s.to_string(), TextureRole::EnvMask))
+                .collect(),
+            ..Config::default()
+        };
+        let second = Config {
+            role_suffixes: suffixes
+                .iter()
+                .rev()
+                .map(|s| (s.to_string(), TextureRole::EnvMask))
+                .collect(),
+            ..Config::default()
+        };
+        assert_eq!(first.settings_fingerprint(), second.settings_fingerprint());
+    }
+
+    #[test]
+    fn invalid_rule_pattern_is_a_config_error() {
+        let error = toml::from_str::<Config>("[[rules]]\npattern = \"textures/[\"\n")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.contains("Invalid rule pattern 'textures/['"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let config = config_with_rules(
+            r#"
+            [[rules]]
+            pattern = "textures/armor/*_n.png"
+            quality = "slow"
+
+            [[rules]]
+            pattern = "**/*_n.png"
+            quality = "fast"
+            "#,
+        );
+        let resolved = config.resolve_for(Path::new("textures/armor/cuirass_n.png"));
+        assert_eq!(resolved.quality, DdsQuality::Slow);
+        let resolved = config.resolve_for(Path::new("textures/weapons/sword_n.png"));
+        assert_eq!(resolved.quality, DdsQuality::Fast);
+    }
+
+    #[test]
+    fn unset_rule_fields_inherit_the_global_settings() {
+        let config = config_with_rules(
+            r#"
+            target_sizes = [2048, 1024]
+            quality = "slow"
+            role = "diffuse"
+
+            [[rules]]
+            pattern = "**/*_n.png"
+            target_sizes = [512]
+            "#,
+        );
+        let resolved = config.resolve_for(Path::new("textures/armor/cuirass_n.png"));
+        assert_eq!(resolved.target_sizes, [SizeSpec::Width(512)]);
+        assert_eq!(resolved.quality, DdsQuality::Slow);
+        assert_eq!(resolved.role, Some(TextureRole::Diffuse));
+
+        let resolved = config.resolve_for(Path::new("textures/armor/cuirass_d.png"));
+        assert_eq!(
+            resolved.target_sizes,
+            [SizeSpec::Width(2048), SizeSpec::Width(1024)]
+        );
+    }
+
+    #[test]
+    fn rule_patterns_match_case_insensitively() {
+        let config = config_with_rules(
+            r#"
+            [[rules]]
+            pattern = "Textures/Armor/*_N.png"
+            quality = "fast"
+            "#,
+        );
+        let resolved = config.resolve_for(Path::new("textures/armor/cuirass_n.PNG"));
+        assert_eq!(resolved.quality, DdsQuality::Fast);
+    }
+
+    #[test]
+    fn only_double_star_crosses_directories() {
+        let config = config_with_rules(
+            r#"
+            [[rules]]
+            pattern = "textures/*.png"
+            quality = "slow"
+
+            [[rules]]
+            pattern = "textures/**/*.png"
+            quality = "fast"
+            "#,
+        );
+        let resolved = config.resolve_for(Path::new("textures/wood.png"));
+        assert_eq!(resolved.quality, DdsQuality::Slow);
+        let resolved = config.resolve_for(Path::new("textures/armor/iron/cuirass.png"));
+        assert_eq!(resolved.quality, DdsQuality::Fast);
+    }
+
+    #[test]
+    fn backslash_separators_are_normalized() {
+        let config = config_with_rules(
+            r#"
+            [[rules]]
+            pattern = "textures/armor/**/*_n.png"
+            quality = "fast"
+            "#,
+        );
+        let resolved = config.resolve_for(Path::new("textures\\armor\\iron\\cuirass_n.png"));
+        assert_eq!(resolved.quality, DdsQuality::Fast);
     }
 }
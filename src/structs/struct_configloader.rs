@@ -1,6 +1,9 @@
 // D:/Work/Projects/Modding/Skyrim/dds-edit/src/structs/config_loader.rs
 
-use super::struct_config::Config;
+use super::{
+    struct_config::Config, struct_ddsconfig::validate_alpha_test_ref,
+    struct_outputlayout::validate_name_template,
+};
 use std::fs;
 use toml;
 
@@ -17,6 +20,33 @@ use toml;
 pub fn load_config_from_file(path: &str) -> Result<Config, String> {
     let config_content = fs::read_to_string(path)
         .map_err(|e| format!("Failed to read config file '{}': {}", path, e))?;
-    toml::from_str(&config_content)
-        .map_err(|e| format!("Failed to parse config file '{}': {}", path, e))
+    let config: Config = toml::from_str(&config_content)
+        .map_err(|e| format!("Failed to parse config file '{}': {}", path, e))?;
+
+    if let Some(alpha_test_ref) = config.alpha_test_ref {
+        validate_alpha_test_ref(alpha_test_ref)
+            .map_err(|e| format!("Invalid config file '{}': {}", path, e))?;
+    }
+
+    if let Some(name_template) = &config.name_template {
+        validate_name_template(name_template)
+            .map_err(|e| format!("Invalid config file '{}': {}", path, e))?;
+    }
+
+    if let Some(sharpen) = &config.sharpen {
+        sharpen
+            .validate()
+            .map_err(|e| format!("Invalid config file '{}': {}", path, e))?;
+    }
+
+    for rule in &config.rules {
+        rule.validate()
+            .map_err(|e| format!("Invalid config file '{}': {}", path, e))?;
+    }
+    for pack in &config.packs {
+        pack.validate()
+            .map_err(|e| format!("Invalid config file '{}': {}", path, e))?;
+    }
+
+    Ok(config)
 }
@@ -1,5 +1,255 @@
 // D:/Work/Projects/Modding/Skyrim/dds-edit/src/structs/struct_ddsconfig.rs
 
+use image_dds::ImageFormat;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+use super::{struct_config::Config, struct_gameprofile::GameProfile};
+
+/// Compression quality level for DDS encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DdsQuality {
+    /// Fastest encoding with slightly lower quality.
+    Fast,
+
+    /// Balanced encoding speed and quality.
+    Normal,
+
+    /// Slowest encoding with the best quality.
+    Slow,
+}
+
+impl From<DdsQuality> for image_dds::Quality {
+    /// Converts a `DdsQuality` to the corresponding `image_dds::Quality`.
+    fn from(quality: DdsQuality) -> Self {
+        match quality {
+            DdsQuality::Fast => image_dds::Quality::Fast,
+            DdsQuality::Normal => image_dds::Quality::Normal,
+            DdsQuality::Slow => image_dds::Quality::Slow,
+        }
+    }
+}
+
+impl std::str::FromStr for DdsQuality {
+    type Err = String;
+
+    /// Parses a quality level from a string (`fast`, `normal`, `slow` or `best`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "fast" => Ok(DdsQuality::Fast),
+            "normal" => Ok(DdsQuality::Normal),
+            "slow" | "best" => Ok(DdsQuality::Slow),
+            _ => Err(format!(
+                "Invalid quality '{}' (expected fast, normal or slow)",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for DdsQuality {
+    /// Formats the `DdsQuality` for display purposes.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DdsQuality::Fast => write!(f, "Fast"),
+            DdsQuality::Normal => write!(f, "Normal"),
+            DdsQuality::Slow => write!(f, "Slow"),
+        }
+    }
+}
+
+/// Mipmap generation setting for DDS encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "MipmapSettingRepr", into = "String")]
+pub enum MipmapSetting {
+    /// Generate mipmaps down to 1x1.
+    Auto,
+
+    /// Only store the base level.
+    None,
+
+    /// Generate a fixed number of mip levels, including the base level.
+    Count(u32),
+}
+
+/// Raw TOML representation of a `MipmapSetting`, either a name or a level count.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MipmapSettingRepr {
+    Count(u32),
+    Name(String),
+}
+
+impl TryFrom<MipmapSettingRepr> for MipmapSetting {
+    type Error = String;
+
+    /// Converts the raw TOML representation to a `MipmapSetting`.
+    fn try_from(repr: MipmapSettingRepr) -> Result<Self, Self::Error> {
+        match repr {
+            MipmapSettingRepr::Count(count) => Ok(MipmapSetting::Count(count)),
+            MipmapSettingRepr::Name(name) => name.parse(),
+        }
+    }
+}
+
+impl From<MipmapSetting> for image_dds::Mipmaps {
+    /// Converts a `MipmapSetting` to the corresponding `image_dds::Mipmaps`.
+    fn from(mipmaps: MipmapSetting) -> Self {
+        match mipmaps {
+            MipmapSetting::Auto => image_dds::Mipmaps::GeneratedAutomatic,
+            MipmapSetting::None => image_dds::Mipmaps::Disabled,
+            MipmapSetting::Count(count) => image_dds::Mipmaps::GeneratedExact(count),
+        }
+    }
+}
+
+impl std::str::FromStr for MipmapSetting {
+    type Err = String;
+
+    /// Parses a mipmap setting from a string (`auto`, `none` or a level count).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "auto" => Ok(MipmapSetting::Auto),
+            "none" => Ok(MipmapSetting::None),
+            count => count.parse::<u32>().map(MipmapSetting::Count).map_err(|_| {
+                format!(
+                    "Invalid mipmaps '{}' (expected auto, none or a level count)",
+                    s
+                )
+            }),
+        }
+    }
+}
+
+impl std::fmt::Display for MipmapSetting {
+    /// Formats the `MipmapSetting` for display purposes.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MipmapSetting::Auto => write!(f, "Auto"),
+            MipmapSetting::None => write!(f, "None"),
+            MipmapSetting::Count(count) => write!(f, "{}", count),
+        }
+    }
+}
+
+impl From<MipmapSetting> for String {
+    /// Converts the `MipmapSetting` to its display form, which is how it is serialized.
+    fn from(mipmaps: MipmapSetting) -> Self {
+        mipmaps.to_string()
+    }
+}
+
+/// Filter used to downsample each mip level from the previous one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MipFilter {
+    /// Averages the pixels covered by each output pixel.
+    Box,
+
+    /// Kaiser-windowed sinc; sharper than box with little ringing.
+    Kaiser,
+
+    /// Lanczos-3 windowed sinc; sharpest, with some ringing at hard edges.
+    Lanczos,
+}
+
+impl std::str::FromStr for MipFilter {
+    type Err = String;
+
+    /// Parses a mip filter from a string (`box`, `kaiser` or `lanczos`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "box" => Ok(MipFilter::Box),
+            "kaiser" => Ok(MipFilter::Kaiser),
+            "lanczos" => Ok(MipFilter::Lanczos),
+            _ => Err(format!(
+                "Invalid mip filter '{}' (expected box, kaiser or lanczos)",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for MipFilter {
+    /// Formats the `MipFilter` for display purposes.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MipFilter::Box => write!(f, "Box"),
+            MipFilter::Kaiser => write!(f, "Kaiser"),
+            MipFilter::Lanczos => write!(f, "Lanczos"),
+        }
+    }
+}
+
+/// Checks that an alpha test reference value lies between 0 and 1.
+///
+/// # Arguments
+///
+/// * `value` - The reference value.
+///
+/// # Returns
+///
+/// * `Ok(f32)` with the value if it is valid.
+/// * `Err(String)` with an error message if it is not.
+pub fn validate_alpha_test_ref(value: f32) -> Result<f32, String> {
+    if (0.0..=1.0).contains(&value) {
+        Ok(value)
+    } else {
+        Err(format!(
+            "Alpha test reference {} must be between 0 and 1",
+            value
+        ))
+    }
+}
+
+/// Explicit DDS output format, wrapping `image_dds::ImageFormat`.
+///
+/// Parsed case-insensitively from the format name (e.g., `BC7RgbaUnormSrgb`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct DdsFormat(pub ImageFormat);
+
+impl TryFrom<String> for DdsFormat {
+    type Error = String;
+
+    /// Converts a format name to a `DdsFormat`.
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        name.parse()
+    }
+}
+
+impl std::str::FromStr for DdsFormat {
+    type Err = String;
+
+    /// Parses a format from its `image_dds::ImageFormat` variant name.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ImageFormat::iter()
+            .find(|format| format.to_string().eq_ignore_ascii_case(s.trim()))
+            .map(DdsFormat)
+            .ok_or_else(|| {
+                format!(
+                    "Invalid format '{}' (e.g. BC1RgbaUnormSrgb, BC7RgbaUnorm)",
+                    s
+                )
+            })
+    }
+}
+
+impl std::fmt::Display for DdsFormat {
+    /// Formats the `DdsFormat` for display purposes.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<DdsFormat> for String {
+    /// Converts the `DdsFormat` to its display form, which is how it is serialized.
+    fn from(format: DdsFormat) -> Self {
+        format.to_string()
+    }
+}
+
 /// Configuration for DDS conversion.
 ///
 /// Contains parameters that dictate how images are converted to DDS format.
@@ -9,18 +259,104 @@ pub struct DdsConvertConfig {
 
     /// Mipmap generation setting (e.g., "GeneratedAutomatic", "None").
     pub mipmaps: image_dds::Mipmaps,
+
+    /// Explicit output format; when `None` the format is picked from the texture role.
+    pub format: Option<ImageFormat>,
+
+    /// Target game the outputs must be readable by.
+    pub game: GameProfile,
+
+    /// Renormalize normal maps on every mip level.
+    pub renormalize_normals: bool,
+
+    /// Filter used to generate the mip levels.
+    pub mip_filter: MipFilter,
+
+    /// Alpha test reference whose coverage is preserved on every mip level, if set.
+    pub alpha_test_ref: Option<f32>,
 }
 
-impl DdsConvertConfig {
-    /// Creates a new `DdsConvertConfig` with default values.
+impl From<&Config> for DdsConvertConfig {
+    /// Creates a `DdsConvertConfig` from the DDS settings in a `Config`.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Reference to the `Config` struct containing configuration parameters.
     ///
     /// # Returns
     ///
-    /// * `Self` - An instance of `DdsConvertConfig` with preset configurations.
-    pub fn new() -> Self {
+    /// * `Self` - An instance of `DdsConvertConfig` with the configured settings.
+    fn from(config: &Config) -> Self {
         Self {
-            quality: image_dds::Quality::Normal,
-            mipmaps: image_dds::Mipmaps::GeneratedAutomatic,
+            quality: config.quality.into(),
+            mipmaps: config.mipmaps.into(),
+            format: config.format.map(|format| format.0),
+            game: config.game,
+            renormalize_normals: config.renormalize_normals,
+            mip_filter: config.mip_filter,
+            alpha_test_ref: config.alpha_test_ref,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quality_parses_and_round_trips() {
+        assert_eq!("BEST".parse(), Ok(DdsQuality::Slow));
+        assert!("ultra".parse::<DdsQuality>().is_err());
+        for quality in [DdsQuality::Fast, DdsQuality::Normal, DdsQuality::Slow] {
+            assert_eq!(quality.to_string().parse(), Ok(quality));
+        }
+    }
+
+    #[test]
+    fn mipmaps_parse_names_and_counts() {
+        #[derive(Deserialize)]
+        struct Settings {
+            mipmaps: MipmapSetting,
+        }
+        let parse = |toml: &str| toml::from_str::<Settings>(toml).map(|s| s.mipmaps);
+        assert_eq!(parse("mipmaps = 4").unwrap(), MipmapSetting::Count(4));
+        assert_eq!(parse("mipmaps = \"none\"").unwrap(), MipmapSetting::None);
+        assert_eq!(parse("mipmaps = \"6\"").unwrap(), MipmapSetting::Count(6));
+        assert!(parse("mipmaps = \"some\"").is_err());
+        for mipmaps in [
+            MipmapSetting::Auto,
+            MipmapSetting::None,
+            MipmapSetting::Count(3),
+        ] {
+            assert_eq!(String::from(mipmaps).parse(), Ok(mipmaps));
+        }
+    }
+
+    #[test]
+    fn mip_filters_parse_and_round_trip() {
+        assert!("bicubic".parse::<MipFilter>().is_err());
+        for filter in [MipFilter::Box, MipFilter::Kaiser, MipFilter::Lanczos] {
+            assert_eq!(filter.to_string().parse(), Ok(filter));
+        }
+    }
+
+    #[test]
+    fn alpha_test_ref_must_be_a_unit_value() {
+        assert_eq!(validate_alpha_test_ref(0.5), Ok(0.5));
+        assert!(validate_alpha_test_ref(1.5).is_err());
+        assert!(validate_alpha_test_ref(-0.1).is_err());
+        assert!(validate_alpha_test_ref(f32::NAN).is_err());
+    }
+
+    #[test]
+    fn formats_parse_case_insensitively_and_round_trip() {
+        assert_eq!(
+            "bc7rgbaunormsrgb".parse(),
+            Ok(DdsFormat(ImageFormat::BC7RgbaUnormSrgb))
+        );
+        assert!("BC8".parse::<DdsFormat>().is_err());
+        for format in ImageFormat::iter() {
+            assert_eq!(DdsFormat(format).to_string().parse(), Ok(DdsFormat(format)));
         }
     }
 }
@@ -0,0 +1,136 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/structs/struct_fileinfo.rs
+
+use serde::Serialize;
+
+/// Details reported by the `info` command for a single file.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum FileInfo {
+    /// A DDS file, described from its header.
+    Dds(Box<DdsInfo>),
+
+    /// Any other supported image.
+    Image(ImageInfo),
+
+    /// A file that could not be read.
+    Error {
+        /// Path of the file.
+        path: String,
+
+        /// Why the file could not be read.
+        error: String,
+    },
+}
+
+/// Header fields of a DDS file.
+///
+/// Values are reported as stored in the file, so that broken headers can be
+/// spotted; `None` means the field is not set or not present.
+#[derive(Debug, Serialize)]
+pub struct DdsInfo {
+    /// Path of the file.
+    pub path: String,
+
+    /// Size of the file in bytes.
+    pub file_size: u64,
+
+    /// Header type, `DX10` or `Legacy`.
+    pub header: String,
+
+    /// Width of the base level in pixels.
+    pub width: u32,
+
+    /// Height of the base level in pixels.
+    pub height: u32,
+
+    /// Depth of volume textures, 1 otherwise.
+    pub depth: u32,
+
+    /// Number of mip levels, including the base level.
+    pub mipmap_count: u32,
+
+    /// Number of array layers (6 per cubemap).
+    pub array_size: u32,
+
+    /// Format name as understood by the converter, if supported.
+    pub format: Option<String>,
+
+    /// FourCC code of the legacy pixel format (`DX10` for extended headers).
+    pub fourcc: Option<String>,
+
+    /// DXGI format of the DX10 header.
+    pub dxgi_format: Option<String>,
+
+    /// Direct3D 9 format matching a legacy pixel format.
+    pub d3d_format: Option<String>,
+
+    /// Flags of the legacy pixel format (e.g., `FOURCC`, `RGB`, `ALPHA_PIXELS`).
+    pub pixel_format_flags: Vec<String>,
+
+    /// Bits per pixel of uncompressed legacy formats.
+    pub rgb_bit_count: Option<u32>,
+
+    /// Red, green, blue and alpha bit masks of uncompressed legacy formats.
+    pub bit_masks: Option<[u32; 4]>,
+
+    /// Surface complexity flags (`TEXTURE`, `MIPMAP`, `COMPLEX`).
+    pub caps: Vec<String>,
+
+    /// Cubemap and volume flags.
+    pub caps2: Vec<String>,
+
+    /// Whether the texture is a cubemap.
+    pub cubemap: bool,
+
+    /// Resource dimension of the DX10 header.
+    pub resource_dimension: Option<String>,
+
+    /// Miscellaneous flags of the DX10 header (e.g., `TEXTURECUBE`).
+    pub misc_flags: Vec<String>,
+
+    /// Alpha mode of the DX10 header.
+    pub alpha_mode: Option<String>,
+
+    /// Row pitch of uncompressed formats as stored in the header.
+    pub pitch: Option<u32>,
+
+    /// Size of the base level of compressed formats as stored in the header.
+    pub linear_size: Option<u32>,
+
+    /// Number of meaningful channels.
+    pub channels: u8,
+
+    /// Size of the pixel data in bytes.
+    pub data_size: usize,
+
+    /// Size of the pixel data the header describes, if it can be computed.
+    pub expected_data_size: Option<u64>,
+}
+
+/// Details of a non-DDS image.
+#[derive(Debug, Serialize)]
+pub struct ImageInfo {
+    /// Path of the file.
+    pub path: String,
+
+    /// Size of the file in bytes.
+    pub file_size: u64,
+
+    /// Detected file type (e.g., `PNG`).
+    pub file_type: String,
+
+    /// Width in pixels.
+    pub width: u32,
+
+    /// Height in pixels.
+    pub height: u32,
+
+    /// Number of meaningful channels.
+    pub channels: u8,
+
+    /// Color space declared by the file, if any.
+    pub color_space: Option<String>,
+
+    /// Texture role derived from the file name suffix.
+    pub role: String,
+}
@@ -1,6 +1,7 @@
 // D:/Work/Projects/Modding/Skyrim/dds-edit/src/structs/struct_filetype.rs
 
 /// Enumeration of supported image file types.
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Clone, Copy)]
 pub enum FileType {
     /// Represents an unknown or unsupported file type.
@@ -11,6 +12,74 @@ pub enum FileType {
 
     /// Represents PNG file type.
     PNG,
+
+    /// Represents DDS (DirectDraw Surface) file type.
+    DDS,
+
+    /// Represents Truevision TGA file type.
+    TGA,
+
+    /// Represents Windows bitmap file type.
+    BMP,
+
+    /// Represents TIFF file type.
+    TIFF,
+
+    /// Represents WebP file type.
+    WEBP,
+
+    /// Represents Photoshop document file type (flattened composite only).
+    PSD,
+}
+
+impl FileType {
+    /// Returns the file extensions of all supported input file types.
+    ///
+    /// # Returns
+    ///
+    /// * `&'static [&'static str]` - The lowercase extensions without a leading dot.
+    pub fn supported_extensions() -> &'static [&'static str] {
+        &[
+            "jpg", "jpeg", "png", "dds", "tga", "bmp", "tif", "tiff", "webp", "psd", "psb",
+        ]
+    }
+
+    /// Detects the file type from the magic bytes at the start of a file.
+    ///
+    /// TGA files have no magic bytes and are only recognised by extension.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The first bytes of the file (at least 12 for WebP).
+    ///
+    /// # Returns
+    ///
+    /// * `Some(FileType)` if the magic bytes match a supported type.
+    /// * `None` otherwise.
+    pub fn from_magic(header: &[u8]) -> Option<Self> {
+        match header {
+            [0x89, b'P', b'N', b'G', ..] => Some(FileType::PNG),
+            [0xFF, 0xD8, 0xFF, ..] => Some(FileType::JPG),
+            [b'D', b'D', b'S', b' ', ..] => Some(FileType::DDS),
+            [b'B', b'M', ..] => Some(FileType::BMP),
+            [b'I', b'I', 0x2A, 0x00, ..] | [b'M', b'M', 0x00, 0x2A, ..] => Some(FileType::TIFF),
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => {
+                Some(FileType::WEBP)
+            }
+            [b'8', b'B', b'P', b'S', ..] => Some(FileType::PSD),
+            _ => None,
+        }
+    }
+
+    /// Returns the file type used when saving intermediate resized images.
+    ///
+    /// Types that cannot be written back (DDS, PSD) are saved as PNG.
+    pub fn intermediate(&self) -> Self {
+        match self {
+            FileType::DDS | FileType::PSD | FileType::UNKNOWN => FileType::PNG,
+            other => *other,
+        }
+    }
 }
 
 impl From<&str> for FileType {
@@ -27,6 +96,12 @@ impl From<&str> for FileType {
         match file_ext.to_lowercase().as_str() {
             "jpg" | "jpeg" => FileType::JPG,
             "png" => FileType::PNG,
+            "dds" => FileType::DDS,
+            "tga" => FileType::TGA,
+            "bmp" => FileType::BMP,
+            "tif" | "tiff" => FileType::TIFF,
+            "webp" => FileType::WEBP,
+            "psd" | "psb" => FileType::PSD,
             _ => FileType::UNKNOWN,
         }
     }
@@ -47,6 +122,12 @@ impl std::fmt::Display for FileType {
             FileType::UNKNOWN => write!(f, "Unknown"),
             FileType::JPG => write!(f, "JPG"),
             FileType::PNG => write!(f, "PNG"),
+            FileType::DDS => write!(f, "DDS"),
+            FileType::TGA => write!(f, "TGA"),
+            FileType::BMP => write!(f, "BMP"),
+            FileType::TIFF => write!(f, "TIFF"),
+            FileType::WEBP => write!(f, "WEBP"),
+            FileType::PSD => write!(f, "PSD"),
         }
     }
 }
@@ -0,0 +1,233 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/structs/struct_gameprofile.rs
+
+use image_dds::ImageFormat;
+use serde::{Deserialize, Serialize};
+
+/// Target game the DDS outputs are written for.
+///
+/// A profile constrains the formats the game can load and decides whether
+/// outputs must use legacy (pre-DX10) DDS headers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum GameProfile {
+    /// Skyrim (2011): Direct3D 9, legacy headers only, no BC6H/BC7.
+    SkyrimLe,
+
+    /// Skyrim Special Edition: Direct3D 11, supports BC7 but not BC6H.
+    SkyrimSe,
+
+    /// Fallout 4: Direct3D 11, supports BC7 but not BC6H.
+    Fallout4,
+
+    /// Any DX10 capable engine; every format is allowed (the default).
+    Dx10,
+}
+
+impl GameProfile {
+    /// Returns `true` if the game can only read legacy DDS headers.
+    pub fn requires_legacy_headers(&self) -> bool {
+        matches!(self, GameProfile::SkyrimLe)
+    }
+
+    /// Returns `true` if the game can load BC7 textures.
+    pub fn supports_bc7(&self) -> bool {
+        self.constrain_format(ImageFormat::BC7RgbaUnorm) == ImageFormat::BC7RgbaUnorm
+    }
+
+    /// Replaces a format the game cannot load with the closest one it can.
+    ///
+    /// The sRGB-ness of the requested format is kept so that color data is
+    /// still encoded in the color space the game expects.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - The requested DDS format.
+    ///
+    /// # Returns
+    ///
+    /// * `ImageFormat` - `format` itself if the game supports it, otherwise its fallback.
+    pub fn constrain_format(&self, format: ImageFormat) -> ImageFormat {
+        match self {
+            GameProfile::SkyrimLe => match format {
+                // Legacy headers only have FourCCs for DXT1-5, ATI1, ATI2 and uncompressed BGRA.
+                ImageFormat::BC1RgbaUnorm
+                | ImageFormat::BC1RgbaUnormSrgb
+                | ImageFormat::BC2RgbaUnorm
+                | ImageFormat::BC2RgbaUnormSrgb
+                | ImageFormat::BC3RgbaUnorm
+                | ImageFormat::BC3RgbaUnormSrgb
+                | ImageFormat::BC4RUnorm
+                | ImageFormat::BC4RSnorm
+                | ImageFormat::BC5RgUnorm
+                | ImageFormat::Bgra8Unorm
+                | ImageFormat::Bgra8UnormSrgb => format,
+                ImageFormat::BC7RgbaUnormSrgb => ImageFormat::BC3RgbaUnormSrgb,
+                ImageFormat::BC7RgbaUnorm
+                | ImageFormat::BC6hRgbUfloat
+                | ImageFormat::BC6hRgbSfloat => ImageFormat::BC3RgbaUnorm,
+                ImageFormat::BC5RgSnorm => ImageFormat::BC5RgUnorm,
+                ImageFormat::Rgba8UnormSrgb => ImageFormat::Bgra8UnormSrgb,
+                _ => ImageFormat::Bgra8Unorm,
+            },
+            GameProfile::SkyrimSe | GameProfile::Fallout4 => match format {
+                ImageFormat::BC6hRgbUfloat | ImageFormat::BC6hRgbSfloat => {
+                    ImageFormat::BC7RgbaUnorm
+                }
+                _ => format,
+            },
+            GameProfile::Dx10 => format,
+        }
+    }
+
+    /// Returns the format a DDS reader reports for an output written in `format`.
+    ///
+    /// Legacy headers cannot mark data as sRGB, so the linear variant is read back.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - The format the output was encoded in.
+    pub fn header_format(&self, format: ImageFormat) -> ImageFormat {
+        if !self.requires_legacy_headers() {
+            return format;
+        }
+        match format {
+            ImageFormat::BC1RgbaUnormSrgb => ImageFormat::BC1RgbaUnorm,
+            ImageFormat::BC2RgbaUnormSrgb => ImageFormat::BC2RgbaUnorm,
+            ImageFormat::BC3RgbaUnormSrgb => ImageFormat::BC3RgbaUnorm,
+            ImageFormat::Bgra8UnormSrgb => ImageFormat::Bgra8Unorm,
+            _ => format,
+        }
+    }
+}
+
+impl TryFrom<String> for GameProfile {
+    type Error = String;
+
+    /// Converts a profile name to a `GameProfile`.
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        name.parse()
+    }
+}
+
+impl std::str::FromStr for GameProfile {
+    type Err = String;
+
+    /// Parses a profile from its name (`skyrim-le`, `skyrim-se`, `fallout4` or `dx10`),
+    /// ignoring case and treating spaces and `_` as `-`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace(['_', ' '], "-").as_str() {
+            "skyrim-le" | "le" | "tesv" => Ok(GameProfile::SkyrimLe),
+            "skyrim-se" | "se" | "sse" | "skyrim-ae" => Ok(GameProfile::SkyrimSe),
+            "fallout4" | "fallout-4" | "fo4" => Ok(GameProfile::Fallout4),
+            "dx10" | "generic" | "generic-dx10" => Ok(GameProfile::Dx10),
+            _ => Err(format!(
+                "Invalid game '{}' (expected skyrim-le, skyrim-se, fallout4 or dx10)",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for GameProfile {
+    /// Formats the `GameProfile` for display purposes.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            GameProfile::SkyrimLe => write!(f, "Skyrim LE"),
+            GameProfile::SkyrimSe => write!(f, "Skyrim SE"),
+            GameProfile::Fallout4 => write!(f, "Fallout 4"),
+            GameProfile::Dx10 => write!(f, "Generic DX10"),
+        }
+    }
+}
+
+impl From<GameProfile> for String {
+    /// Converts the `GameProfile` to its display form, which is how it is serialized.
+    fn from(game: GameProfile) -> Self {
+        game.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_names_and_aliases() {
+        assert_eq!("skyrim-le".parse(), Ok(GameProfile::SkyrimLe));
+        assert_eq!("SSE".parse(), Ok(GameProfile::SkyrimSe));
+        assert_eq!("fallout_4".parse(), Ok(GameProfile::Fallout4));
+        assert_eq!(" dx10 ".parse(), Ok(GameProfile::Dx10));
+        assert!("oblivion".parse::<GameProfile>().is_err());
+    }
+
+    #[test]
+    fn display_names_round_trip() {
+        for game in [
+            GameProfile::SkyrimLe,
+            GameProfile::SkyrimSe,
+            GameProfile::Fallout4,
+            GameProfile::Dx10,
+        ] {
+            assert_eq!(String::from(game).parse(), Ok(game));
+        }
+    }
+
+    #[test]
+    fn skyrim_le_keeps_legacy_formats() {
+        let game = GameProfile::SkyrimLe;
+        for format in [
+            ImageFormat::BC1RgbaUnormSrgb,
+            ImageFormat::BC3RgbaUnorm,
+            ImageFormat::BC4RUnorm,
+            ImageFormat::BC5RgUnorm,
+            ImageFormat::Bgra8Unorm,
+        ] {
+            assert_eq!(game.constrain_format(format), format);
+        }
+        assert_eq!(
+            game.constrain_format(ImageFormat::BC7RgbaUnormSrgb),
+            ImageFormat::BC3RgbaUnormSrgb
+        );
+        assert_eq!(
+            game.constrain_format(ImageFormat::BC6hRgbUfloat),
+            ImageFormat::BC3RgbaUnorm
+        );
+        assert_eq!(
+            game.constrain_format(ImageFormat::Rgba8UnormSrgb),
+            ImageFormat::Bgra8UnormSrgb
+        );
+        assert!(!game.supports_bc7());
+    }
+
+    #[test]
+    fn dx11_games_only_replace_bc6h() {
+        for game in [GameProfile::SkyrimSe, GameProfile::Fallout4] {
+            assert_eq!(
+                game.constrain_format(ImageFormat::BC6hRgbSfloat),
+                ImageFormat::BC7RgbaUnorm
+            );
+            assert_eq!(
+                game.constrain_format(ImageFormat::BC4RUnorm),
+                ImageFormat::BC4RUnorm
+            );
+            assert!(game.supports_bc7());
+        }
+    }
+
+    #[test]
+    fn legacy_headers_read_back_linear() {
+        let game = GameProfile::SkyrimLe;
+        assert_eq!(
+            game.header_format(ImageFormat::BC1RgbaUnormSrgb),
+            ImageFormat::BC1RgbaUnorm
+        );
+        assert_eq!(
+            game.header_format(ImageFormat::BC4RUnorm),
+            ImageFormat::BC4RUnorm
+        );
+        assert_eq!(
+            GameProfile::SkyrimSe.header_format(ImageFormat::BC1RgbaUnormSrgb),
+            ImageFormat::BC1RgbaUnormSrgb
+        );
+    }
+}
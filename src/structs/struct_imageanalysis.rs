@@ -0,0 +1,47 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/structs/struct_imageanalysis.rs
+
+/// Enumeration of how an image uses its alpha channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlphaContent {
+    /// Alpha is fully opaque everywhere, or the image has no alpha.
+    Opaque,
+
+    /// Alpha is only fully transparent or fully opaque (cutouts).
+    Binary,
+
+    /// Alpha uses intermediate values.
+    Gradient,
+}
+
+impl std::fmt::Display for AlphaContent {
+    /// Formats the `AlphaContent` for display purposes.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            AlphaContent::Opaque => write!(f, "Opaque"),
+            AlphaContent::Binary => write!(f, "1-bit"),
+            AlphaContent::Gradient => write!(f, "Gradient"),
+        }
+    }
+}
+
+/// Result of analyzing the pixels of an image, used to pick the cheapest
+/// DDS format that preserves its content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageAnalysis {
+    /// How the alpha channel is used.
+    pub alpha: AlphaContent,
+
+    /// The red, green and blue channels are equal in every pixel.
+    pub grayscale: bool,
+}
+
+impl std::fmt::Display for ImageAnalysis {
+    /// Formats the `ImageAnalysis` for display purposes (e.g., `Alpha = 1-bit, Grayscale`).
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Alpha = {}", self.alpha)?;
+        if self.grayscale {
+            write!(f, ", Grayscale")?;
+        }
+        Ok(())
+    }
+}
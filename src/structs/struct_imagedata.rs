@@ -1,7 +1,10 @@
 // D:/Work/Projects/Modding/Skyrim/dds-edit/src/structs/struct_imagedata.rs
 
+use super::{
+    struct_colorspace::ColorSpace, struct_filetype::FileType, struct_imageanalysis::ImageAnalysis,
+    struct_texturerole::TextureRole,
+};
 use std::path::{Path, PathBuf};
-use super::struct_filetype::FileType;
 
 /// Struct to hold image data.
 ///
@@ -17,11 +20,23 @@ pub struct ImageData {
     /// File name without extension.
     pub file_name: String,
 
+    /// File extension as found on disk, without the leading dot.
+    pub file_extension: String,
+
     /// File type (e.g., JPG, PNG).
     pub file_type: FileType,
 
     /// Number of color channels in the image (e.g., 3 for RGB, 4 for RGBA).
     pub channels: u8,
+
+    /// Texture role derived from the file name suffix (e.g., Diffuse, Normal).
+    pub role: TextureRole,
+
+    /// Color space the image data is encoded in.
+    pub color_space: ColorSpace,
+
+    /// Alpha and color content of the pixels; when set, it refines the role-based format.
+    pub analysis: Option<ImageAnalysis>,
 }
 
 impl From<(PathBuf, PathBuf)> for ImageData {
@@ -36,26 +51,56 @@ impl From<(PathBuf, PathBuf)> for ImageData {
     /// * `Self` - An instance of `ImageData` populated with metadata.
     fn from(paths: (PathBuf, PathBuf)) -> Self {
         // Extract directory paths, defaulting to an empty path if unavailable.
-        let file_path_in = paths.0.parent().unwrap_or_else(|| Path::new("")).to_path_buf();
-        let file_path_out = paths.1.parent().unwrap_or_else(|| Path::new("")).to_path_buf();
+        let file_path_in = paths
+            .0
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .to_path_buf();
+        let file_path_out = paths
+            .1
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .to_path_buf();
 
         // Extract the file stem (name without extension).
-        let file_name = paths.0.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let file_name = paths
+            .0
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
 
         // Determine the file type based on the file extension.
         let filetype_string = paths.0.extension().unwrap_or_default().to_string_lossy();
         let file_type = FileType::from(filetype_string.as_ref());
+        let file_extension = filetype_string.to_string();
 
         ImageData {
             file_path_in,
             file_path_out,
             file_name,
+            file_extension,
             file_type,
             channels: 0, // Default to 0; will be updated after image loading.
+            role: TextureRole::Diffuse, // Updated once the configuration is known.
+            color_space: ColorSpace::Srgb, // Updated after image loading.
+            analysis: None, // Updated once the pixels have been analyzed.
         }
     }
 }
 
+impl ImageData {
+    /// Returns the full path of the input file.
+    ///
+    /// # Returns
+    ///
+    /// * `PathBuf` - The input directory joined with the file name and original extension.
+    pub fn input_path(&self) -> PathBuf {
+        self.file_path_in
+            .join(format!("{}.{}", self.file_name, self.file_extension))
+    }
+}
+
 impl std::fmt::Display for ImageData {
     /// Formats the `ImageData` for display purposes.
     ///
@@ -69,8 +114,14 @@ impl std::fmt::Display for ImageData {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(
             f,
-            "ImageData {{\n  file_path_in: {:?},\n  file_path_out: {:?},\n  file_name: {},\n  file_type: {},\n  channels: {}\n}}",
-            self.file_path_in, self.file_path_out, self.file_name, self.file_type, self.channels
+            "ImageData {{\n  file_path_in: {:?},\n  file_path_out: {:?},\n  file_name: {},\n  file_type: {},\n  channels: {},\n  role: {},\n  color_space: {}\n}}",
+            self.file_path_in,
+            self.file_path_out,
+            self.file_name,
+            self.file_type,
+            self.channels,
+            self.role,
+            self.color_space
         )
     }
 }
@@ -0,0 +1,72 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/structs/struct_normalgen.rs
+
+use serde::{Deserialize, Serialize};
+
+use super::struct_ddsconfig::DdsFormat;
+
+/// Gradient kernel used to derive slopes from a height map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GradientKernel {
+    /// 3x3 Sobel operator.
+    Sobel,
+
+    /// 3x3 Scharr operator, more rotationally symmetric than Sobel.
+    Scharr,
+}
+
+impl GradientKernel {
+    /// Returns the weights of the kernel's outer and center rows.
+    ///
+    /// The horizontal gradient is `outer * (row above + row below) + center * row`
+    /// of the differences between the right and left neighbours; the weights
+    /// are normalized so a slope of one height unit per pixel yields 1.
+    pub fn weights(&self) -> (f32, f32) {
+        match self {
+            GradientKernel::Sobel => (1.0 / 8.0, 2.0 / 8.0),
+            GradientKernel::Scharr => (3.0 / 32.0, 10.0 / 32.0),
+        }
+    }
+}
+
+impl std::fmt::Display for GradientKernel {
+    /// Formats the `GradientKernel` for display purposes.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            GradientKernel::Sobel => write!(f, "Sobel"),
+            GradientKernel::Scharr => write!(f, "Scharr"),
+        }
+    }
+}
+
+/// Settings for generating normal maps from height maps.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NormalGenConfig {
+    /// Scale of the height differences; higher values give steeper normals.
+    pub strength: f32,
+
+    /// Gradient kernel.
+    pub kernel: GradientKernel,
+
+    /// Sample across the opposite edge at borders, for tiling textures.
+    pub wrap: bool,
+
+    /// Suffix replacing the height map suffix in the output name (e.g., `_n`).
+    pub output: String,
+
+    /// Explicit DDS format; when unset the normal map format is picked by role.
+    pub format: Option<DdsFormat>,
+}
+
+impl Default for NormalGenConfig {
+    fn default() -> Self {
+        Self {
+            strength: 2.0,
+            kernel: GradientKernel::Sobel,
+            wrap: false,
+            output: "_n".to_string(),
+            format: None,
+        }
+    }
+}
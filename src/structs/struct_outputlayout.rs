@@ -0,0 +1,208 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/structs/struct_outputlayout.rs
+
+use serde::{Deserialize, Serialize};
+
+use super::struct_sizespec::SizeSpec;
+
+/// Placeholders accepted in naming templates.
+pub const TEMPLATE_PLACEHOLDERS: &[&str] = &[
+    "stem", "size", "width", "height", "role", "format", "relpath",
+];
+
+/// How outputs are arranged inside the output directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputLayout {
+    /// Mirror the input directory structure (`textures/armor/cuirass_2048.dds`).
+    Mirrored,
+
+    /// Write every output directly into the output directory.
+    Flat,
+
+    /// Mirror the input structure below one folder per target size
+    /// (`2K/textures/armor/cuirass.dds`), as mod managers expect for resolution options.
+    PerSize,
+}
+
+impl OutputLayout {
+    /// Returns the naming template used when none is configured.
+    ///
+    /// Per-size folders already tell the sizes apart, so they keep the original
+    /// file names; the other layouts append the target size. The flat layout
+    /// also prefixes the input directory, so that inputs with the same name in
+    /// different folders do not overwrite each other.
+    pub fn default_template(&self) -> &'static str {
+        match self {
+            OutputLayout::Mirrored => "{stem}_{size}",
+            OutputLayout::Flat => "{relpath}_{stem}_{size}",
+            OutputLayout::PerSize => "{stem}",
+        }
+    }
+}
+
+impl std::str::FromStr for OutputLayout {
+    type Err = String;
+
+    /// Parses an output layout (`mirrored`, `flat` or `per_size`), ignoring case, spaces, `-` and `_`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s
+            .trim()
+            .to_lowercase()
+            .replace(['-', '_', ' '], "")
+            .as_str()
+        {
+            "mirrored" => Ok(OutputLayout::Mirrored),
+            "flat" => Ok(OutputLayout::Flat),
+            "persize" => Ok(OutputLayout::PerSize),
+            _ => Err(format!(
+                "Invalid output layout '{}' (expected mirrored, flat or per_size)",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for OutputLayout {
+    /// Formats the `OutputLayout` for display purposes.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            OutputLayout::Mirrored => write!(f, "Mirrored"),
+            OutputLayout::Flat => write!(f, "Flat"),
+            OutputLayout::PerSize => write!(f, "Per Size"),
+        }
+    }
+}
+
+/// Checks that a naming template is well-formed and only uses known placeholders.
+///
+/// # Arguments
+///
+/// * `template` - The template (e.g., `{stem}_{width}x{height}`).
+///
+/// # Returns
+///
+/// * `Ok(())` if the template is valid.
+/// * `Err(String)` with an error message if it is not.
+pub fn validate_name_template(template: &str) -> Result<(), String> {
+    let mut rest = template;
+    let mut has_name = false;
+    while let Some(start) = rest.find(['{', '}']) {
+        if rest[start..].starts_with('}') {
+            return Err(format!(
+                "Naming template '{}' has an unmatched '}}'",
+                template
+            ));
+        }
+        has_name |= !rest[..start].trim_matches('/').is_empty();
+        let Some(end) = rest[start..].find('}') else {
+            return Err(format!(
+                "Naming template '{}' has an unmatched '{{'",
+                template
+            ));
+        };
+        let placeholder = &rest[start + 1..start + end];
+        if !TEMPLATE_PLACEHOLDERS.contains(&placeholder) {
+            return Err(format!(
+                "Unknown placeholder '{{{}}}' in naming template '{}' (expected one of {})",
+                placeholder,
+                template,
+                TEMPLATE_PLACEHOLDERS
+                    .iter()
+                    .map(|name| format!("{{{}}}", name))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        has_name |= placeholder != "relpath";
+        rest = &rest[start + end + 1..];
+    }
+    has_name |= !rest.trim_matches('/').is_empty();
+    if !has_name || template.split('/').any(|part| part == "..") {
+        return Err(format!(
+            "Naming template '{}' does not name a file",
+            template
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that a naming template gives every target size its own output.
+///
+/// # Arguments
+///
+/// * `layout` - The output layout.
+/// * `template` - The naming template.
+/// * `target_sizes` - The target sizes written with the template.
+///
+/// # Returns
+///
+/// * `Ok(())` if the outputs cannot collide.
+/// * `Err(String)` with an error message if several sizes would share a file.
+pub fn validate_name_collisions(
+    layout: OutputLayout,
+    template: &str,
+    target_sizes: &[SizeSpec],
+) -> Result<(), String> {
+    let distinguishes_sizes = ["{size}", "{width}", "{height}"]
+        .iter()
+        .any(|placeholder| template.contains(placeholder));
+    if layout != OutputLayout::PerSize && target_sizes.len() > 1 && !distinguishes_sizes {
+        return Err(format!(
+            "Naming template '{}' gives every target size the same file name; \
+             add {{size}}, {{width}} or {{height}}, or use the per_size layout",
+            template
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layouts_parse_and_round_trip() {
+        assert_eq!("per-size".parse(), Ok(OutputLayout::PerSize));
+        assert_eq!("FLAT".parse(), Ok(OutputLayout::Flat));
+        assert!("nested".parse::<OutputLayout>().is_err());
+        for layout in [
+            OutputLayout::Mirrored,
+            OutputLayout::Flat,
+            OutputLayout::PerSize,
+        ] {
+            assert_eq!(layout.to_string().parse(), Ok(layout));
+            assert!(validate_name_template(layout.default_template()).is_ok());
+        }
+    }
+
+    #[test]
+    fn validates_templates() {
+        for template in [
+            "{stem}_{width}x{height}",
+            "{size}/{relpath}/{stem}",
+            "{role}/{stem}",
+        ] {
+            assert!(validate_name_template(template).is_ok(), "{}", template);
+        }
+        for template in [
+            "{stem",
+            "stem}",
+            "{name}",
+            "{relpath}",
+            "{relpath}/",
+            "../{stem}",
+            "",
+        ] {
+            assert!(validate_name_template(template).is_err(), "{}", template);
+        }
+    }
+
+    #[test]
+    fn rejects_templates_that_collide_across_sizes() {
+        let sizes = [SizeSpec::Width(1024), SizeSpec::Width(2048)];
+        assert!(validate_name_collisions(OutputLayout::Mirrored, "{stem}", &sizes).is_err());
+        assert!(validate_name_collisions(OutputLayout::Mirrored, "{stem}_{width}", &sizes).is_ok());
+        assert!(validate_name_collisions(OutputLayout::PerSize, "{stem}", &sizes).is_ok());
+        assert!(validate_name_collisions(OutputLayout::Flat, "{stem}", &sizes[..1]).is_ok());
+    }
+}
@@ -0,0 +1,41 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/structs/struct_packageconfig.rs
+
+use serde::Deserialize;
+
+use super::struct_sizespec::SizeSpec;
+
+/// Metadata of the FOMOD installer generated for a multi-resolution mod.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PackageConfig {
+    /// Mod name; the name of the input directory when empty.
+    pub name: String,
+
+    /// Mod author.
+    pub author: String,
+
+    /// Mod version.
+    pub version: String,
+
+    /// Description shown by the mod manager.
+    pub description: String,
+
+    /// Optional mod page URL.
+    pub website: Option<String>,
+
+    /// Target size preselected in the installer; the first target size when unset.
+    pub default_size: Option<SizeSpec>,
+}
+
+impl Default for PackageConfig {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            author: String::new(),
+            version: "1.0".to_string(),
+            description: String::new(),
+            website: None,
+            default_size: None,
+        }
+    }
+}
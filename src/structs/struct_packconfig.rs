@@ -0,0 +1,228 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/structs/struct_packconfig.rs
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    struct_channelop::Channel, struct_ddsconfig::DdsFormat, struct_texturerole::TextureRole,
+};
+
+/// A texture packed from the channels of several source maps.
+///
+/// Sources are found by file name suffix next to each other: with
+/// `output = "_rmaos"` and `r = "_rough"`, `armor_rough.png` feeds the red
+/// channel of `armor_rmaos`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PackConfig {
+    /// Suffix of the packed texture (e.g., `_rmaos`).
+    pub output: String,
+
+    /// Source of the red channel.
+    pub r: Option<PackSource>,
+
+    /// Source of the green channel.
+    pub g: Option<PackSource>,
+
+    /// Source of the blue channel.
+    pub b: Option<PackSource>,
+
+    /// Source of the alpha channel; without a source map the packed texture has no alpha.
+    pub a: Option<PackSource>,
+
+    /// Texture role of the packed texture; classified from `output` when unset.
+    pub role: Option<TextureRole>,
+
+    /// Explicit DDS format of the packed texture.
+    pub format: Option<DdsFormat>,
+
+    /// Also convert the source maps on their own.
+    #[serde(default)]
+    pub keep_sources: bool,
+}
+
+impl PackConfig {
+    /// Returns the sources of the red, green, blue and alpha channels.
+    pub fn channels(&self) -> [Option<&PackSource>; 4] {
+        [
+            self.r.as_ref(),
+            self.g.as_ref(),
+            self.b.as_ref(),
+            self.a.as_ref(),
+        ]
+    }
+
+    /// Returns `true` if the packed texture has an alpha channel.
+    ///
+    /// That is the case for an alpha source map and for a constant alpha
+    /// below 1.0; a constant alpha of 1.0 is the same as no alpha.
+    pub fn has_alpha(&self) -> bool {
+        match self.a {
+            Some(PackSource::Map { .. }) => true,
+            Some(PackSource::Constant(value)) => value < 1.0,
+            None => false,
+        }
+    }
+
+    /// Returns the suffixes of all source maps, in channel order.
+    pub fn source_suffixes(&self) -> Vec<&str> {
+        self.channels()
+            .into_iter()
+            .flatten()
+            .filter_map(|source| match source {
+                PackSource::Map { suffix, .. } => Some(suffix.as_str()),
+                PackSource::Constant(_) => None,
+            })
+            .collect()
+    }
+
+    /// Checks that the pack reads at least one source map.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` if the pack is usable.
+    /// * `Err(String)` with an error message if it is not.
+    pub fn validate(&self) -> Result<(), String> {
+        let suffixes = self.source_suffixes();
+        if suffixes.is_empty() {
+            return Err(format!("Pack '{}' has no source maps", self.output));
+        }
+        if suffixes
+            .iter()
+            .any(|suffix| suffix.eq_ignore_ascii_case(&self.output))
+        {
+            return Err(format!("Pack '{}' reads its own output", self.output));
+        }
+        Ok(())
+    }
+}
+
+/// The source of one channel of a packed texture.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "PackSourceRepr", into = "PackSourceRepr")]
+pub enum PackSource {
+    /// A channel of the source map with this suffix, written as `"_rough"`
+    /// (red channel) or `"_spec.a"`.
+    Map {
+        /// File name suffix of the source map.
+        suffix: String,
+
+        /// Channel read from the source map.
+        channel: Channel,
+    },
+
+    /// A constant value between 0 and 1.
+    Constant(f32),
+}
+
+/// Raw TOML representation of a `PackSource`, either a suffix or a constant.
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum PackSourceRepr {
+    Constant(f32),
+    Map(String),
+}
+
+impl TryFrom<PackSourceRepr> for PackSource {
+    type Error = String;
+
+    /// Converts the raw TOML representation to a `PackSource`.
+    fn try_from(repr: PackSourceRepr) -> Result<Self, Self::Error> {
+        match repr {
+            PackSourceRepr::Constant(value) if (0.0..=1.0).contains(&value) => {
+                Ok(PackSource::Constant(value))
+            }
+            PackSourceRepr::Constant(value) => {
+                Err(format!("Pack constant {} must be between 0 and 1", value))
+            }
+            PackSourceRepr::Map(source) => {
+                // A trailing '.r', '.g', '.b' or '.a' selects the channel.
+                let (suffix, channel) = match source.rsplit_once('.') {
+                    Some((suffix, channel)) => {
+                        let channel = match channel.to_lowercase().as_str() {
+                            "r" => Channel::R,
+                            "g" => Channel::G,
+                            "b" => Channel::B,
+                            "a" => Channel::A,
+                            _ => {
+                                return Err(format!("Invalid channel in pack source '{}'", source))
+                            }
+                        };
+                        (suffix.to_string(), channel)
+                    }
+                    None => (source, Channel::R),
+                };
+                if suffix.is_empty() {
+                    return Err("Pack source suffix must not be empty".to_string());
+                }
+                Ok(PackSource::Map { suffix, channel })
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for PackSource {
+    /// Formats the `PackSource` in the syntax it is parsed from.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PackSource::Map { suffix, channel } => write!(f, "{}.{}", suffix, channel),
+            PackSource::Constant(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl From<PackSource> for PackSourceRepr {
+    /// Converts the `PackSource` to the raw TOML representation it is serialized as.
+    fn from(source: PackSource) -> Self {
+        match source {
+            PackSource::Constant(value) => PackSourceRepr::Constant(value),
+            map => PackSourceRepr::Map(map.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pack(toml: &str) -> PackConfig {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn parses_sources_and_round_trips() {
+        let pack = pack("output = \"_rmaos\"\nr = \"_rough\"\ng = \"_spec.A\"\nb = 0.25");
+        assert_eq!(
+            pack.r,
+            Some(PackSource::Map {
+                suffix: "_rough".to_string(),
+                channel: Channel::R
+            })
+        );
+        assert_eq!(pack.g.as_ref().unwrap().to_string(), "_spec.a");
+        assert_eq!(pack.b, Some(PackSource::Constant(0.25)));
+        assert_eq!(pack.source_suffixes(), ["_rough", "_spec"]);
+    }
+
+    #[test]
+    fn serialized_sources_parse_back() {
+        let pack = pack("output = \"_rmaos\"\nr = \"_rough\"\ng = \"_spec.A\"\nb = 0.25");
+        let serialized = toml::to_string(&pack).unwrap();
+        let parsed: PackConfig = toml::from_str(&serialized).unwrap();
+        assert_eq!([parsed.r, parsed.g, parsed.b], [pack.r, pack.g, pack.b]);
+    }
+
+    #[test]
+    fn rejects_invalid_sources() {
+        for source in ["1.5", "\"_spec.x\"", "\".a\""] {
+            let toml = format!("output = \"_rmaos\"\nr = {}", source);
+            assert!(toml::from_str::<PackConfig>(&toml).is_err(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn translucent_constant_alpha_counts_as_alpha() {
+        assert!(!pack("output = \"_p\"").has_alpha());
+        assert!(!pack("output = \"_p\"\na = 1.0").has_alpha());
+        assert!(pack("output = \"_p\"\na = 0.5").has_alpha());
+        assert!(pack("output = \"_p\"\na = \"_spec\"").has_alpha());
+    }
+}
@@ -0,0 +1,134 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/structs/struct_pathrule.rs
+
+use glob::{MatchOptions, Pattern};
+use serde::Deserialize;
+use std::path::Path;
+
+use super::{
+    struct_channelop::ChannelOp,
+    struct_colorspace::ColorSpace,
+    struct_ddsconfig::{validate_alpha_test_ref, DdsFormat, DdsQuality, MipFilter, MipmapSetting},
+    struct_normalgen::NormalGenConfig,
+    struct_resizeconfig::{ResizeFilter, SharpenConfig},
+    struct_sizespec::{Pow2Policy, SizeSpec, UpscalePolicy},
+    struct_texturerole::TextureRole,
+};
+
+/// Options used when matching rule patterns against input paths.
+///
+/// `*` does not cross directory separators (use `**` for that), and matching is
+/// case-insensitive since mod trees are usually authored on Windows.
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: false,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// The glob pattern of a rule, compiled once when the configuration is loaded.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "String")]
+pub struct RulePattern(Pattern);
+
+impl TryFrom<String> for RulePattern {
+    type Error = String;
+
+    /// Compiles a glob pattern, rejecting invalid ones rather than never matching.
+    fn try_from(pattern: String) -> Result<Self, Self::Error> {
+        Pattern::new(&pattern)
+            .map(RulePattern)
+            .map_err(|e| format!("Invalid rule pattern '{}': {}", pattern, e))
+    }
+}
+
+impl std::fmt::Display for RulePattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.0.as_str())
+    }
+}
+
+/// A glob-based rule overriding conversion settings for matching input files.
+///
+/// Any field left unset inherits the value from the global configuration.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PathRule {
+    /// Glob pattern matched against the input path relative to `path_in`
+    /// (e.g., `textures/actors/**/*_n.png`).
+    pub pattern: RulePattern,
+
+    /// List of target sizes for resizing images.
+    pub target_sizes: Option<Vec<SizeSpec>>,
+
+    /// Policy for target sizes larger than the source image.
+    pub upscale: Option<UpscalePolicy>,
+
+    /// Filter used to enlarge images.
+    pub upscale_filter: Option<ResizeFilter>,
+
+    /// Power-of-two policy.
+    pub pow2: Option<Pow2Policy>,
+
+    /// Resize filter.
+    pub resize_filter: Option<ResizeFilter>,
+
+    /// Unsharp mask applied after resizing.
+    pub sharpen: Option<SharpenConfig>,
+
+    /// Compression quality for DDS encoding.
+    pub quality: Option<DdsQuality>,
+
+    /// Mipmap generation setting.
+    pub mipmaps: Option<MipmapSetting>,
+
+    /// Mip filter.
+    pub mip_filter: Option<MipFilter>,
+
+    /// Alpha test reference whose coverage is preserved on every mip level.
+    pub alpha_test_ref: Option<f32>,
+
+    /// Explicit DDS format.
+    pub format: Option<DdsFormat>,
+
+    /// Texture role, replacing the suffix-based classification.
+    pub role: Option<TextureRole>,
+
+    /// Color space override for color textures.
+    pub color_space: Option<ColorSpace>,
+
+    /// Channel operations, replacing the global list.
+    pub channel_ops: Option<Vec<ChannelOp>>,
+
+    /// Normal map generation from height maps.
+    pub normal_from_height: Option<NormalGenConfig>,
+}
+
+impl PathRule {
+    /// Checks that the values of the rule are in range.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` if the rule is valid.
+    /// * `Err(String)` with an error message if it is not.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(alpha_test_ref) = self.alpha_test_ref {
+            validate_alpha_test_ref(alpha_test_ref)
+                .map_err(|e| format!("Invalid rule '{}': {}", self.pattern, e))?;
+        }
+        if let Some(sharpen) = &self.sharpen {
+            sharpen
+                .validate()
+                .map_err(|e| format!("Invalid rule '{}': {}", self.pattern, e))?;
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if the rule matches the given path.
+    ///
+    /// # Arguments
+    ///
+    /// * `relative_path` - The input path relative to `path_in`.
+    pub fn matches(&self, relative_path: &Path) -> bool {
+        // Normalise separators so patterns written with '/' work on every platform.
+        let path = relative_path.to_string_lossy().replace('\\', "/");
+        self.pattern.0.matches_with(&path, MATCH_OPTIONS)
+    }
+}
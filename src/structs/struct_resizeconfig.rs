@@ -0,0 +1,216 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/structs/struct_resizeconfig.rs
+
+use std::collections::BTreeMap;
+
+use image::imageops::FilterType;
+use serde::{Deserialize, Serialize};
+
+use super::struct_sizespec::SizeSpec;
+
+/// Filter used to resize images to the target widths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum ResizeFilter {
+    /// Nearest neighbor; keeps hard pixel edges, for pixel art.
+    Nearest,
+
+    /// Linear filter; smooth, slightly soft.
+    Triangle,
+
+    /// Cubic filter; sharper than triangle.
+    CatmullRom,
+
+    /// Gaussian filter; soft, without ringing.
+    Gaussian,
+
+    /// Lanczos with a window of 3; sharpest, may ring around hard edges.
+    Lanczos3,
+}
+
+impl From<ResizeFilter> for FilterType {
+    /// Converts a `ResizeFilter` to the corresponding `image::imageops::FilterType`.
+    fn from(filter: ResizeFilter) -> Self {
+        match filter {
+            ResizeFilter::Nearest => FilterType::Nearest,
+            ResizeFilter::Triangle => FilterType::Triangle,
+            ResizeFilter::CatmullRom => FilterType::CatmullRom,
+            ResizeFilter::Gaussian => FilterType::Gaussian,
+            ResizeFilter::Lanczos3 => FilterType::Lanczos3,
+        }
+    }
+}
+
+impl TryFrom<String> for ResizeFilter {
+    type Error = String;
+
+    /// Converts a filter name to a `ResizeFilter`.
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        name.parse()
+    }
+}
+
+impl std::str::FromStr for ResizeFilter {
+    type Err = String;
+
+    /// Parses a resize filter from a string (`nearest`, `triangle`, `catmullrom`,
+    /// `gaussian` or `lanczos3`), ignoring case, `-` and `_`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace(['-', '_'], "").as_str() {
+            "nearest" => Ok(ResizeFilter::Nearest),
+            "triangle" | "linear" => Ok(ResizeFilter::Triangle),
+            "catmullrom" | "cubic" => Ok(ResizeFilter::CatmullRom),
+            "gaussian" => Ok(ResizeFilter::Gaussian),
+            "lanczos3" | "lanczos" => Ok(ResizeFilter::Lanczos3),
+            _ => Err(format!(
+                "Invalid resize filter '{}' (expected nearest, triangle, catmull-rom, gaussian or lanczos3)",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for ResizeFilter {
+    /// Formats the `ResizeFilter` for display purposes.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ResizeFilter::Nearest => write!(f, "Nearest"),
+            ResizeFilter::Triangle => write!(f, "Triangle"),
+            ResizeFilter::CatmullRom => write!(f, "Catmull-Rom"),
+            ResizeFilter::Gaussian => write!(f, "Gaussian"),
+            ResizeFilter::Lanczos3 => write!(f, "Lanczos3"),
+        }
+    }
+}
+
+impl From<ResizeFilter> for String {
+    /// Converts the `ResizeFilter` to its display form, which is how it is serialized.
+    fn from(filter: ResizeFilter) -> Self {
+        filter.to_string()
+    }
+}
+
+/// Settings of the unsharp mask applied after resizing.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SharpenConfig {
+    /// Strength for target widths without an entry in `sizes`; 0 disables sharpening.
+    pub amount: f32,
+
+    /// Standard deviation of the blur, in pixels of the resized image.
+    pub radius: f32,
+
+    /// Minimum difference (0 to 1) from the blurred image a pixel needs to be sharpened,
+    /// so that flat areas and noise are left alone.
+    pub threshold: f32,
+
+    /// Strength per target size (e.g., `{ 512 = 0.8, "h:256" = 1.0 }`), overriding `amount`.
+    /// Keyed by the size spec as written, since TOML keys are strings.
+    pub sizes: BTreeMap<String, f32>,
+}
+
+impl Default for SharpenConfig {
+    fn default() -> Self {
+        Self {
+            amount: 0.5,
+            radius: 1.0,
+            threshold: 0.0,
+            sizes: BTreeMap::new(),
+        }
+    }
+}
+
+impl SharpenConfig {
+    /// Returns the strength for a target size.
+    ///
+    /// # Arguments
+    ///
+    /// * `spec` - The configured target size.
+    pub fn amount_for(&self, spec: SizeSpec) -> f32 {
+        self.sizes
+            .iter()
+            .find(|(size, _)| size.parse::<SizeSpec>().ok() == Some(spec))
+            .map(|(_, amount)| *amount)
+            .unwrap_or(self.amount)
+    }
+
+    /// Checks that the settings are in range.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` if the settings are valid.
+    /// * `Err(String)` with an error message if they are not.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.radius <= 0.0 {
+            return Err(format!(
+                "Sharpen radius {} must be greater than 0",
+                self.radius
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.threshold) {
+            return Err(format!(
+                "Sharpen threshold {} must be between 0 and 1",
+                self.threshold
+            ));
+        }
+        if let Some(size) = self
+            .sizes
+            .keys()
+            .find(|size| size.parse::<SizeSpec>().is_err())
+        {
+            return Err(format!("Sharpen size '{}' is not a target size", size));
+        }
+        if let Some(amount) = std::iter::once(&self.amount)
+            .chain(self.sizes.values())
+            .find(|amount| **amount < 0.0)
+        {
+            return Err(format!("Sharpen amount {} must not be negative", amount));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_parse_and_round_trip() {
+        assert_eq!("Catmull_Rom".parse(), Ok(ResizeFilter::CatmullRom));
+        assert_eq!("linear".parse(), Ok(ResizeFilter::Triangle));
+        assert_eq!("lanczos".parse(), Ok(ResizeFilter::Lanczos3));
+        assert!("bicubic-ish".parse::<ResizeFilter>().is_err());
+        for filter in [
+            ResizeFilter::Nearest,
+            ResizeFilter::Triangle,
+            ResizeFilter::CatmullRom,
+            ResizeFilter::Gaussian,
+            ResizeFilter::Lanczos3,
+        ] {
+            assert_eq!(filter.to_string().parse(), Ok(filter));
+        }
+    }
+
+    #[test]
+    fn sharpen_amount_is_looked_up_by_size() {
+        let sharpen: SharpenConfig =
+            toml::from_str("amount = 0.3\nsizes = { 512 = 0.8, \"h:256\" = 1.0 }").unwrap();
+        assert!(sharpen.validate().is_ok());
+        assert_eq!(sharpen.amount_for(SizeSpec::Width(512)), 0.8);
+        assert_eq!(sharpen.amount_for(SizeSpec::Height(256)), 1.0);
+        assert_eq!(sharpen.amount_for(SizeSpec::Width(1024)), 0.3);
+    }
+
+    #[test]
+    fn sharpen_settings_are_validated() {
+        for toml in [
+            "radius = 0.0",
+            "threshold = 1.5",
+            "amount = -0.1",
+            "sizes = { big = 0.5 }",
+            "sizes = { 512 = -1.0 }",
+        ] {
+            let sharpen: SharpenConfig = toml::from_str(toml).unwrap();
+            assert!(sharpen.validate().is_err(), "{}", toml);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/structs/struct_runreport.rs
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use super::struct_sizespec::UpscalePolicy;
+
+/// Current version of the run report layout.
+pub const RUN_REPORT_VERSION: u32 = 1;
+
+/// How the output of a target size was produced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResizeAction {
+    /// Resized to the target size, which fits within the source.
+    Resized,
+
+    /// Kept at the source size, since the target size is larger.
+    Clamped,
+
+    /// Enlarged to the target size with the upscale filter.
+    Upscaled,
+
+    /// Not written, since the target size is larger than the source.
+    Skipped,
+}
+
+impl std::fmt::Display for ResizeAction {
+    /// Formats the `ResizeAction` for display purposes.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ResizeAction::Resized => write!(f, "Resized"),
+            ResizeAction::Clamped => write!(f, "Clamped"),
+            ResizeAction::Upscaled => write!(f, "Upscaled"),
+            ResizeAction::Skipped => write!(f, "Skipped"),
+        }
+    }
+}
+
+/// Record of one target size of a processed image.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TargetRecord {
+    /// File name of the image without extension; differs from the input for generated normal maps.
+    pub image: String,
+
+    /// The target size as configured (e.g., `1024`, `h:512`).
+    pub size: String,
+
+    /// The upscale policy in effect for the image.
+    pub upscale: UpscalePolicy,
+
+    /// What was done for the target size.
+    pub action: ResizeAction,
+
+    /// Width of the output, unless skipped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+
+    /// Height of the output, unless skipped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+
+    /// DDS format of the output, unless skipped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+
+    /// The DDS file written; relative to `path_out` in the report.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<PathBuf>,
+}
+
+/// Files written for an input, with a record of each of its target sizes.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessedOutputs {
+    /// Every file written, including intermediate images.
+    pub files: Vec<PathBuf>,
+
+    /// One record per target size, including skipped ones.
+    pub targets: Vec<TargetRecord>,
+}
+
+impl ProcessedOutputs {
+    /// Appends the files and records of another image, e.g. a generated normal map.
+    pub fn extend(&mut self, other: ProcessedOutputs) {
+        self.files.extend(other.files);
+        self.targets.extend(other.targets);
+    }
+}
+
+/// Outcome of an input in a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputStatus {
+    /// Processed in this run.
+    Processed,
+
+    /// Skipped, since its contents and settings are unchanged.
+    Unchanged,
+
+    /// Processing failed.
+    Failed,
+}
+
+/// Report entry of a single input.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct InputReport {
+    /// Input path relative to `path_in`, using `/` separators.
+    pub input: String,
+
+    /// Outcome of the input.
+    pub status: InputStatus,
+
+    /// Files of the input relative to `path_out`, using `/` separators.
+    pub outputs: Vec<String>,
+
+    /// Target size records; only present for processed inputs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<TargetRecord>,
+}
+
+/// Summary of a conversion run, written to the output directory.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RunReport {
+    /// Layout version of the report.
+    pub version: u32,
+
+    /// Input directory of the run.
+    pub path_in: String,
+
+    /// Output directory of the run.
+    pub path_out: String,
+
+    /// Duration of the run in seconds.
+    pub elapsed_seconds: f64,
+
+    /// Entries of all inputs and packs, sorted by input path.
+    pub inputs: Vec<InputReport>,
+}
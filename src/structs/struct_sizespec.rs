@@ -0,0 +1,381 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/structs/struct_sizespec.rs
+
+use serde::{Deserialize, Serialize};
+
+/// A target size, given by one dimension or a scale factor; the other
+/// dimension follows from the aspect ratio of the source.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "SizeSpecRepr", into = "String")]
+pub enum SizeSpec {
+    /// Target width, written as `1024` or `w:1024`.
+    Width(u32),
+
+    /// Target height, written as `h:512`.
+    Height(u32),
+
+    /// Target size of the longer side, written as `max:2048`.
+    Max(u32),
+
+    /// Percentage of the source size, written as `50%`.
+    Scale(f32),
+}
+
+/// Raw TOML representation of a `SizeSpec`, either a width or a spec string.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SizeSpecRepr {
+    Width(u32),
+    Spec(String),
+}
+
+impl TryFrom<SizeSpecRepr> for SizeSpec {
+    type Error = String;
+
+    /// Converts the raw TOML representation to a `SizeSpec`.
+    fn try_from(repr: SizeSpecRepr) -> Result<Self, Self::Error> {
+        match repr {
+            SizeSpecRepr::Width(width) => SizeSpec::Width(width).validated(),
+            SizeSpecRepr::Spec(spec) => spec.parse(),
+        }
+    }
+}
+
+impl SizeSpec {
+    /// Returns the spec if its value is positive.
+    fn validated(self) -> Result<Self, String> {
+        let positive = match self {
+            SizeSpec::Width(size) | SizeSpec::Height(size) | SizeSpec::Max(size) => size > 0,
+            SizeSpec::Scale(percent) => percent > 0.0 && percent.is_finite(),
+        };
+        if positive {
+            Ok(self)
+        } else {
+            Err(format!("Target size '{}' must be greater than 0", self))
+        }
+    }
+
+    /// Returns the suffix appended to output file names (e.g., `1024`, `h512`,
+    /// `max2048`, `50pct`). Plain widths keep the historical `_{width}` names.
+    pub fn suffix(&self) -> String {
+        match self {
+            SizeSpec::Width(width) => width.to_string(),
+            SizeSpec::Height(height) => format!("h{}", height),
+            SizeSpec::Max(max) => format!("max{}", max),
+            SizeSpec::Scale(percent) => format!("{}pct", percent),
+        }
+    }
+
+    /// Returns the folder name of the spec in the per-size layout, with sizes
+    /// in multiples of 1024 written the way mod pages do (e.g., `2K`, `512`,
+    /// `h1K`, `max4K`, `50pct`).
+    pub fn folder_name(&self) -> String {
+        let size = |size: u32| {
+            if size >= 1024 && size.is_multiple_of(1024) {
+                format!("{}K", size / 1024)
+            } else {
+                size.to_string()
+            }
+        };
+        match self {
+            SizeSpec::Width(width) => size(*width),
+            SizeSpec::Height(height) => format!("h{}", size(*height)),
+            SizeSpec::Max(max) => format!("max{}", size(*max)),
+            SizeSpec::Scale(_) => self.suffix(),
+        }
+    }
+
+    /// Calculates the size of a source image resized to this spec, keeping the aspect ratio.
+    ///
+    /// The derived dimension is rounded to the nearest pixel and is at least 1.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - Width of the source image.
+    /// * `height` - Height of the source image.
+    ///
+    /// # Returns
+    ///
+    /// * `(u32, u32)` - The resized width and height.
+    pub fn dimensions(&self, width: u32, height: u32) -> (u32, u32) {
+        let scale = match *self {
+            SizeSpec::Width(target) => target as f64 / width as f64,
+            SizeSpec::Height(target) => target as f64 / height as f64,
+            SizeSpec::Max(target) => target as f64 / width.max(height) as f64,
+            SizeSpec::Scale(percent) => percent as f64 / 100.0,
+        };
+        let scaled = |size: u32| ((size as f64 * scale).round() as u32).max(1);
+
+        // Keep the specified dimension exact.
+        match *self {
+            SizeSpec::Width(target) => (target, scaled(height)),
+            SizeSpec::Height(target) => (scaled(width), target),
+            _ => (scaled(width), scaled(height)),
+        }
+    }
+}
+
+impl std::str::FromStr for SizeSpec {
+    type Err = String;
+
+    /// Parses a size spec (`1024`, `w:1024`, `h:512`, `max:2048` or `50%`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let spec = s.trim().to_lowercase();
+        let invalid = || {
+            format!(
+                "Invalid target size '{}' (expected e.g. 1024, w:1024, h:512, max:2048 or 50%)",
+                s.trim()
+            )
+        };
+        let size = |value: &str| value.trim().parse::<u32>().map_err(|_| invalid());
+
+        let parsed = if let Some(percent) = spec.strip_suffix('%') {
+            SizeSpec::Scale(percent.trim().parse::<f32>().map_err(|_| invalid())?)
+        } else if let Some(width) = spec.strip_prefix("w:") {
+            SizeSpec::Width(size(width)?)
+        } else if let Some(height) = spec.strip_prefix("h:") {
+            SizeSpec::Height(size(height)?)
+        } else if let Some(max) = spec.strip_prefix("max:") {
+            SizeSpec::Max(size(max)?)
+        } else {
+            SizeSpec::Width(size(&spec)?)
+        };
+        parsed.validated()
+    }
+}
+
+impl std::fmt::Display for SizeSpec {
+    /// Formats the `SizeSpec` in the syntax it is parsed from.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SizeSpec::Width(width) => write!(f, "{}", width),
+            SizeSpec::Height(height) => write!(f, "h:{}", height),
+            SizeSpec::Max(max) => write!(f, "max:{}", max),
+            SizeSpec::Scale(percent) => write!(f, "{}%", percent),
+        }
+    }
+}
+
+impl From<SizeSpec> for String {
+    /// Converts the `SizeSpec` to its display form, which is how it is serialized.
+    fn from(spec: SizeSpec) -> Self {
+        spec.to_string()
+    }
+}
+
+/// Policy for making target dimensions powers of two.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Pow2Policy {
+    /// Keep the dimensions derived from the size spec.
+    None,
+
+    /// Round each dimension up to the next power of two.
+    Up,
+
+    /// Round each dimension down to the previous power of two.
+    Down,
+
+    /// Round each dimension to the closest power of two.
+    Nearest,
+
+    /// Keep the resized image and pad the canvas to the next power of two.
+    Pad,
+}
+
+impl Pow2Policy {
+    /// Applies the rounding policies to a single dimension; `None` and `Pad` keep it.
+    pub fn round(&self, size: u32) -> u32 {
+        let up = size.max(1).next_power_of_two();
+        let down = if up == size { up } else { up / 2 };
+        match self {
+            Pow2Policy::None | Pow2Policy::Pad => size,
+            Pow2Policy::Up => up,
+            Pow2Policy::Down => down.max(1),
+            Pow2Policy::Nearest => {
+                if size - down < up - size {
+                    down.max(1)
+                } else {
+                    up
+                }
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for Pow2Policy {
+    type Err = String;
+
+    /// Parses a power-of-two policy (`none`, `up`, `down`, `nearest` or `pad`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "none" => Ok(Pow2Policy::None),
+            "up" => Ok(Pow2Policy::Up),
+            "down" => Ok(Pow2Policy::Down),
+            "nearest" => Ok(Pow2Policy::Nearest),
+            "pad" => Ok(Pow2Policy::Pad),
+            _ => Err(format!(
+                "Invalid power-of-two policy '{}' (expected none, up, down, nearest or pad)",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for Pow2Policy {
+    /// Formats the `Pow2Policy` for display purposes.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Pow2Policy::None => write!(f, "None"),
+            Pow2Policy::Up => write!(f, "Up"),
+            Pow2Policy::Down => write!(f, "Down"),
+            Pow2Policy::Nearest => write!(f, "Nearest"),
+            Pow2Policy::Pad => write!(f, "Pad"),
+        }
+    }
+}
+
+/// Policy for target sizes larger than the source image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpscalePolicy {
+    /// Do not write an output for the target size.
+    Skip,
+
+    /// Write the output at the source size, under the target size's file name.
+    Clamp,
+
+    /// Enlarge the image to the target size with the upscale filter.
+    Upscale,
+}
+
+impl std::str::FromStr for UpscalePolicy {
+    type Err = String;
+
+    /// Parses an upscale policy (`skip`, `clamp` or `upscale`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "skip" => Ok(UpscalePolicy::Skip),
+            "clamp" => Ok(UpscalePolicy::Clamp),
+            "upscale" => Ok(UpscalePolicy::Upscale),
+            _ => Err(format!(
+                "Invalid upscale policy '{}' (expected skip, clamp or upscale)",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for UpscalePolicy {
+    /// Formats the `UpscalePolicy` for display purposes.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            UpscalePolicy::Skip => write!(f, "Skip"),
+            UpscalePolicy::Clamp => write!(f, "Clamp"),
+            UpscalePolicy::Upscale => write!(f, "Upscale"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_every_form() {
+        assert_eq!("1024".parse(), Ok(SizeSpec::Width(1024)));
+        assert_eq!(" W:1024 ".parse(), Ok(SizeSpec::Width(1024)));
+        assert_eq!("h:512".parse(), Ok(SizeSpec::Height(512)));
+        assert_eq!("max:2048".parse(), Ok(SizeSpec::Max(2048)));
+        assert_eq!("50%".parse(), Ok(SizeSpec::Scale(50.0)));
+    }
+
+    #[test]
+    fn rejects_invalid_specs() {
+        for spec in ["0", "h:0", "0%", "-5%", "inf%", "x:512", "max:", "big"] {
+            assert!(spec.parse::<SizeSpec>().is_err(), "{}", spec);
+        }
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for spec in [
+            SizeSpec::Width(1024),
+            SizeSpec::Height(512),
+            SizeSpec::Max(2048),
+            SizeSpec::Scale(12.5),
+        ] {
+            assert_eq!(spec.to_string().parse(), Ok(spec));
+        }
+    }
+
+    #[test]
+    fn deserializes_numbers_and_strings() {
+        #[derive(Deserialize)]
+        struct Sizes {
+            sizes: Vec<SizeSpec>,
+        }
+        let sizes: Sizes = toml::from_str("sizes = [1024, \"h:512\", \"50%\"]").unwrap();
+        assert_eq!(
+            sizes.sizes,
+            [
+                SizeSpec::Width(1024),
+                SizeSpec::Height(512),
+                SizeSpec::Scale(50.0)
+            ]
+        );
+        assert!(toml::from_str::<Sizes>("sizes = [0]").is_err());
+        assert_eq!(serde_json::to_string(&sizes.sizes[1]).unwrap(), "\"h:512\"");
+    }
+
+    #[test]
+    fn names_outputs_and_folders() {
+        assert_eq!(SizeSpec::Width(1024).suffix(), "1024");
+        assert_eq!(SizeSpec::Max(2048).suffix(), "max2048");
+        assert_eq!(SizeSpec::Scale(50.0).suffix(), "50pct");
+        assert_eq!(SizeSpec::Width(2048).folder_name(), "2K");
+        assert_eq!(SizeSpec::Width(1536).folder_name(), "1536");
+        assert_eq!(SizeSpec::Height(1024).folder_name(), "h1K");
+    }
+
+    #[test]
+    fn keeps_the_aspect_ratio() {
+        assert_eq!(SizeSpec::Width(512).dimensions(2048, 1024), (512, 256));
+        assert_eq!(SizeSpec::Height(512).dimensions(2048, 1024), (1024, 512));
+        assert_eq!(SizeSpec::Max(512).dimensions(1024, 2048), (256, 512));
+        assert_eq!(SizeSpec::Scale(50.0).dimensions(300, 100), (150, 50));
+        assert_eq!(SizeSpec::Width(4).dimensions(4096, 2), (4, 1));
+    }
+
+    #[test]
+    fn rounds_to_powers_of_two() {
+        assert_eq!(Pow2Policy::Up.round(600), 1024);
+        assert_eq!(Pow2Policy::Down.round(600), 512);
+        assert_eq!(Pow2Policy::Nearest.round(600), 512);
+        assert_eq!(Pow2Policy::Nearest.round(800), 1024);
+        assert_eq!(Pow2Policy::Up.round(512), 512);
+        assert_eq!(Pow2Policy::Down.round(0), 1);
+        assert_eq!(Pow2Policy::Pad.round(600), 600);
+    }
+
+    #[test]
+    fn policies_round_trip() {
+        for policy in [
+            Pow2Policy::None,
+            Pow2Policy::Up,
+            Pow2Policy::Down,
+            Pow2Policy::Nearest,
+            Pow2Policy::Pad,
+        ] {
+            assert_eq!(policy.to_string().parse(), Ok(policy));
+        }
+        for policy in [
+            UpscalePolicy::Skip,
+            UpscalePolicy::Clamp,
+            UpscalePolicy::Upscale,
+        ] {
+            assert_eq!(policy.to_string().parse(), Ok(policy));
+        }
+        assert!("sideways".parse::<Pow2Policy>().is_err());
+        assert!("stretch".parse::<UpscalePolicy>().is_err());
+    }
+}
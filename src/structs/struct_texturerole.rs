@@ -0,0 +1,190 @@
+// D:/Work/Projects/Modding/Skyrim/dds-edit/src/structs/struct_texturerole.rs
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Enumeration of texture roles recognised from Bethesda file name suffixes.
+///
+/// The role decides which DDS format a texture is compressed to and whether
+/// its contents are treated as color or as raw data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextureRole {
+    /// Base color texture (no suffix, `_d`).
+    Diffuse,
+
+    /// Tangent or model space normal map (`_n`, `_msn`).
+    Normal,
+
+    /// Emissive glow map (`_g`, `_glow`).
+    Glow,
+
+    /// Height or parallax map (`_h`, `_p`).
+    Height,
+
+    /// Environment mask (`_m`, `_em`).
+    EnvMask,
+
+    /// Specular map (`_s`, `_spec`).
+    Specular,
+
+    /// Environment cubemap (`_e`).
+    Cubemap,
+
+    /// Skin tint map (`_sk`).
+    SkinTint,
+
+    /// Backlight map (`_b`, `_bl`).
+    Backlight,
+}
+
+/// Built-in suffix table following the Bethesda naming conventions.
+const ROLE_SUFFIXES: &[(&str, TextureRole)] = &[
+    ("d", TextureRole::Diffuse),
+    ("n", TextureRole::Normal),
+    ("msn", TextureRole::Normal),
+    ("g", TextureRole::Glow),
+    ("glow", TextureRole::Glow),
+    ("h", TextureRole::Height),
+    ("p", TextureRole::Height),
+    ("m", TextureRole::EnvMask),
+    ("em", TextureRole::EnvMask),
+    ("s", TextureRole::Specular),
+    ("spec", TextureRole::Specular),
+    ("e", TextureRole::Cubemap),
+    ("sk", TextureRole::SkinTint),
+    ("b", TextureRole::Backlight),
+    ("bl", TextureRole::Backlight),
+];
+
+impl TextureRole {
+    /// Classifies a texture by the suffix after the last underscore of its file name.
+    ///
+    /// Suffixes in `overrides` take precedence over the built-in table. Keys may be
+    /// written with or without the leading underscore (`"_n"` or `"n"`).
+    ///
+    /// # Arguments
+    ///
+    /// * `file_name` - File name without extension (e.g., `Daedric_DaggerA_h`).
+    /// * `overrides` - Additional suffix to role mappings from the configuration.
+    ///
+    /// # Returns
+    ///
+    /// * `TextureRole` - The matched role, or `Diffuse` if no suffix matches.
+    pub fn classify(file_name: &str, overrides: &HashMap<String, TextureRole>) -> Self {
+        let suffix = match file_name.rsplit_once('_') {
+            Some((_, suffix)) => suffix.to_lowercase(),
+            None => return TextureRole::Diffuse,
+        };
+
+        // Check the configured overrides first.
+        for (key, role) in overrides {
+            if key.trim_start_matches('_').to_lowercase() == suffix {
+                return *role;
+            }
+        }
+
+        ROLE_SUFFIXES
+            .iter()
+            .find(|(key, _)| *key == suffix)
+            .map(|(_, role)| *role)
+            .unwrap_or(TextureRole::Diffuse)
+    }
+
+    /// Returns `true` if the texture stores color data rather than raw values.
+    pub fn is_color(&self) -> bool {
+        matches!(
+            self,
+            TextureRole::Diffuse
+                | TextureRole::Glow
+                | TextureRole::Cubemap
+                | TextureRole::SkinTint
+                | TextureRole::Backlight
+        )
+    }
+}
+
+impl std::fmt::Display for TextureRole {
+    /// Formats the `TextureRole` for display purposes.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The formatter.
+    ///
+    /// # Returns
+    ///
+    /// * `std::fmt::Result` - The result of the formatting operation.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TextureRole::Diffuse => write!(f, "Diffuse"),
+            TextureRole::Normal => write!(f, "Normal"),
+            TextureRole::Glow => write!(f, "Glow"),
+            TextureRole::Height => write!(f, "Height"),
+            TextureRole::EnvMask => write!(f, "EnvMask"),
+            TextureRole::Specular => write!(f, "Specular"),
+            TextureRole::Cubemap => write!(f, "Cubemap"),
+            TextureRole::SkinTint => write!(f, "SkinTint"),
+            TextureRole::Backlight => write!(f, "Backlight"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_bethesda_suffixes() {
+        let none = HashMap::new();
+        assert_eq!(
+            TextureRole::classify("Daedric_DaggerA_h", &none),
+            TextureRole::Height
+        );
+        assert_eq!(
+            TextureRole::classify("armor_MSN", &none),
+            TextureRole::Normal
+        );
+        assert_eq!(
+            TextureRole::classify("armor_spec", &none),
+            TextureRole::Specular
+        );
+        assert_eq!(TextureRole::classify("armor", &none), TextureRole::Diffuse);
+        assert_eq!(
+            TextureRole::classify("armor_old", &none),
+            TextureRole::Diffuse
+        );
+    }
+
+    #[test]
+    fn overrides_take_precedence() {
+        let overrides = HashMap::from([
+            ("_nm".to_string(), TextureRole::Normal),
+            ("s".to_string(), TextureRole::EnvMask),
+        ]);
+        assert_eq!(
+            TextureRole::classify("armor_nm", &overrides),
+            TextureRole::Normal
+        );
+        assert_eq!(
+            TextureRole::classify("armor_S", &overrides),
+            TextureRole::EnvMask
+        );
+        assert_eq!(
+            TextureRole::classify("armor_n", &overrides),
+            TextureRole::Normal
+        );
+    }
+
+    #[test]
+    fn deserializes_lowercase_names() {
+        #[derive(Deserialize)]
+        struct Settings {
+            role: TextureRole,
+        }
+        let settings: Settings = toml::from_str("role = \"envmask\"").unwrap();
+        assert_eq!(settings.role, TextureRole::EnvMask);
+        assert!(toml::from_str::<Settings>("role = \"albedo\"").is_err());
+        assert!(TextureRole::SkinTint.is_color());
+        assert!(!TextureRole::Normal.is_color());
+    }
+}